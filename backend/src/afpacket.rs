@@ -0,0 +1,236 @@
+use std::io;
+use std::mem;
+use std::net::IpAddr;
+use std::os::fd::{AsRawFd, FromRawFd, OwnedFd};
+
+use pnet::datalink::NetworkInterface;
+
+use crate::bpf::Instruction;
+use crate::interfaces::{self, CaptureOptions};
+use crate::source::{now_timestamp, Frame, KernelStats, LinkType, PacketSource, POLL_INTERVAL};
+
+// Sentezlenen SLL2 başlığı: protokol (2), ayrılmış (2), arayüz sırası (4),
+// ARPHRD türü (2), paket türü (1), adres uzunluğu (1), adres (8)
+const SLL2_HEADER_LEN: usize = 20;
+
+/// Linux AF_PACKET soketi üzerinden canlı arayüz. pnet kanalı soketi dışarı
+/// açmadığından çekirdek filtresi ya da alıcı tampon boyutu gerektiğinde
+/// ve `any` sözde arayüzünde bu kaynak kullanılır.
+pub struct AfPacketSource {
+    name: String,
+    addrs: Vec<IpAddr>,
+    socket: OwnedFd,
+    link_type: LinkType,
+    buffer: Vec<u8>,
+    stats: KernelStats,
+}
+
+impl AfPacketSource {
+    /// Arayüze bağlı soket açar ve varsa filtre programını ekler. Filtre
+    /// eklenemezse hata döner; çağıran kullanıcı alanı süzmeye geri düşer.
+    pub fn open(
+        interface: &NetworkInterface,
+        options: &CaptureOptions,
+        program: Option<&[Instruction]>,
+    ) -> io::Result<Self> {
+        // Tüm arayüzlerde bağlantı başlıkları farklı olabileceğinden
+        // çekirdek başlığı kaldırır (SOCK_DGRAM); yerine SLL2 başlığı yazılır
+        let link_type = interfaces::link_type(interface);
+        let cooked = link_type == LinkType::LinuxSll2;
+        let socket = open_socket(if cooked { libc::SOCK_DGRAM } else { libc::SOCK_RAW })?;
+        let fd = socket.as_raw_fd();
+
+        if let Some(program) = program {
+            attach_filter(fd, program)?;
+        }
+
+        let timeout = libc::timeval {
+            tv_sec: POLL_INTERVAL.as_secs() as libc::time_t,
+            tv_usec: POLL_INTERVAL.subsec_micros() as libc::suseconds_t,
+        };
+        set_option(fd, libc::SOL_SOCKET, libc::SO_RCVTIMEO, &timeout)?;
+        if let Some(buffer_size) = options.buffer_size {
+            let buffer_size = buffer_size.min(libc::c_int::MAX as usize) as libc::c_int;
+            set_option(fd, libc::SOL_SOCKET, libc::SO_RCVBUF, &buffer_size)?;
+        }
+        if cooked && options.promiscuous {
+            println!("{} arayüzünde karışık mod desteklenmiyor", interface.name);
+        }
+        bind(fd, interface, options.promiscuous && !cooked)?;
+
+        Ok(Self {
+            name: interface.name.clone(),
+            addrs: interface.ips.iter().map(|network| network.ip()).collect(),
+            socket,
+            link_type,
+            // Tampondan uzun çerçeveler `recv` tarafından kesilir
+            buffer: vec![0; options.snaplen + if cooked { SLL2_HEADER_LEN } else { 0 }],
+            stats: KernelStats::default(),
+        })
+    }
+}
+
+impl PacketSource for AfPacketSource {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn link_type(&self) -> LinkType {
+        self.link_type
+    }
+
+    fn local_addrs(&self) -> Vec<IpAddr> {
+        self.addrs.clone()
+    }
+
+    fn next_frame(&mut self) -> io::Result<Option<Frame<'_>>> {
+        if self.link_type == LinkType::LinuxSll2 {
+            return self.next_cooked_frame();
+        }
+
+        let len = unsafe {
+            libc::recv(
+                self.socket.as_raw_fd(),
+                self.buffer.as_mut_ptr() as *mut libc::c_void,
+                self.buffer.len(),
+                0,
+            )
+        };
+        if len < 0 {
+            return Err(io::Error::last_os_error());
+        }
+
+        Ok(Some(Frame {
+            timestamp: now_timestamp(),
+            link_type: self.link_type,
+            data: &self.buffer[..len as usize],
+        }))
+    }
+
+    fn kernel_stats(&mut self) -> Option<KernelStats> {
+        let stats: libc::tpacket_stats = statistics(self.socket.as_raw_fd()).ok()?;
+        self.stats.add(stats.tp_packets, stats.tp_drops, 0);
+        Some(self.stats)
+    }
+}
+
+impl AfPacketSource {
+    /// Ağ katmanından başlayan paketi okur ve önüne gönderen adresinden
+    /// SLL2 başlığını yazar.
+    fn next_cooked_frame(&mut self) -> io::Result<Option<Frame<'_>>> {
+        let mut address: libc::sockaddr_ll = unsafe { mem::zeroed() };
+        let mut address_len = mem::size_of::<libc::sockaddr_ll>() as libc::socklen_t;
+        let payload = &mut self.buffer[SLL2_HEADER_LEN..];
+        let len = unsafe {
+            libc::recvfrom(
+                self.socket.as_raw_fd(),
+                payload.as_mut_ptr() as *mut libc::c_void,
+                payload.len(),
+                0,
+                &mut address as *mut libc::sockaddr_ll as *mut libc::sockaddr,
+                &mut address_len,
+            )
+        };
+        if len < 0 {
+            return Err(io::Error::last_os_error());
+        }
+
+        let header = &mut self.buffer[..SLL2_HEADER_LEN];
+        // `sll_protocol` zaten ağ bayt sırasında
+        header[0..2].copy_from_slice(&address.sll_protocol.to_ne_bytes());
+        header[2..4].fill(0);
+        header[4..8].copy_from_slice(&(address.sll_ifindex as u32).to_be_bytes());
+        header[8..10].copy_from_slice(&address.sll_hatype.to_be_bytes());
+        header[10] = address.sll_pkttype;
+        header[11] = address.sll_halen.min(8);
+        header[12..20].copy_from_slice(&address.sll_addr);
+
+        Ok(Some(Frame {
+            timestamp: now_timestamp(),
+            link_type: self.link_type,
+            data: &self.buffer[..SLL2_HEADER_LEN + len as usize],
+        }))
+    }
+}
+
+/// Paket almayan AF_PACKET soketi açar. Protokol 0 ile açılan soket
+/// `bind` edilene kadar paket almaz; böylece filtre ve halka kurulmadan
+/// süzülmemiş paket kuyruğa girmez.
+/// `kind` bağlantı başlığını da alan `SOCK_RAW` ya da ağ katmanından
+/// başlayan `SOCK_DGRAM` olabilir.
+pub fn open_socket(kind: libc::c_int) -> io::Result<OwnedFd> {
+    let socket = check(unsafe { libc::socket(libc::AF_PACKET, kind | libc::SOCK_CLOEXEC, 0) })?;
+    Ok(unsafe { OwnedFd::from_raw_fd(socket) })
+}
+
+/// Soketi arayüzün tüm protokollerine bağlar ve istenirse karışık moda
+/// geçirir.
+pub fn bind(fd: libc::c_int, interface: &NetworkInterface, promiscuous: bool) -> io::Result<()> {
+    let mut address: libc::sockaddr_ll = unsafe { mem::zeroed() };
+    address.sll_family = libc::AF_PACKET as u16;
+    address.sll_protocol = (libc::ETH_P_ALL as u16).to_be();
+    address.sll_ifindex = interface.index as i32;
+    check(unsafe {
+        libc::bind(
+            fd,
+            &address as *const libc::sockaddr_ll as *const libc::sockaddr,
+            mem::size_of::<libc::sockaddr_ll>() as libc::socklen_t,
+        )
+    })?;
+    if !promiscuous {
+        return Ok(());
+    }
+
+    let mut membership: libc::packet_mreq = unsafe { mem::zeroed() };
+    membership.mr_ifindex = interface.index as i32;
+    membership.mr_type = libc::PACKET_MR_PROMISC as u16;
+    set_option(fd, libc::SOL_PACKET, libc::PACKET_ADD_MEMBERSHIP, &membership)
+}
+
+/// Çekirdeğin son okumadan bu yana tuttuğu sayaçlar; okuma sayaçları sıfırlar.
+pub fn statistics<T>(fd: libc::c_int) -> io::Result<T> {
+    let mut value: T = unsafe { mem::zeroed() };
+    let mut len = mem::size_of::<T>() as libc::socklen_t;
+    check(unsafe {
+        libc::getsockopt(
+            fd,
+            libc::SOL_PACKET,
+            libc::PACKET_STATISTICS,
+            &mut value as *mut T as *mut libc::c_void,
+            &mut len,
+        )
+    })?;
+    Ok(value)
+}
+
+pub fn attach_filter(fd: libc::c_int, program: &[Instruction]) -> io::Result<()> {
+    let mut filters: Vec<libc::sock_filter> = program
+        .iter()
+        .map(|ins| libc::sock_filter { code: ins.code, jt: ins.jt, jf: ins.jf, k: ins.k })
+        .collect();
+    let len = u16::try_from(filters.len())
+        .map_err(|_| io::Error::new(io::ErrorKind::InvalidInput, "BPF programı çok uzun"))?;
+    let program = libc::sock_fprog { len, filter: filters.as_mut_ptr() };
+    set_option(fd, libc::SOL_SOCKET, libc::SO_ATTACH_FILTER, &program)
+}
+
+pub fn set_option<T>(fd: libc::c_int, level: libc::c_int, name: libc::c_int, value: &T) -> io::Result<()> {
+    check(unsafe {
+        libc::setsockopt(
+            fd,
+            level,
+            name,
+            value as *const T as *const libc::c_void,
+            mem::size_of::<T>() as libc::socklen_t,
+        )
+    })
+    .map(|_| ())
+}
+
+pub fn check(result: libc::c_int) -> io::Result<libc::c_int> {
+    if result < 0 {
+        Err(io::Error::last_os_error())
+    } else {
+        Ok(result)
+    }
+}
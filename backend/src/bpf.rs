@@ -0,0 +1,659 @@
+use std::collections::HashMap;
+use std::fmt::Write;
+use std::net::IpAddr;
+
+use crate::filter::{Expr, Filter, Predicate, Side};
+
+// Klasik BPF komut kodları (linux/filter.h)
+const LD_W_ABS: u16 = 0x20;
+const LD_H_ABS: u16 = 0x28;
+const LD_B_ABS: u16 = 0x30;
+const LD_H_IND: u16 = 0x48;
+const LDX_B_MSH: u16 = 0xb1;
+const ALU_AND_K: u16 = 0x54;
+const JMP_JA: u16 = 0x05;
+const JMP_JEQ_K: u16 = 0x15;
+const JMP_JGT_K: u16 = 0x25;
+const JMP_JGE_K: u16 = 0x35;
+const JMP_JSET_K: u16 = 0x45;
+const RET_K: u16 = 0x06;
+
+// Kabul edilen paketten alınacak en fazla bayt
+const ACCEPT_SNAPLEN: u32 = 262_144;
+// Klasik BPF'te koşullu atlama en fazla 255 komut ileri gidebilir
+const MAX_JUMP: usize = u8::MAX as usize;
+
+const ETHERTYPE_IPV4: u32 = 0x0800;
+const ETHERTYPE_IPV6: u32 = 0x86dd;
+const ETHERTYPE_VLAN: [u32; 3] = [0x8100, 0x88a8, 0x9100];
+// Tünelleri kullanıcı alanı açtığından dış başlıkları çekirdekte elenmemeli
+const TUNNEL_PROTOCOLS: [u32; 3] = [4, 41, 47];
+const TUNNEL_UDP_PORTS: [u32; 3] = [4789, 8472, 6081];
+// Ad çözümleme açıksa filtreden bağımsız olarak DNS yanıtları da okunur
+const DNS_PORT: u32 = 53;
+
+// Ethernet başlığından sonraki alanların konumları
+const IPV4_FLAGS_OFFSET: u32 = 20;
+const IPV4_PROTOCOL: u32 = 23;
+const IPV4_SRC: u32 = 26;
+const IPV4_DST: u32 = 30;
+const IPV6_NEXT_HEADER: u32 = 20;
+const IPV6_SRC: u32 = 22;
+const IPV6_DST: u32 = 38;
+const IPV6_PAYLOAD: u32 = 54;
+// Taşıma katmanı başlığı içindeki port alanları
+const SRC_PORT: u32 = 0;
+const DST_PORT: u32 = 2;
+
+/// Tek bir klasik BPF komutu (`struct sock_filter`)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Instruction {
+    pub code: u16,
+    pub jt: u8,
+    pub jf: u8,
+    pub k: u32,
+}
+
+/// Filtre ifadesinden derlenmiş, Ethernet çerçeveleri üzerinde çalışan
+/// çekirdek programı
+#[derive(Debug, Clone)]
+pub struct KernelFilter {
+    pub program: Vec<Instruction>,
+    /// `false` ise ifadenin bir kısmı (ülke, yön, tünel ...) çekirdekte
+    /// ifade edilemedi; program daha geniş bir kümeyi kabul eder ve kesin
+    /// eleme kullanıcı alanında yapılır
+    pub exact: bool,
+}
+
+/// İfadeyi çekirdek filtresine derler. Çekirdekte hiçbir koşul ifade
+/// edilemiyorsa ya da program sınırları aşılıyorsa `None` döner; bu
+/// durumda filtre yalnızca kullanıcı alanında uygulanır. `dns` ise
+/// kaynak portu 53 olan TCP ve UDP paketleri her durumda kabul edilir.
+pub fn compile(filter: &Filter, dns: bool) -> Result<Option<KernelFilter>, String> {
+    let (cond, exact) = approximate(filter.expr());
+    if cond == Cond::True {
+        return Ok(None);
+    }
+
+    let mut compiler = Compiler::default();
+    let accept = compiler.label();
+    let reject = compiler.label();
+    let v4 = compiler.label();
+    let v6 = compiler.label();
+
+    // Ağ katmanı türü; IP dışı çerçeveler zaten çözümlenmez. VLAN etiketli
+    // çerçevelerde konumlar kaydığından karar kullanıcı alanına bırakılır.
+    compiler.load(LD_H_ABS, 12);
+    compiler.jump_eq(ETHERTYPE_IPV4, v4, Target::Next);
+    compiler.jump_eq(ETHERTYPE_IPV6, v6, Target::Next);
+    for ethertype in ETHERTYPE_VLAN {
+        compiler.jump_eq(ethertype, accept, Target::Next);
+    }
+    compiler.jump(reject);
+
+    // IPv4: parçalar, ICMP (akışlara bağlanan hatalar) ve tüneller her
+    // durumda kullanıcı alanına gider
+    compiler.place(v4);
+    compiler.load(LD_H_ABS, IPV4_FLAGS_OFFSET);
+    compiler.emit(JMP_JSET_K, 0x3fff, Target::Label(accept), Target::Next);
+    compiler.load(LD_B_ABS, IPV4_PROTOCOL);
+    compiler.jump_eq(1, accept, Target::Next);
+    for protocol in TUNNEL_PROTOCOLS {
+        compiler.jump_eq(protocol, accept, Target::Next);
+    }
+    let v4_main = compiler.label();
+    let v4_dns = compiler.label();
+    if dns {
+        compiler.jump_eq(6, v4_dns, Target::Next);
+    }
+    compiler.jump_eq(17, Target::Next, v4_main);
+    compiler.load_port(Family::V4, DST_PORT);
+    for port in TUNNEL_UDP_PORTS {
+        compiler.jump_eq(port, accept, Target::Next);
+    }
+    if dns {
+        compiler.place(v4_dns);
+        compiler.load_port(Family::V4, SRC_PORT);
+        compiler.jump_eq(DNS_PORT, accept, Target::Next);
+    }
+    compiler.place(v4_main);
+    compiler.cond(&cond, Family::V4, accept, reject);
+
+    // IPv6: uzantı başlığı taşıyan paketler ve ICMPv6 kullanıcı alanına
+    compiler.place(v6);
+    let v6_main = compiler.label();
+    let v6_udp = compiler.label();
+    let v6_dns = compiler.label();
+    compiler.load(LD_B_ABS, IPV6_NEXT_HEADER);
+    compiler.jump_eq(6, if dns { v6_dns } else { v6_main }, Target::Next);
+    compiler.jump_eq(17, v6_udp, Target::Label(accept));
+    compiler.place(v6_udp);
+    compiler.load_port(Family::V6, DST_PORT);
+    for port in TUNNEL_UDP_PORTS {
+        compiler.jump_eq(port, accept, Target::Next);
+    }
+    if dns {
+        compiler.place(v6_dns);
+        compiler.load_port(Family::V6, SRC_PORT);
+        compiler.jump_eq(DNS_PORT, accept, Target::Next);
+    }
+    compiler.place(v6_main);
+    compiler.cond(&cond, Family::V6, accept, reject);
+
+    compiler.place(accept);
+    compiler.emit(RET_K, ACCEPT_SNAPLEN, Target::Next, Target::Next);
+    compiler.place(reject);
+    compiler.emit(RET_K, 0, Target::Next, Target::Next);
+
+    Ok(Some(KernelFilter { program: compiler.finish()?, exact }))
+}
+
+/// Programı `tcpdump -d` biçiminde yazar.
+pub fn disassemble(program: &[Instruction]) -> String {
+    let mut out = String::new();
+    for (i, ins) in program.iter().enumerate() {
+        let next = i + 1;
+        let text = match ins.code {
+            LD_W_ABS => format!("ld       [{}]", ins.k),
+            LD_H_ABS => format!("ldh      [{}]", ins.k),
+            LD_B_ABS => format!("ldb      [{}]", ins.k),
+            LD_H_IND => format!("ldh      [x + {}]", ins.k),
+            LDX_B_MSH => format!("ldxb     4*([{}]&0xf)", ins.k),
+            ALU_AND_K => format!("and      #0x{:x}", ins.k),
+            JMP_JA => format!("ja       {}", next + ins.k as usize),
+            JMP_JEQ_K | JMP_JGT_K | JMP_JGE_K | JMP_JSET_K => {
+                let name = match ins.code {
+                    JMP_JEQ_K => "jeq",
+                    JMP_JGT_K => "jgt",
+                    JMP_JGE_K => "jge",
+                    _ => "jset",
+                };
+                format!(
+                    "{:<8} #0x{:<14x}jt {}\tjf {}",
+                    name, ins.k, next + ins.jt as usize, next + ins.jf as usize
+                )
+            }
+            RET_K => format!("ret      #{}", ins.k),
+            code => format!("??? 0x{:04x} {}", code, ins.k),
+        };
+        let _ = writeln!(out, "({:03}) {}", i, text);
+    }
+    out
+}
+
+/// Çekirdekte değerlendirilebilen koşul ağacı
+#[derive(Debug, Clone, PartialEq)]
+enum Cond {
+    True,
+    And(Box<Cond>, Box<Cond>),
+    Or(Box<Cond>, Box<Cond>),
+    Not(Box<Cond>),
+    Predicate(Predicate),
+}
+
+/// İfadeyi, çekirdekte ifade edilemeyen koşulları "her zaman doğru" kabul
+/// ederek genişletir. Sonuç orijinal ifadenin kabul ettiği her paketi
+/// kabul eder; ikinci değer bu genişletmenin gerekip gerekmediğini söyler.
+fn approximate(expr: &Expr) -> (Cond, bool) {
+    match expr {
+        Expr::And(left, right) => {
+            let (left, left_exact) = approximate(left);
+            let (right, right_exact) = approximate(right);
+            let cond = match (left, right) {
+                (Cond::True, other) | (other, Cond::True) => other,
+                (left, right) => Cond::And(Box::new(left), Box::new(right)),
+            };
+            (cond, left_exact && right_exact)
+        }
+        Expr::Or(left, right) => {
+            let (left, left_exact) = approximate(left);
+            let (right, right_exact) = approximate(right);
+            let cond = match (left, right) {
+                (Cond::True, _) | (_, Cond::True) => Cond::True,
+                (left, right) => Cond::Or(Box::new(left), Box::new(right)),
+            };
+            (cond, left_exact && right_exact)
+        }
+        Expr::Not(inner) => match approximate(inner) {
+            (inner, true) => (Cond::Not(Box::new(inner)), true),
+            // Genişletilmiş koşulun tersi daraltır; güvenli olan her şeyi kabul
+            (_, false) => (Cond::True, false),
+        },
+        Expr::Predicate(predicate) => match predicate {
+            Predicate::Protocol(_) | Predicate::Ipv4 | Predicate::Ipv6 => (Cond::Predicate(predicate.clone()), true),
+            // Kullanıcı alanı yönü akışı başlatan uca göre belirler; çekirdek
+            // tek tek paketleri gördüğünden iki yön de kabul edilmeli
+            Predicate::Host(side, ip) => any_side(*side, Predicate::Host(Side::Any, *ip)),
+            Predicate::Net(side, net, prefix) => any_side(*side, Predicate::Net(Side::Any, *net, *prefix)),
+            Predicate::PortRange(side, low, high) => {
+                any_side(*side, Predicate::PortRange(Side::Any, *low, *high))
+            }
+            _ => (Cond::True, false),
+        },
+    }
+}
+
+fn any_side(side: Side, predicate: Predicate) -> (Cond, bool) {
+    (Cond::Predicate(predicate), side == Side::Any)
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Family {
+    V4,
+    V6,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+struct Label(usize);
+
+#[derive(Debug, Clone, Copy)]
+enum Target {
+    Next,
+    Label(Label),
+}
+
+impl From<Label> for Target {
+    fn from(label: Label) -> Self {
+        Target::Label(label)
+    }
+}
+
+struct Op {
+    code: u16,
+    k: u32,
+    jt: Target,
+    jf: Target,
+}
+
+#[derive(Default)]
+struct Compiler {
+    ops: Vec<Op>,
+    labels: HashMap<Label, usize>,
+    next_label: usize,
+}
+
+impl Compiler {
+    fn label(&mut self) -> Label {
+        self.next_label += 1;
+        Label(self.next_label)
+    }
+
+    fn place(&mut self, label: Label) {
+        self.labels.insert(label, self.ops.len());
+    }
+
+    fn emit(&mut self, code: u16, k: u32, jt: Target, jf: Target) {
+        self.ops.push(Op { code, k, jt, jf });
+    }
+
+    fn load(&mut self, code: u16, k: u32) {
+        self.emit(code, k, Target::Next, Target::Next);
+    }
+
+    fn jump(&mut self, target: Label) {
+        self.emit(JMP_JA, 0, Target::Label(target), Target::Next);
+    }
+
+    fn jump_eq(&mut self, k: u32, jt: impl Into<Target>, jf: impl Into<Target>) {
+        self.emit(JMP_JEQ_K, k, jt.into(), jf.into());
+    }
+
+    /// Taşıma katmanı başlığındaki 16 bitlik alanı (port) yükler.
+    fn load_port(&mut self, family: Family, offset: u32) {
+        match family {
+            Family::V4 => {
+                self.load(LDX_B_MSH, 14);
+                self.load(LD_H_IND, 14 + offset);
+            }
+            Family::V6 => self.load(LD_H_ABS, IPV6_PAYLOAD + offset),
+        }
+    }
+
+    fn cond(&mut self, cond: &Cond, family: Family, t: Label, f: Label) {
+        match cond {
+            Cond::True => self.jump(t),
+            Cond::And(left, right) => {
+                let mid = self.label();
+                self.cond(left, family, mid, f);
+                self.place(mid);
+                self.cond(right, family, t, f);
+            }
+            Cond::Or(left, right) => {
+                let mid = self.label();
+                self.cond(left, family, t, mid);
+                self.place(mid);
+                self.cond(right, family, t, f);
+            }
+            Cond::Not(inner) => self.cond(inner, family, f, t),
+            Cond::Predicate(predicate) => self.predicate(predicate, family, t, f),
+        }
+    }
+
+    fn predicate(&mut self, predicate: &Predicate, family: Family, t: Label, f: Label) {
+        match predicate {
+            Predicate::Protocol(protocol) => {
+                let offset = if family == Family::V4 { IPV4_PROTOCOL } else { IPV6_NEXT_HEADER };
+                self.load(LD_B_ABS, offset);
+                self.jump_eq(*protocol as u32, t, f);
+            }
+            Predicate::Ipv4 => self.jump(if family == Family::V4 { t } else { f }),
+            Predicate::Ipv6 => self.jump(if family == Family::V6 { t } else { f }),
+            // Farklı aileden adres hiçbir zaman eşleşmez
+            Predicate::Host(_, ip) | Predicate::Net(_, ip, _) if ip.is_ipv4() != (family == Family::V4) => {
+                self.jump(f)
+            }
+            Predicate::Host(side, ip) => {
+                let prefix = if ip.is_ipv4() { 32 } else { 128 };
+                self.on_side(*side, t, f, |compiler, side, t, f| compiler.net(side, family, *ip, prefix, t, f));
+            }
+            Predicate::Net(side, net, prefix) => {
+                self.on_side(*side, t, f, |compiler, side, t, f| compiler.net(side, family, *net, *prefix, t, f));
+            }
+            Predicate::PortRange(side, low, high) => {
+                self.on_side(*side, t, f, |compiler, side, t, f| compiler.port(side, family, *low, *high, t, f));
+            }
+            // `approximate` bu koşulları çekirdek programına almaz
+            _ => self.jump(t),
+        }
+    }
+
+    fn on_side(&mut self, side: Side, t: Label, f: Label, mut test: impl FnMut(&mut Self, Side, Label, Label)) {
+        match side {
+            Side::Any => {
+                let mid = self.label();
+                test(self, Side::Src, t, mid);
+                self.place(mid);
+                test(self, Side::Dst, t, f);
+            }
+            side => test(self, side, t, f),
+        }
+    }
+
+    fn net(&mut self, side: Side, family: Family, net: IpAddr, prefix: u8, t: Label, f: Label) {
+        let words: Vec<u32> = match (family, net) {
+            (Family::V4, IpAddr::V4(net)) => vec![u32::from(net)],
+            (Family::V6, IpAddr::V6(net)) => net
+                .octets()
+                .chunks(4)
+                .map(|chunk| u32::from_be_bytes([chunk[0], chunk[1], chunk[2], chunk[3]]))
+                .collect(),
+            _ => return self.jump(f),
+        };
+        let base = match (family, side) {
+            (Family::V4, Side::Dst) => IPV4_DST,
+            (Family::V4, _) => IPV4_SRC,
+            (Family::V6, Side::Dst) => IPV6_DST,
+            (Family::V6, _) => IPV6_SRC,
+        };
+
+        let mut remaining = prefix as u32;
+        let mut checks = Vec::new();
+        for (i, word) in words.iter().enumerate() {
+            if remaining == 0 {
+                break;
+            }
+            let bits = remaining.min(32);
+            let mask = u32::MAX.checked_shl(32 - bits).unwrap_or(0);
+            checks.push((base + i as u32 * 4, mask, word & mask));
+            remaining -= bits;
+        }
+
+        if checks.is_empty() {
+            return self.jump(t);
+        }
+        let last = checks.len() - 1;
+        for (i, (offset, mask, value)) in checks.into_iter().enumerate() {
+            self.load(LD_W_ABS, offset);
+            if mask != u32::MAX {
+                self.load(ALU_AND_K, mask);
+            }
+            if i == last {
+                self.jump_eq(value, t, f);
+            } else {
+                self.jump_eq(value, Target::Next, f);
+            }
+        }
+    }
+
+    fn port(&mut self, side: Side, family: Family, low: u16, high: u16, t: Label, f: Label) {
+        // Port yalnızca TCP ve UDP'de anlamlı
+        let has_ports = self.label();
+        let offset = if family == Family::V4 { IPV4_PROTOCOL } else { IPV6_NEXT_HEADER };
+        self.load(LD_B_ABS, offset);
+        self.jump_eq(6, has_ports, Target::Next);
+        self.jump_eq(17, has_ports, f);
+        self.place(has_ports);
+
+        let field = if side == Side::Dst { DST_PORT } else { SRC_PORT };
+        self.load_port(family, field);
+        if low == high {
+            self.jump_eq(low as u32, t, f);
+        } else {
+            self.emit(JMP_JGE_K, low as u32, Target::Next, Target::Label(f));
+            self.emit(JMP_JGT_K, high as u32, Target::Label(f), Target::Label(t));
+        }
+    }
+
+    /// Etiketleri göreli atlamalara çevirir.
+    fn finish(self) -> Result<Vec<Instruction>, String> {
+        let resolve = |index: usize, target: Target| -> Result<usize, String> {
+            let destination = match target {
+                Target::Next => return Ok(0),
+                Target::Label(label) => *self.labels.get(&label).ok_or("yerleştirilmemiş etiket")?,
+            };
+            destination
+                .checked_sub(index + 1)
+                .ok_or_else(|| "geriye atlama".to_string())
+        };
+
+        self.ops
+            .iter()
+            .enumerate()
+            .map(|(index, op)| {
+                let jt = resolve(index, op.jt)?;
+                let jf = resolve(index, op.jf)?;
+                if op.code == JMP_JA {
+                    return Ok(Instruction { code: op.code, jt: 0, jf: 0, k: jt as u32 });
+                }
+                if jt > MAX_JUMP || jf > MAX_JUMP {
+                    return Err("filtre çekirdek programı için fazla büyük".to_string());
+                }
+                Ok(Instruction { code: op.code, jt: jt as u8, jf: jf as u8, k: op.k })
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testutil::*;
+    use std::net::{Ipv4Addr, Ipv6Addr};
+
+    /// Derlenen programı çerçeve üzerinde çekirdek gibi çalıştırır; sınır
+    /// dışı okuma paketi reddeder.
+    fn run(program: &[Instruction], frame: &[u8]) -> u32 {
+        let load = |offset: usize, len: usize| -> Option<u32> {
+            let bytes = frame.get(offset..offset + len)?;
+            Some(bytes.iter().fold(0, |value, byte| value << 8 | *byte as u32))
+        };
+        let (mut a, mut x, mut pc) = (0u32, 0u32, 0usize);
+        loop {
+            let ins = program[pc];
+            pc += 1;
+            let k = ins.k as usize;
+            let loaded = match ins.code {
+                LD_W_ABS => load(k, 4),
+                LD_H_ABS => load(k, 2),
+                LD_B_ABS => load(k, 1),
+                LD_H_IND => load(x as usize + k, 2),
+                _ => Some(a),
+            };
+            a = match loaded {
+                Some(value) => value,
+                None => return 0,
+            };
+            let jump = |taken: bool| if taken { ins.jt as usize } else { ins.jf as usize };
+            match ins.code {
+                LDX_B_MSH => match load(k, 1) {
+                    Some(byte) => x = 4 * (byte & 0xf),
+                    None => return 0,
+                },
+                ALU_AND_K => a &= ins.k,
+                JMP_JA => pc += k,
+                JMP_JEQ_K => pc += jump(a == ins.k),
+                JMP_JGT_K => pc += jump(a > ins.k),
+                JMP_JGE_K => pc += jump(a >= ins.k),
+                JMP_JSET_K => pc += jump(a & ins.k != 0),
+                RET_K => return ins.k,
+                _ => {}
+            }
+        }
+    }
+
+    fn kernel_filter(text: &str) -> Option<KernelFilter> {
+        compile(&Filter::parse(text).unwrap(), false).unwrap()
+    }
+
+    fn accepts(filter: &KernelFilter, frame: &[u8]) -> bool {
+        run(&filter.program, frame) > 0
+    }
+
+    const CLIENT: Ipv4Addr = Ipv4Addr::new(10, 0, 0, 5);
+    const SERVER: Ipv4Addr = Ipv4Addr::new(151, 101, 1, 69);
+
+    fn v4(src: Ipv4Addr, dst: Ipv4Addr, protocol: u8, transport: &[u8]) -> Vec<u8> {
+        ethernet(CLIENT_MAC, SERVER_MAC, &ipv4(src, dst, protocol, transport))
+    }
+
+    fn v4_tcp(src_port: u16, dst_port: u16) -> Vec<u8> {
+        v4(CLIENT, SERVER, 6, &tcp(src_port, dst_port, 1, 0, SYN, 65535, &[]))
+    }
+
+    fn v4_udp(src_port: u16, dst_port: u16) -> Vec<u8> {
+        v4(CLIENT, SERVER, 17, &udp(src_port, dst_port, &[0; 8]))
+    }
+
+    fn v6_tcp(src: &str, dst: &str, dst_port: u16) -> Vec<u8> {
+        let (src, dst): (Ipv6Addr, Ipv6Addr) = (src.parse().unwrap(), dst.parse().unwrap());
+        ethernet(CLIENT_MAC, SERVER_MAC, &ipv6(src, dst, 6, &tcp(50000, dst_port, 1, 0, SYN, 65535, &[])))
+    }
+
+    #[test]
+    fn protocol_and_port_run_in_kernel() {
+        let filter = kernel_filter("tcp and port 443").unwrap();
+        assert!(filter.exact);
+        assert!(accepts(&filter, &v4_tcp(50000, 443)));
+        assert!(accepts(&filter, &v4_tcp(443, 50000)));
+        assert!(!accepts(&filter, &v4_tcp(50000, 80)));
+        assert!(!accepts(&filter, &v4_udp(50000, 443)));
+        assert!(accepts(&filter, &v6_tcp("2001:470::5", "2a00:1450::1", 443)));
+        assert!(!accepts(&filter, &v6_tcp("2001:470::5", "2a00:1450::1", 80)));
+    }
+
+    #[test]
+    fn port_range_and_ipv4_options_header() {
+        let filter = kernel_filter("dst portrange 1000-2000").unwrap();
+        // Yönlü koşul çekirdekte iki yön için de kabul edilir
+        assert!(!filter.exact);
+        assert!(accepts(&filter, &v4_udp(50000, 1000)));
+        assert!(accepts(&filter, &v4_udp(2000, 50000)));
+        assert!(!accepts(&filter, &v4_udp(999, 2001)));
+
+        // Seçenekli IPv4 başlığında port konumu IHL'den hesaplanır
+        let mut packet = ipv4(CLIENT, SERVER, 17, &[]);
+        packet[0] = 0x46;
+        packet.extend_from_slice(&[1, 1, 1, 0]);
+        packet.extend(udp(50000, 1500, &[0; 8]));
+        assert!(accepts(&filter, &ethernet(CLIENT_MAC, SERVER_MAC, &packet)));
+    }
+
+    #[test]
+    fn hosts_and_nets_match_either_address() {
+        let filter = kernel_filter("not net 10.0.0.0/8").unwrap();
+        assert!(filter.exact);
+        assert!(!accepts(&filter, &v4_tcp(50000, 443)));
+        assert!(accepts(&filter, &v4(SERVER, Ipv4Addr::new(8, 8, 8, 8), 17, &udp(1, 2, &[]))));
+
+        let filter = kernel_filter("src host 151.101.1.69").unwrap();
+        assert!(!filter.exact);
+        assert!(accepts(&filter, &v4_tcp(50000, 443)));
+        assert!(!accepts(&filter, &v4(CLIENT, Ipv4Addr::new(8, 8, 8, 8), 6, &tcp(1, 2, 0, 0, SYN, 0, &[]))));
+        // Farklı ailedeki adres hiç eşleşmez
+        assert!(!accepts(&filter, &v6_tcp("2001:470::5", "2a00:1450::1", 443)));
+
+        let filter = kernel_filter("net 2a00:1450::/29").unwrap();
+        assert!(accepts(&filter, &v6_tcp("2001:470::5", "2a00:1457::1", 443)));
+        assert!(!accepts(&filter, &v6_tcp("2001:470::5", "2a00:1458::1", 443)));
+        assert!(!accepts(&filter, &v4_tcp(50000, 443)));
+    }
+
+    #[test]
+    fn fragments_icmp_tunnels_and_vlans_reach_userspace() {
+        let filter = kernel_filter("tcp and port 443").unwrap();
+        let fragment = ipv4_fragment(CLIENT, SERVER, 17, 1, 8, false, &[0; 8]);
+        assert!(accepts(&filter, &ethernet(CLIENT_MAC, SERVER_MAC, &fragment)));
+        assert!(accepts(&filter, &v4(SERVER, CLIENT, 1, &[3, 3, 0, 0, 0, 0, 0, 0])));
+        assert!(accepts(&filter, &v4(CLIENT, SERVER, 47, &[0; 4])));
+        assert!(accepts(&filter, &v4_udp(50000, 4789)));
+
+        let mut tagged = v4_udp(50000, 53);
+        tagged.splice(12..12, [0x81, 0x00, 0x00, 0x64]);
+        assert!(accepts(&filter, &tagged));
+
+        let mut arp = v4_tcp(50000, 443);
+        arp[12..14].copy_from_slice(&[0x08, 0x06]);
+        assert!(!accepts(&filter, &arp));
+    }
+
+    #[test]
+    fn dns_responses_pass_when_names_are_resolved() {
+        let without = kernel_filter("tcp and port 443").unwrap();
+        let filter = compile(&Filter::parse("tcp and port 443").unwrap(), true).unwrap().unwrap();
+        let response = v4(SERVER, CLIENT, 17, &udp(53, 50000, &[0; 8]));
+        let tcp_response = v4(SERVER, CLIENT, 6, &tcp(53, 50000, 1, 0, ACK, 65535, &[0; 8]));
+        let v6_response = ethernet(
+            SERVER_MAC,
+            CLIENT_MAC,
+            &ipv6("2001:4860:4860::8888".parse().unwrap(), "2001:470::5".parse().unwrap(), 17, &udp(53, 50000, &[0; 8])),
+        );
+        for frame in [&response, &tcp_response, &v6_response] {
+            assert!(!accepts(&without, frame));
+            assert!(accepts(&filter, frame));
+        }
+
+        // Sorgular ve diğer trafik filtreye tabi kalır
+        assert!(!accepts(&filter, &v4_udp(50000, 53)));
+        assert!(!accepts(&filter, &v4_tcp(50000, 80)));
+        assert!(accepts(&filter, &v4_tcp(50000, 443)));
+        assert!(accepts(&filter, &v4_udp(50000, 4789)));
+    }
+
+    #[test]
+    fn userspace_only_predicates_widen_the_program() {
+        assert!(kernel_filter("country == TR").is_none());
+        assert!(kernel_filter("tcp or direction == inbound").is_none());
+        assert!(kernel_filter("not country == TR").is_none());
+
+        let filter = kernel_filter("udp and not country == TR").unwrap();
+        assert!(!filter.exact);
+        assert!(accepts(&filter, &v4_udp(50000, 53)));
+        assert!(!accepts(&filter, &v4_tcp(50000, 443)));
+    }
+
+    #[test]
+    fn oversized_programs_are_rejected() {
+        let text = (1..=300).map(|port| format!("port {}", port)).collect::<Vec<_>>().join(" or ");
+        assert!(compile(&Filter::parse(&text).unwrap(), false).is_err());
+    }
+
+    #[test]
+    fn disassembles_like_tcpdump() {
+        let filter = kernel_filter("tcp").unwrap();
+        let text = disassemble(&filter.program);
+        assert_eq!(text.lines().count(), filter.program.len());
+        assert!(text.starts_with("(000) ldh      [12]\n(001) jeq      #0x800"));
+        assert!(text.contains("ret      #262144"));
+        assert!(text.trim_end().ends_with("ret      #0"));
+    }
+}
@@ -1,38 +1,49 @@
-use pnet::datalink::{self, NetworkInterface};
-use pnet::packet::ip::IpNextHeaderProtocols;
-use pnet::packet::ipv4::Ipv4Packet;
-use pnet::packet::tcp::TcpPacket;
-use pnet::packet::udp::UdpPacket;
-use pnet::packet::Packet;
-use tokio::sync::broadcast;
+use pnet::datalink::NetworkInterface;
+use pnet::packet::ip::{IpNextHeaderProtocol, IpNextHeaderProtocols};
+use pnet::packet::tcp::TcpFlags;
+use pnet::util::MacAddr;
+use tokio::sync::{broadcast, mpsc};
 use serde_json::json;
+use std::io;
 use std::net::IpAddr;
+use std::path::Path;
 use maxminddb::geoip2;
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
-use std::collections::HashSet;
 use std::time::{Duration, Instant};
 
-struct ConnectionTracker {
-    connections: HashSet<(String, String)>,
-    last_cleanup: Instant,
-}
-
-impl ConnectionTracker {
-    fn new() -> Self {
-        Self {
-            connections: HashSet::new(),
-            last_cleanup: Instant::now(),
-        }
-    }
+#[cfg(target_os = "linux")]
+use crate::afpacket::AfPacketSource;
+#[cfg(target_os = "linux")]
+use crate::bpf;
+#[cfg(target_os = "linux")]
+use crate::config::RingConfig;
+#[cfg(target_os = "linux")]
+use crate::ring::RingSource;
+use crate::config::{CaptureBackend, Config};
+use crate::decode::{self, IcmpMessage, IpPacket, Transport};
+use crate::dns::{self, DnsCache, TcpStreams};
+use crate::filter::{Filter, Subject};
+use crate::interfaces::CaptureOptions;
+use crate::flow::{Direction, EndReason, Endpoint, Flow, FlowCounters, FlowGeo, FlowKey, FlowNames, FlowTable};
+use crate::pipeline::Pipeline;
+use crate::reassembly::Reassembler;
+use crate::recorder::Recorder;
+use crate::source::{FileSource, Frame, KernelStats, LinkType, LiveSource, PacketSource, ReplaySpeed};
+use crate::stats::{Stats, Traffic, TrafficKey};
+use crate::synthetic::{SyntheticConfig, SyntheticSource};
+use crate::tcp::{TcpEvent, TcpSide, TcpState, TcpTracker};
+use crate::tls::{self, ClientHello};
+use crate::tunnel::{self, Inner, Tunnel};
 
-    fn is_new_connection(&mut self, src: &str, dst: &str) -> bool {
-        if self.last_cleanup.elapsed() > Duration::from_secs(60) {
-            self.connections.clear();
-            self.last_cleanup = Instant::now();
-        }
-        self.connections.insert((src.to_string(), dst.to_string()))
-    }
-}
+// Çözümleme thread'inden async tarafa giden olay kuyruğu
+const EVENT_QUEUE_LEN: usize = 1024;
+// Akış zaman aşımlarının kontrol aralığı (paket zamanıyla)
+const SWEEP_INTERVAL: Duration = Duration::from_secs(1);
+// İç içe tünellerde en fazla bu kadar katman açılır
+const MAX_TUNNEL_DEPTH: usize = 4;
+// Olay beklenirken kapatma isteğinin kontrol aralığı
+const STOP_POLL_INTERVAL: Duration = Duration::from_millis(250);
 
 fn should_track_ip(ip: IpAddr) -> bool {
     match ip {
@@ -48,142 +59,1720 @@ fn should_track_ip(ip: IpAddr) -> bool {
             // Özel IP'leri reddet
             !ip.is_private()
         },
-        _ => false
+        IpAddr::V6(ip) => {
+            // IPv4 eşlemeli adresler (::ffff:a.b.c.d) IPv4 kurallarına tabi
+            if let Some(v4) = ip.to_ipv4_mapped() {
+                return should_track_ip(IpAddr::V4(v4));
+            }
+            if ip.is_loopback() || ip.is_unspecified() || ip.is_multicast() {
+                return false;
+            }
+            let first = ip.segments()[0];
+            // ULA (fc00::/7), link-local (fe80::/10) ve dokümantasyon
+            // (2001:db8::/32, 3fff::/20) aralıklarını reddet
+            if first & 0xfe00 == 0xfc00 || first & 0xffc0 == 0xfe80 {
+                return false;
+            }
+            if (first == 0x2001 && ip.segments()[1] == 0x0db8) || (first == 0x3fff && ip.segments()[1] & 0xf000 == 0) {
+                return false;
+            }
+            // Yalnızca global unicast (2000::/3)
+            first & 0xe000 == 0x2000
+        }
     }
 }
 
-pub async fn start_packet_capture(tx: broadcast::Sender<String>) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
-    let reader = Arc::new(maxminddb::Reader::open_readfile("assets/GeoLite2-City.mmdb")
+type GeoIpReader = maxminddb::Reader<Vec<u8>>;
+
+fn open_geoip_reader() -> Result<GeoIpReader, Box<dyn std::error::Error + Send + Sync>> {
+    let reader = maxminddb::Reader::open_readfile("assets/GeoLite2-City.mmdb")
         .or_else(|_| maxminddb::Reader::open_readfile("../assets/GeoLite2-City.mmdb"))
         .or_else(|_| maxminddb::Reader::open_readfile("../../assets/GeoLite2-City.mmdb"))
-        .map_err(|e| format!("GeoIP veritabanı yüklenemedi: {}", e))?);
+        .map_err(|e| format!("GeoIP veritabanı yüklenemedi: {}", e))?;
 
     println!("GeoIP veritabanı başarıyla yüklendi");
+    Ok(reader)
+}
+
+/// GeoIP sorgusunun sonucu
+struct Location {
+    coords: (f64, f64),
+    /// ISO 3166 ülke kodu
+    country: Option<String>,
+}
+
+fn lookup_location(reader: &maxminddb::Reader<Vec<u8>>, ip: IpAddr, label: &str) -> Option<Location> {
+    match reader.lookup::<geoip2::City>(ip) {
+        Ok(city) => {
+            let country = city.country.as_ref()
+                .and_then(|country| country.iso_code)
+                .map(str::to_string);
+            city.location.as_ref()
+                .map(|loc| Location {
+                    coords: (loc.latitude.unwrap_or_default(), loc.longitude.unwrap_or_default()),
+                    country,
+                })
+        },
+        Err(e) => {
+            println!("GeoIP hatası ({}): {} için {}", label, ip, e);
+            // Yerel IP için İstanbul koordinatları
+            if ip.to_string().starts_with("192.168.") {
+                Some(Location { coords: (41.0082, 28.9784), country: None })
+            } else {
+                None
+            }
+        }
+    }
+}
+
+/// Sorguyu GeoIP sayaçlarına işler.
+fn locate(reader: &maxminddb::Reader<Vec<u8>>, stats: &Stats, ip: IpAddr, label: &str) -> Option<Location> {
+    stats.geoip.lookups.inc();
+    let location = lookup_location(reader, ip, label);
+    if location.is_some() {
+        stats.geoip.hits.inc();
+    } else {
+        stats.geoip.misses.inc();
+    }
+    location
+}
+
+fn timestamp_millis(timestamp: Duration) -> u64 {
+    timestamp.as_millis() as u64
+}
+
+/// Dıştan içe doğru tünel katmanları
+fn tunnels_json(tunnels: &[Tunnel]) -> serde_json::Value {
+    tunnels
+        .iter()
+        .map(|tunnel| {
+            let mut value = json!({
+                "type": tunnel.kind.as_str(),
+                "source": tunnel.src.to_string(),
+                "destination": tunnel.dst.to_string()
+            });
+            if let Some(id) = tunnel.id {
+                value[tunnel.kind.id_field()] = json!(id);
+            }
+            value
+        })
+        .collect()
+}
+
+fn flow_start_json(
+    flow: &Flow,
+    geo: &FlowGeo,
+    macs: (Option<MacAddr>, Option<MacAddr>),
+    vlan_ids: &[u16],
+    tunnels: &[Tunnel],
+) -> serde_json::Value {
+    let initiator = flow.initiator;
+    let responder = flow.responder();
+    json!([{
+        "type": "flow_start",
+        "flow_id": flow.id,
+        "protocol": IpNextHeaderProtocol::new(flow.key.protocol).to_string().to_lowercase(),
+        "direction": flow.direction.as_str(),
+        "source": {
+            "ip": initiator.ip.to_string(),
+            "port": initiator.port,
+            "mac": macs.0.map(|mac| mac.to_string()),
+            "hostname": flow.names.src,
+            "latitude": geo.src.0,
+            "longitude": geo.src.1,
+            "country": geo.src_country
+        },
+        "destination": {
+            "ip": responder.ip.to_string(),
+            "port": responder.port,
+            "mac": macs.1.map(|mac| mac.to_string()),
+            "hostname": flow.names.dst,
+            "latitude": geo.dst.0,
+            "longitude": geo.dst.1,
+            "country": geo.dst_country
+        },
+        "ip_version": if initiator.ip.is_ipv4() { 4 } else { 6 },
+        "vlan_ids": vlan_ids,
+        "tunnels": tunnels_json(tunnels),
+        "first_seen": timestamp_millis(flow.first_seen),
+        "tcp_state": flow.tcp_state().map(|state| state.as_str()),
+        "tls": flow.tls.as_ref().map(tls_json),
+        "source_to_destination": counters_json(&flow.forward),
+        "destination_to_source": counters_json(&flow.reverse)
+    }])
+}
+
+/// Başlangıçtan sonra öğrenilen adlar da güncellemelerle gelir
+fn names_json(names: &FlowNames) -> serde_json::Value {
+    json!({
+        "source": names.src,
+        "destination": names.dst
+    })
+}
+
+fn counters_json(counters: &FlowCounters) -> serde_json::Value {
+    json!({
+        "packets": counters.packets,
+        "bytes": counters.bytes
+    })
+}
+
+fn tcp_side_json(side: &TcpSide) -> serde_json::Value {
+    json!({
+        "retransmissions": side.retransmissions,
+        "out_of_order": side.out_of_order,
+        "zero_windows": side.zero_windows
+    })
+}
+
+/// El sıkışma RTT'si ve yön bazında ağ kalitesi sayaçları
+fn tcp_json(tcp: &TcpTracker) -> serde_json::Value {
+    json!({
+        "handshake_rtt_ms": tcp.handshake_rtt.map(|rtt| rtt.as_secs_f64() * 1000.0),
+        "source_to_destination": tcp_side_json(&tcp.forward),
+        "destination_to_source": tcp_side_json(&tcp.reverse)
+    })
+}
+
+/// İstemcinin sunduğu en yüksek sürüm, SNI, ALPN ve tercih sırasıyla şifre takımları
+fn tls_json(hello: &ClientHello) -> serde_json::Value {
+    json!({
+        "version": tls::version_name(hello.version),
+        "sni": hello.sni,
+        "alpn": hello.alpn,
+        "cipher_suites": hello.cipher_suites.iter().map(|suite| tls::cipher_suite_name(*suite)).collect::<Vec<_>>()
+    })
+}
+
+/// Güncelleme ve bitiş olaylarında ortak akış alanları
+fn flow_record_json(flow: &Flow, event_type: &str) -> serde_json::Value {
+    json!({
+        "type": event_type,
+        "flow_id": flow.id,
+        "direction": flow.direction.as_str(),
+        "first_seen": timestamp_millis(flow.first_seen),
+        "last_seen": timestamp_millis(flow.last_seen),
+        "duration_ms": timestamp_millis(flow.duration()),
+        "tcp_state": flow.tcp_state().map(|state| state.as_str()),
+        "tcp": flow.tcp.as_ref().map(tcp_json),
+        "tls": flow.tls.as_ref().map(tls_json),
+        "icmp_errors": flow.icmp_errors,
+        "hostnames": names_json(&flow.names),
+        "source_to_destination": counters_json(&flow.forward),
+        "destination_to_source": counters_json(&flow.reverse)
+    })
+}
+
+fn flow_update_json(flow: &Flow) -> serde_json::Value {
+    flow_record_json(flow, "flow_update")
+}
+
+fn flow_end_json(flow: &Flow, reason: EndReason) -> serde_json::Value {
+    let mut event = flow_record_json(flow, "flow_end");
+    event["reason"] = json!(reason.as_str());
+    event
+}
+
+fn tcp_event_json(flow: &Flow, event: TcpEvent) -> serde_json::Value {
+    flow_record_json(flow, event.as_str())
+}
+
+/// `from_initiator` ucu alım penceresini sıfırladı
+fn zero_window_json(flow: &Flow, from_initiator: bool) -> serde_json::Value {
+    let mut event = flow_record_json(flow, "tcp_zero_window");
+    event["side"] = json!(if from_initiator { "source" } else { "destination" });
+    event
+}
+
+/// SYN'e yanıt alınamayan bağlantı; `reason` "refused" (RST) ya da "timeout",
+/// `failed_handshakes` tüm işlemcilerdeki toplam
+fn connection_failed_json(flow: &Flow, reason: &str, failed_handshakes: u64) -> serde_json::Value {
+    let mut event = flow_record_json(flow, TcpEvent::Refused.as_str());
+    event["reason"] = json!(reason);
+    event["failed_handshakes"] = json!(failed_handshakes);
+    event
+}
+
+/// `flow_start` dizisindeki her akışa ya da tek olaya arayüz adını ekler.
+fn tag_interface(event: &mut serde_json::Value, interface: &str) {
+    match event {
+        serde_json::Value::Array(events) => {
+            for event in events {
+                event["interface"] = json!(interface);
+            }
+        }
+        serde_json::Value::Object(event) => {
+            event.insert("interface".to_string(), json!(interface));
+        }
+        _ => {}
+    }
+}
+
+/// Çekirdekte düşen paketler; halka ya da tampon küçük kaldığında artar
+fn capture_stats_json(source: &str, worker: usize, stats: KernelStats) -> serde_json::Value {
+    json!({
+        "type": "capture_stats",
+        "source": source,
+        "worker": worker,
+        "kernel_received": stats.received,
+        "kernel_dropped": stats.dropped,
+        "kernel_freeze_queue": stats.freeze_queue,
+    })
+}
 
-    let interfaces = datalink::interfaces();
-    let interface = interfaces
-        .into_iter()
-        .find(|iface| iface.is_up() && !iface.is_loopback() && !iface.ips.is_empty())
-        .ok_or("Aktif ağ arayüzü bulunamadı")?;
+/// Hata mesajı ayrı bir kategori olarak bildirilir; gömülü başlık bilinen
+/// bir akışa aitse `flow_id` o akışı gösterir.
+fn icmp_error_json(
+    ip_packet: &IpPacket,
+    icmp: &IcmpMessage,
+    reporter: Option<Location>,
+    flow_id: Option<u64>,
+    timestamp: Duration,
+) -> serde_json::Value {
+    let original = icmp.original.map(|original| {
+        let (src_port, dst_port) = original.ports.unzip();
+        json!({
+            "protocol": original.protocol.to_string().to_lowercase(),
+            "source": { "ip": original.src.to_string(), "port": src_port },
+            "destination": { "ip": original.dst.to_string(), "port": dst_port }
+        })
+    });
+
+    json!({
+        "type": "icmp_error",
+        "category": "icmp",
+        "icmp_type": icmp.icmp_type,
+        "icmp_code": icmp.code,
+        "description": icmp.type_name(),
+        "ip_version": if icmp.v6 { 6 } else { 4 },
+        "reporter": {
+            "ip": ip_packet.src.to_string(),
+            "latitude": reporter.as_ref().map(|location| location.coords.0),
+            "longitude": reporter.as_ref().map(|location| location.coords.1),
+            "country": reporter.and_then(|location| location.country)
+        },
+        "flow_id": flow_id,
+        "original": original,
+        "timestamp": timestamp_millis(timestamp)
+    })
+}
 
-    println!("Seçilen ağ arayüzü: {}", interface.name);
-    println!("IP adresleri: {:?}", interface.ips);
+/// Filtre ifadesinin akış başında değerlendirildiği alanlar
+fn flow_subject(flow: &Flow, geo: &FlowGeo, vlan_ids: &[u16], tunnels: &[Tunnel]) -> Subject {
+    let initiator = flow.initiator;
+    let responder = flow.responder();
+    // ICMP echo akışlarında port yerine tanımlayıcı tutulur
+    let has_ports = matches!(IpNextHeaderProtocol::new(flow.key.protocol), IpNextHeaderProtocols::Tcp | IpNextHeaderProtocols::Udp);
 
-    let (_, mut rx) = match datalink::channel(&interface, Default::default()) {
-        Ok(datalink::Channel::Ethernet(tx, rx)) => (tx, rx),
-        Ok(_) => return Err("Desteklenmeyen kanal türü".into()),
-        Err(e) => return Err(e.into()),
+    Subject {
+        protocol: flow.key.protocol,
+        src: initiator.ip,
+        dst: responder.ip,
+        src_port: has_ports.then_some(initiator.port),
+        dst_port: has_ports.then_some(responder.port),
+        src_country: geo.src_country.clone(),
+        dst_country: geo.dst_country.clone(),
+        direction: Some(flow.direction),
+        vlan_ids: vlan_ids.to_vec(),
+        tunneled: !tunnels.is_empty(),
+        tunnel_ids: tunnels.iter().filter_map(|tunnel| tunnel.id).collect(),
+    }
+}
+
+fn describe_location(ip: IpAddr, country: Option<&str>, coords: (f64, f64)) -> String {
+    format!("{} [{}] ({:.4}, {:.4})", ip, country.unwrap_or("?"), coords.0, coords.1)
+}
+
+/// Kayıttaki pakete eklenen akış ve konum bilgisi
+fn flow_comment(flow: &Flow, geo: &FlowGeo) -> String {
+    format!(
+        "akış {} {} {}: {} -> {}",
+        flow.id,
+        IpNextHeaderProtocol::new(flow.key.protocol).to_string().to_lowercase(),
+        flow.direction.as_str(),
+        describe_location(flow.initiator.ip, geo.src_country.as_deref(), geo.src),
+        describe_location(flow.responder().ip, geo.dst_country.as_deref(), geo.dst)
+    )
+}
+
+fn icmp_comment(ip_packet: &IpPacket, icmp: &IcmpMessage, reporter: Option<&Location>, flow_id: Option<u64>) -> String {
+    let reporter = match reporter {
+        Some(location) => describe_location(ip_packet.src, location.country.as_deref(), location.coords),
+        None => ip_packet.src.to_string(),
     };
+    match flow_id {
+        Some(flow_id) => format!("icmp {} (akış {}): raporlayan {}", icmp.type_name(), flow_id, reporter),
+        None => format!("icmp {}: raporlayan {}", icmp.type_name(), reporter),
+    }
+}
 
-    println!("Paket yakalama başladı...");
-    let mut tracker = ConnectionTracker::new();
+/// Henüz adı bilinmeyen uçları önbellekte arar.
+fn resolve_names(dns: &DnsCache, flow: &mut Flow, now: Duration) {
+    if flow.names.src.is_none() {
+        flow.names.src = dns.lookup(flow.initiator.ip, now);
+    }
+    if flow.names.dst.is_none() {
+        flow.names.dst = dns.lookup(flow.responder().ip, now);
+    }
+}
 
-    loop {
-        match rx.next() {
-            Ok(packet) => {
-                if let Some(ip_packet) = Ipv4Packet::new(packet) {
-                    let src_ip = IpAddr::V4(ip_packet.get_source());
-                    let dst_ip = IpAddr::V4(ip_packet.get_destination());
-
-                    // En az bir IP public olmalı
-                    if !should_track_ip(src_ip) && !should_track_ip(dst_ip) {
-                        continue;
+/// Uzak uç gelen akışta başlatan, diğerlerinde karşı uçtur.
+fn traffic_key(flow: &Flow) -> TrafficKey {
+    let country = flow.geo.as_ref().and_then(|geo| match flow.direction {
+        Direction::Inbound => geo.src_country.clone(),
+        Direction::Outbound | Direction::Transit => geo.dst_country.clone(),
+    });
+    TrafficKey { protocol: flow.key.protocol, country, direction: flow.direction }
+}
+
+/// Hangi `PacketSource`'tan gelirse gelsin her çerçeveyi aynı çözümleme,
+/// GeoIP ve akış takibi adımlarından geçirir.
+pub struct PacketProcessor {
+    reader: Arc<GeoIpReader>,
+    flows: FlowTable,
+    fragments: Reassembler,
+    // Yapılandırmadaki filtre; yalnızca uyan akışlar bildirilir
+    filter: Option<Filter>,
+    ended: Vec<(Flow, EndReason)>,
+    // Yakalama yapılan arayüzün kendi adresleri; akış yönü için
+    local_addrs: Vec<IpAddr>,
+    last_sweep: Option<Duration>,
+    // Fanout'taki sırası; tek kaynakta 0
+    worker: usize,
+    // Canlı yakalamada olayların geldiği arayüz
+    interface: Option<String>,
+    // Son paketin yakalama zamanı ve işlendiği an; paket gelmediğinde
+    // zaman aşımlarını ilerletmek için
+    clock: Option<(Duration, Instant)>,
+    stats: Arc<Stats>,
+    // Kayda henüz eklenmemiş etiketli trafik sayaçları
+    traffic: Traffic,
+    recorder: Option<Recorder>,
+    // İşlenen çerçevenin kayıtta taşıyacağı GeoIP bilgisi
+    comment: Option<String>,
+    // Tüm işlemcilerce paylaşılan adres→ad önbelleği
+    dns: Option<Arc<DnsCache>>,
+    dns_streams: TcpStreams,
+    tx: mpsc::Sender<String>,
+}
+
+impl PacketProcessor {
+    /// `reader` tüm işlemciler arasında paylaşılır; veritabanı bir kez
+    /// belleğe alınır.
+    pub fn new(
+        tx: mpsc::Sender<String>,
+        reader: Arc<GeoIpReader>,
+        config: &Config,
+        stats: Arc<Stats>,
+    ) -> Self {
+        if let Some(filter) = &config.filter {
+            println!("Yakalama filtresi: {}", filter.as_str());
+        }
+        Self {
+            reader,
+            flows: FlowTable::new(config.flows),
+            fragments: Reassembler::new(config.fragments, stats.clone()),
+            filter: config.filter.clone(),
+            ended: Vec::new(),
+            local_addrs: config.local_addrs.clone(),
+            last_sweep: None,
+            worker: 0,
+            interface: None,
+            clock: None,
+            stats,
+            traffic: Traffic::new(),
+            recorder: None,
+            comment: None,
+            dns: None,
+            dns_streams: TcpStreams::default(),
+            tx,
+        }
+    }
+
+    pub fn process(&mut self, frame: &Frame) {
+        self.analyze(frame);
+        let comment = self.comment.take();
+        if let Some(recorder) = &self.recorder {
+            recorder.record(self.interface.as_deref().unwrap_or_default(), frame, comment);
+        }
+    }
+
+    fn analyze(&mut self, frame: &Frame) {
+        let timestamp = frame.timestamp;
+        self.clock = Some((timestamp, Instant::now()));
+        self.stats.decode.frames.inc();
+
+        let link = match decode::decode_link(frame.link_type, frame.data) {
+            Some(link) => link,
+            None => {
+                self.stats.decode.undecodable.inc();
+                return;
+            }
+        };
+
+        let ip_packet = match decode::decode_ip(link.network) {
+            Some(ip_packet) => ip_packet,
+            None => {
+                self.stats.decode.undecodable.inc();
+                return;
+            }
+        };
+
+        // Parçalar tamamlanana kadar bekletilir; ilk olmayan parçalarda
+        // taşıma başlığı olmadığından tek başlarına çözülemezler
+        let reassembled;
+        let ip_packet = match ip_packet.fragment {
+            Some(fragment) => match self.fragments.insert(timestamp, &ip_packet, fragment) {
+                Some(data) => {
+                    reassembled = data;
+                    match decode::reassembled_ip(&ip_packet, &reassembled) {
+                        Some(ip_packet) => ip_packet,
+                        None => return,
                     }
+                }
+                None => {
+                    self.sweep(timestamp);
+                    return;
+                }
+            },
+            None => ip_packet,
+        };
 
-                    // Aynı bağlantıyı tekrar gösterme
-                    if !tracker.is_new_connection(&src_ip.to_string(), &dst_ip.to_string()) {
-                        continue;
+        // Tünelli trafikte haritada iç akış gösterilir; dış uçlar ve
+        // VNI/anahtar olaya eklenir. Açılamayan tünelde dış paket akış
+        // olarak sayılır.
+        let mut tunnels = Vec::new();
+        let mut macs = (link.src_mac, link.dst_mac);
+        let inner_reassembled;
+        let mut ip_packet = ip_packet;
+        while let Some((tunnel, inner)) = tunnel::decapsulate(&ip_packet) {
+            if tunnels.len() >= MAX_TUNNEL_DEPTH {
+                self.stats.decode.tunnels_too_deep.inc();
+                break;
+            }
+            let (network, inner_macs) = match inner {
+                Inner::Ethernet(data) => match decode::decode_ethernet(data) {
+                    Some(inner_ethernet) => {
+                        (inner_ethernet.network, Some((inner_ethernet.src_mac, inner_ethernet.dst_mac)))
+                    }
+                    None => {
+                        self.stats.decode.tunnel_undecodable.inc();
+                        break;
                     }
+                },
+                Inner::Network(network) => (network, None),
+            };
+            let inner_packet = match decode::decode_ip(network) {
+                Some(inner_packet) => inner_packet,
+                None => {
+                    self.stats.decode.tunnel_undecodable.inc();
+                    break;
+                }
+            };
+            if let Some(inner_macs) = inner_macs {
+                macs = inner_macs;
+            }
+            tunnels.push(tunnel);
 
-                    let (src_port, dst_port) = match ip_packet.get_next_level_protocol() {
-                        IpNextHeaderProtocols::Tcp => {
-                            if let Some(tcp) = TcpPacket::new(ip_packet.payload()) {
-                                (tcp.get_source(), tcp.get_destination())
-                            } else {
-                                continue;
-                            }
-                        },
-                        IpNextHeaderProtocols::Udp => {
-                            if let Some(udp) = UdpPacket::new(ip_packet.payload()) {
-                                (udp.get_source(), udp.get_destination())
-                            } else {
-                                continue;
-                            }
-                        },
-                        _ => continue,
+            // Tünel içindeki parçalar da birleştirilir; birleşen paket
+            // başka bir tünel taşısa da yeniden açılmaz
+            let fragment = match inner_packet.fragment {
+                Some(fragment) => fragment,
+                None => {
+                    ip_packet = inner_packet;
+                    continue;
+                }
+            };
+            match self.fragments.insert(timestamp, &inner_packet, fragment) {
+                Some(data) => {
+                    inner_reassembled = data;
+                    ip_packet = match decode::reassembled_ip(&inner_packet, &inner_reassembled) {
+                        Some(ip_packet) => ip_packet,
+                        None => return,
                     };
+                    break;
+                }
+                None => {
+                    self.sweep(timestamp);
+                    return;
+                }
+            }
+        }
 
-                    // GeoIP sorguları
-                    let src_location = match reader.lookup::<geoip2::City>(src_ip) {
-                        Ok(city) => {
-                            city.location.as_ref()
-                                .and_then(|loc| {
-                                    Some((
-                                        loc.latitude.unwrap_or_default(),
-                                        loc.longitude.unwrap_or_default()
-                                    ))
-                                })
-                        },
-                        Err(e) => {
-                            println!("GeoIP hatası (kaynak): {} için {}", src_ip, e);
-                            // Yerel IP için İstanbul koordinatları
-                            if src_ip.to_string().starts_with("192.168.") {
-                                Some((41.0082, 28.9784))
-                            } else {
-                                None
-                            }
-                        }
-                    };
+        if !tunnels.is_empty() {
+            self.stats.decode.tunneled.inc();
+        }
 
-                    let dst_location = match reader.lookup::<geoip2::City>(dst_ip) {
-                        Ok(city) => {
-                            city.location.as_ref()
-                                .and_then(|loc| {
-                                    Some((
-                                        loc.latitude.unwrap_or_default(),
-                                        loc.longitude.unwrap_or_default()
-                                    ))
-                                })
-                        },
-                        Err(e) => {
-                            println!("GeoIP hatası (hedef): {} için {}", dst_ip, e);
-                            // Yerel IP için İstanbul koordinatları
-                            if dst_ip.to_string().starts_with("192.168.") {
-                                Some((41.0082, 28.9784))
-                            } else {
-                                None
-                            }
-                        }
-                    };
+        let src_ip = ip_packet.src;
+        let dst_ip = ip_packet.dst;
+
+        let icmp = decode::decode_icmp(&ip_packet);
+        if let Some(icmp) = icmp.filter(IcmpMessage::is_error) {
+            self.stats.decode.icmp_errors.inc();
+            self.process_icmp_error(timestamp, &ip_packet, &icmp);
+            self.sweep(timestamp);
+            return;
+        }
+
+        // ICMP echo akışlarında tanımlayıcı port yerine geçer; diğer ICMP
+        // mesajları (komşu keşfi vb.) akış açmaz
+        let transport = match icmp {
+            Some(icmp) => icmp
+                .echo
+                .map(|echo| (Transport { src_port: echo.id, dst_port: echo.id, tcp: None, payload: &[] }, Some(echo))),
+            None => decode::decode_transport(&ip_packet).map(|transport| (transport, None)),
+        };
+
+        // Yerel çözümleyiciye giden sorgular izlenmese de yanıtları okunur
+        if let Some((transport, None)) = &transport {
+            if transport.src_port == dns::DNS_PORT && self.dns.is_some() {
+                self.sniff_dns(timestamp, src_ip, dst_ip, transport);
+            }
+        }
+
+        // En az bir IP public olmalı
+        if !should_track_ip(src_ip) && !should_track_ip(dst_ip) {
+            self.stats.decode.untracked.inc();
+            return;
+        }
+
+        let (transport, echo) = match transport {
+            Some(transport) => transport,
+            None => {
+                self.stats.decode.no_transport.inc();
+                return;
+            }
+        };
+
+        let src = Endpoint { ip: src_ip, port: transport.src_port };
+        let dst = Endpoint { ip: dst_ip, port: transport.dst_port };
+        let tcp_flags = transport.tcp.map(|tcp| tcp.flags).unwrap_or(0);
+        let syn = tcp_flags & TcpFlags::SYN != 0;
+        let ack = tcp_flags & TcpFlags::ACK != 0;
+
+        // SYN ya da echo isteği gönderen başlatandır; ilk görülen paket
+        // SYN-ACK ya da echo yanıtıysa karşı uç
+        let reply = (syn && ack) || echo.is_some_and(|echo| echo.reply);
+        let initiator = if reply { dst } else { src };
+        let key = FlowKey::new(ip_packet.protocol.0, src, dst);
+
+        // Kapanmış bağlantının uç noktaları yeniden kullanılıyorsa yeni akış açılır
+        if syn && !ack {
+            self.flows.end_closed(&key, &mut self.ended);
+        }
+
+        let (flow, is_new) = self.flows.observe(key, initiator, timestamp, &mut self.ended);
+        let mut events = Vec::new();
+        self.stats.flows.packets.inc();
+        if is_new {
+            self.stats.flows.started.inc();
+        } else {
+            self.stats.flows.merged.inc();
+        }
+
+        if syn && !flow.initiator_from_syn {
+            // Akış ortasından yakalamaya başlandıysa yön SYN görülünce düzelir
+            flow.set_initiator(initiator);
+            flow.initiator_from_syn = true;
+            flow.direction = Direction::classify(initiator.ip, flow.responder().ip, &self.local_addrs);
+        }
+        flow.record(src, ip_packet.len as u64);
+
+        let from_initiator = src == flow.initiator;
+        let (tcp_event, zero_window) = match transport.tcp {
+            Some(tcp) => {
+                let tracker = flow.tcp.get_or_insert_with(TcpTracker::default);
+                let event = tracker.on_segment(timestamp, from_initiator, tcp.flags);
+                (event, tracker.on_sequence(timestamp, from_initiator, &tcp))
+            }
+            None => (None, false),
+        };
+
+        // Başlatan tarafın ilk verisinde ClientHello aranır; birden çok
+        // segmente yayılmışsa segmentler birleştirilir
+        let mut client_hello = false;
+        if let Some(tcp) = transport.tcp.filter(|_| from_initiator && !flow.hello.is_done()) {
+            match flow.hello.push(&tcp, transport.payload) {
+                tls::Parse::Complete(hello) => {
+                    self.stats.tls.client_hellos.inc();
+                    flow.tls = Some(hello);
+                    client_hello = true;
+                }
+                tls::Parse::Malformed => self.stats.tls.malformed.inc(),
+                tls::Parse::Incomplete | tls::Parse::NotTls => {}
+            }
+        }
+
+        if is_new {
+            let initiator = flow.initiator;
+            let responder = flow.responder();
+            flow.direction = Direction::classify(initiator.ip, responder.ip, &self.local_addrs);
+
+            // GeoIP sorguları yalnızca akış başında yapılır
+            let src_location = locate(&self.reader, &self.stats, initiator.ip, "kaynak");
+            let dst_location = locate(&self.reader, &self.stats, responder.ip, "hedef");
+
+            if let Some(dns) = &self.dns {
+                resolve_names(dns, flow, timestamp);
+            }
+
+            if let (Some(src_location), Some(dst_location)) = (src_location, dst_location) {
+                let geo = FlowGeo {
+                    src: src_location.coords,
+                    dst: dst_location.coords,
+                    src_country: src_location.country,
+                    dst_country: dst_location.country,
+                };
+
+                // Filtreye uymayan akış tabloda izlenmeye devam eder ama
+                // hiçbir olayı bildirilmez
+                let subject = flow_subject(flow, &geo, &link.vlan_ids, &tunnels);
+                if self.filter.as_ref().is_none_or(|filter| filter.matches(&subject)) {
+                    self.stats.flows.reported.inc();
+                    let macs = if initiator == src { macs } else { (macs.1, macs.0) };
+                    events.push(flow_start_json(flow, &geo, macs, &link.vlan_ids, &tunnels));
+
+                    println!(
+                        "Yeni bağlantı ({}): {}:{} -> {}:{}",
+                        flow.direction.as_str(), initiator.ip, initiator.port, responder.ip, responder.port
+                    );
+                    println!("Konumlar: ({}, {}) -> ({}, {})", geo.src.0, geo.src.1, geo.dst.0, geo.dst.1);
+                    flow.geo = Some(geo);
+                } else {
+                    self.stats.flows.filtered.inc();
+                }
+            } else {
+                self.stats.flows.unlocated.inc();
+            }
+        }
+
+        let traffic = self.traffic.entry(traffic_key(flow)).or_default();
+        if is_new {
+            traffic.flows += 1;
+        }
+        traffic.packets += 1;
+        traffic.bytes += ip_packet.len as u64;
+
+        if let (Some(geo), Some(_)) = (&flow.geo, &self.recorder) {
+            self.comment = Some(flow_comment(flow, geo));
+        }
+
+        // Akışın ilk paketinde tamamlandıysa `flow_start` içinde gitti
+        if client_hello && !is_new && flow.geo.is_some() {
+            events.push(flow_record_json(flow, "tls_client_hello"));
+        }
+
+        if zero_window && flow.geo.is_some() {
+            events.push(zero_window_json(flow, from_initiator));
+        }
+
+        if let Some(tcp_event) = tcp_event {
+            if tcp_event == TcpEvent::Refused {
+                self.stats.flows.failed_handshakes.inc();
+            }
+            if flow.geo.is_some() {
+                events.push(match tcp_event {
+                    TcpEvent::Refused => connection_failed_json(flow, "refused", self.stats.flows.failed_handshakes.get()),
+                    _ => tcp_event_json(flow, tcp_event),
+                });
+            }
+            if flow.close_reason().is_some() {
+                self.flows.schedule_close(key, timestamp);
+            }
+        }
+
+        // Bu paket yüzünden sonlanan akışlar yeni olaylardan önce bildirilir
+        self.flush_ended();
+        for event in events {
+            self.send(event);
+        }
+
+        self.sweep(timestamp);
+    }
+
+    /// Sunucudan gelen DNS yanıtındaki adresleri önbelleğe ekler; TCP'de
+    /// yanıt birden çok segmente yayılabilir.
+    fn sniff_dns(&mut self, timestamp: Duration, src_ip: IpAddr, dst_ip: IpAddr, transport: &Transport) {
+        let messages = match transport.tcp {
+            Some(tcp) => self.dns_streams.push(
+                Endpoint { ip: src_ip, port: transport.src_port },
+                Endpoint { ip: dst_ip, port: transport.dst_port },
+                tcp,
+                transport.payload,
+                timestamp,
+            ),
+            None => vec![transport.payload.to_vec()],
+        };
+
+        let dns = match &self.dns {
+            Some(dns) => dns,
+            None => return,
+        };
+        for message in messages {
+            let resolutions = match dns::parse_response(&message) {
+                Some(resolutions) => resolutions,
+                None => {
+                    self.stats.dns.malformed.inc();
+                    continue;
+                }
+            };
+            self.stats.dns.responses.inc();
+            self.stats.dns.addresses.add(resolutions.len() as u64);
+            for resolution in resolutions {
+                dns.insert(resolution, timestamp);
+            }
+        }
+    }
+
+    fn process_icmp_error(&mut self, timestamp: Duration, ip_packet: &IpPacket, icmp: &IcmpMessage) {
+        // Raporlayan yönlendirici özel adresli olabilir; hataya yol açan
+        // paketin uçlarından biri public ise yine de bildirilir
+        let tracked = should_track_ip(ip_packet.src)
+            || should_track_ip(ip_packet.dst)
+            || icmp.original.is_some_and(|original| should_track_ip(original.src) || should_track_ip(original.dst));
+        if !tracked {
+            return;
+        }
+
+        let flow = icmp.original.and_then(|original| {
+            let (src_port, dst_port) = original.ports?;
+            let key = FlowKey::new(
+                original.protocol.0,
+                Endpoint { ip: original.src, port: src_port },
+                Endpoint { ip: original.dst, port: dst_port },
+            );
+            self.flows.get_mut(&key)
+        });
+
+        let mut flow_id = None;
+        if let Some(flow) = flow {
+            flow.icmp_errors += 1;
+            // Başlangıcı bildirilmemiş akışa bağlanmaz
+            flow_id = flow.geo.as_ref().map(|_| flow.id);
+        }
+
+        let reporter = locate(&self.reader, &self.stats, ip_packet.src, "ICMP");
+        if self.recorder.is_some() {
+            self.comment = Some(icmp_comment(ip_packet, icmp, reporter.as_ref(), flow_id));
+        }
+
+        // Bildirilmiş bir akışa ait olmayan hata kendi başına filtrelenir
+        if let (None, Some(filter)) = (flow_id, &self.filter) {
+            let subject = Subject {
+                protocol: ip_packet.protocol.0,
+                src: ip_packet.src,
+                dst: ip_packet.dst,
+                src_port: None,
+                dst_port: None,
+                src_country: reporter.as_ref().and_then(|location| location.country.clone()),
+                dst_country: None,
+                direction: None,
+                vlan_ids: Vec::new(),
+                tunneled: false,
+                tunnel_ids: Vec::new(),
+            };
+            if !filter.matches(&subject) {
+                return;
+            }
+        }
+        println!(
+            "ICMP hatası ({}, kod {}): {} -> {}, akış: {:?}",
+            icmp.type_name(), icmp.code, ip_packet.src, ip_packet.dst, flow_id
+        );
+        self.send(icmp_error_json(ip_packet, icmp, reporter, flow_id, timestamp));
+    }
+
+    /// Paket gelmediğinde zaman aşımlarının işlemesi için düzenli çağrılır.
+    pub fn tick(&mut self) {
+        if let Some((timestamp, seen_at)) = self.clock {
+            self.sweep(timestamp + seen_at.elapsed());
+        }
+    }
 
-                    if let (Some((src_lat, src_lon)), Some((dst_lat, dst_lon))) = (src_location, dst_location) {
-                        let connection = json!([{
-                            "source": {
-                                "ip": src_ip.to_string(),
-                                "port": src_port,
-                                "latitude": src_lat,
-                                "longitude": src_lon
-                            },
-                            "destination": {
-                                "ip": dst_ip.to_string(),
-                                "port": dst_port,
-                                "latitude": dst_lat,
-                                "longitude": dst_lon
-                            }
-                        }]);
-
-                        println!("Yeni bağlantı: {}:{} -> {}:{}", src_ip, src_port, dst_ip, dst_port);
-                        println!("Konumlar: ({}, {}) -> ({}, {})", src_lat, src_lon, dst_lat, dst_lon);
-
-                        if let Err(e) = tx.send(connection.to_string()) {
-                            eprintln!("Veri gönderme hatası: {}", e);
-                        }
+    /// Kaynak tükendiğinde açık akışları sonlandırır.
+    pub fn finish(&mut self) {
+        self.flows.drain(&mut self.ended);
+        self.flush_ended();
+        self.stats.merge_traffic(&mut self.traffic);
+    }
+
+    /// Fanout ile paralel çalışan çözümleme thread'lerinden biri olarak
+    /// ayarlar; akış kimlikleri diğer thread'lerinkilerle çakışmaz.
+    pub fn set_worker(&mut self, index: usize, count: usize) {
+        self.worker = index;
+        self.flows.set_id_stride(index, count);
+    }
+
+    /// Bu işlemcinin tüm olaylarına `interface` alanı eklenir.
+    pub fn set_interface(&mut self, name: &str) {
+        self.interface = Some(name.to_string());
+    }
+
+    /// DNS yanıtları bu önbelleğe eklenir ve akış uçlarının adları buradan
+    /// aranır.
+    pub fn set_dns(&mut self, dns: Arc<DnsCache>) {
+        self.dns = Some(dns);
+    }
+
+    /// İşlenen her çerçeve GeoIP yorumuyla birlikte kayda yazılır.
+    pub fn set_recorder(&mut self, recorder: Recorder) {
+        self.recorder = Some(recorder);
+    }
+
+    /// Çekirdeğin yakalama sayaçlarını istemcilere bildirir.
+    pub fn report_kernel_stats(&self, source: &str, stats: KernelStats) {
+        self.send(capture_stats_json(source, self.worker, stats));
+    }
+
+    pub fn add_local_addrs(&mut self, addrs: &[IpAddr]) {
+        for addr in addrs {
+            if !self.local_addrs.contains(addr) {
+                self.local_addrs.push(*addr);
+            }
+        }
+    }
+
+    pub fn is_closed(&self) -> bool {
+        self.tx.is_closed()
+    }
+
+    fn sweep(&mut self, now: Duration) {
+        let last_sweep = *self.last_sweep.get_or_insert(now);
+        if now.saturating_sub(last_sweep) >= SWEEP_INTERVAL {
+            self.last_sweep = Some(now);
+            self.flows.expire(now, &mut self.ended);
+            self.fragments.expire(now);
+            self.dns_streams.expire(now);
+            self.stats.merge_traffic(&mut self.traffic);
+
+            let dns = self.dns.as_deref();
+            let updates: Vec<_> = self.flows
+                .due_updates(now)
+                .into_iter()
+                .filter(|flow| flow.geo.is_some())
+                .map(|flow| {
+                    if let Some(dns) = dns {
+                        resolve_names(dns, flow, now);
                     }
+                    flow_update_json(flow)
+                })
+                .collect();
+            for update in updates {
+                self.send(update);
+            }
+        }
+
+        self.flush_ended();
+    }
+
+    fn flush_ended(&mut self) {
+        let ended = std::mem::take(&mut self.ended);
+        self.stats.flows.ended.add(ended.len() as u64);
+        for (flow, reason) in ended {
+            // SYN-ACK görülmeden zaman aşımına uğrayan bağlantı başarısız sayılır
+            let failed = flow.tcp_state() == Some(TcpState::SynSent) && reason != EndReason::Shutdown;
+            if failed {
+                self.stats.flows.failed_handshakes.inc();
+            }
+
+            // Başlangıcı bildirilmemiş akışın sonu da bildirilmez
+            if flow.geo.is_some() {
+                if failed {
+                    self.send(connection_failed_json(&flow, "timeout", self.stats.flows.failed_handshakes.get()));
                 }
+                self.send(flow_end_json(&flow, reason));
             }
-            Err(e) => {
-                eprintln!("Paket yakalama hatası: {}", e);
+        }
+    }
+
+    fn send(&self, mut event: serde_json::Value) {
+        if let Some(interface) = &self.interface {
+            tag_interface(&mut event, interface);
+        }
+        // Kuyruk doluysa çözümleme thread'i async taraf yetişene kadar bekler
+        if self.tx.blocking_send(event.to_string()).is_err() {
+            eprintln!("Olay kuyruğu kapandı");
+        }
+    }
+}
+
+/// Her kaynağı ayrı thread'lerde çalıştırır, üretilen olayları broadcast
+/// kanalına aktarır ve yakalanan çerçeve sayısını döndürür. Birden çok
+/// kaynak (arayüzler, fanout) varsa her biri kendi akış tablosuyla
+/// çözümlenir. `tag_interface` ise olaylara kaynağın arayüz adı eklenir.
+/// Kaynaklardan biri kalıcı hata verirse diğerleri de durdurulur ve hata
+/// döner; denetleyici bozulan arayüzü de yeniden açarak yakalamayı kurar.
+#[allow(clippy::too_many_arguments)]
+async fn run_pipeline(
+    sources: Vec<Box<dyn PacketSource + Send>>,
+    reader: Arc<GeoIpReader>,
+    tx: broadcast::Sender<String>,
+    config: &Config,
+    stop: Arc<AtomicBool>,
+    stats: Arc<Stats>,
+    recorder: Option<Recorder>,
+    tag_interface: bool,
+) -> Result<u64, Box<dyn std::error::Error + Send + Sync>> {
+    let (event_tx, mut event_rx) = mpsc::channel(EVENT_QUEUE_LEN);
+    let (failure_tx, mut failure_rx) = mpsc::unbounded_channel();
+    // Yalnızca bu kaynakları durdurur; dışarıdan gelen istek buraya aktarılır
+    let run_stop = Arc::new(AtomicBool::new(false));
+    let dns = config.dns.map(|dns| Arc::new(DnsCache::new(dns)));
+    let workers = sources.len();
+    let mut pipelines = Vec::with_capacity(workers);
+    for (index, source) in sources.into_iter().enumerate() {
+        let mut processor = PacketProcessor::new(event_tx.clone(), reader.clone(), config, stats.clone());
+        processor.add_local_addrs(&source.local_addrs());
+        if workers > 1 {
+            processor.set_worker(index, workers);
+        }
+        if tag_interface {
+            processor.set_interface(source.name());
+        }
+        if let Some(recorder) = &recorder {
+            processor.set_recorder(recorder.clone());
+        }
+        if let Some(dns) = &dns {
+            processor.set_dns(dns.clone());
+        }
+        pipelines.push(Pipeline::spawn(source, processor, run_stop.clone(), stats.clone(), failure_tx.clone())?);
+    }
+    // Tüm çözümleme thread'leri bitince kanal kapanır
+    drop(event_tx);
+    drop(failure_tx);
+
+    let mut poll = tokio::time::interval(STOP_POLL_INTERVAL);
+    let mut failed = false;
+    loop {
+        tokio::select! {
+            event = event_rx.recv() => match event {
+                Some(event) => {
+                    stats.websocket.events.inc();
+                    if let Err(e) = tx.send(event) {
+                        stats.websocket.no_receivers.inc();
+                        eprintln!("Veri gönderme hatası: {}", e);
+                    }
+                }
+                None => break,
+            },
+            Some(reason) = failure_rx.recv(), if !failed => {
+                eprintln!("Kaynak kalıcı hata verdi ({}), yakalama durduruluyor", reason);
+                failed = true;
+                run_stop.store(true, Ordering::Relaxed);
+            }
+            _ = poll.tick() => {
+                if stop.load(Ordering::Relaxed) {
+                    run_stop.store(true, Ordering::Relaxed);
+                }
+            }
+        }
+    }
+
+    let captured = tokio::task::spawn_blocking(move || {
+        pipelines.into_iter().map(Pipeline::join).sum::<Result<u64, String>>()
+    })
+    .await??;
+    Ok(captured)
+}
+
+/// Seçilen arayüzlerden yakalar; `stop` işaretlenene ya da kaynaklardan
+/// biri kalıcı hata verene kadar sürer.
+pub async fn start_packet_capture(
+    tx: broadcast::Sender<String>,
+    config: &Config,
+    selected: &[(NetworkInterface, CaptureOptions)],
+    stop: Arc<AtomicBool>,
+    stats: Arc<Stats>,
+    recorder: Option<Recorder>,
+) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    for (interface, options) in selected {
+        println!(
+            "Seçilen ağ arayüzü: {} (#{}, karışık mod: {}, snaplen: {})",
+            interface.name,
+            interface.index,
+            if options.promiscuous { "açık" } else { "kapalı" },
+            options.snaplen
+        );
+        println!("IP adresleri: {:?}", interface.ips);
+    }
+
+    let sources = open_live_sources(selected, config)?;
+    // Fanout işçileri ve arayüzler aynı veritabanı kopyasını kullanır
+    let reader = Arc::new(open_geoip_reader()?);
+
+    println!("Paket yakalama başladı...");
+
+    run_pipeline(sources, reader, tx, config, stop, stats, recorder, true).await?;
+    Ok(())
+}
+
+/// Seçilen arayüzlerin canlı yakalama kaynaklarını açar. Linux'ta filtre
+/// varsa çekirdek BPF programına bir kez derlenip her sokete eklenir.
+#[cfg(target_os = "linux")]
+fn open_live_sources(
+    selected: &[(NetworkInterface, CaptureOptions)],
+    config: &Config,
+) -> io::Result<Vec<Box<dyn PacketSource + Send>>> {
+    // Ad çözümleme açıksa DNS yanıtları filtreye uymasa da okunmalı
+    let dns = config.dns.is_some();
+    let kernel_filter = match config.filter.as_ref().map(|filter| bpf::compile(filter, dns)) {
+        Some(Ok(kernel_filter)) => kernel_filter,
+        Some(Err(e)) => {
+            eprintln!("Filtre çekirdek programına derlenemedi ({}), kullanıcı alanında uygulanacak", e);
+            None
+        }
+        None => None,
+    };
+    if let Some(kernel_filter) = &kernel_filter {
+        if config.bpf_dump {
+            print!("Çekirdek BPF programı:\n{}", bpf::disassemble(&kernel_filter.program));
+        }
+        if !kernel_filter.exact {
+            println!("Filtrenin çekirdekte ifade edilemeyen kısmı kullanıcı alanında uygulanacak");
+        }
+    }
+    let program = kernel_filter.as_ref().map(|kernel_filter| kernel_filter.program.as_slice());
+
+    let mut sources = Vec::new();
+    for (interface, options) in selected {
+        sources.extend(open_interface(interface, options, config, program)?);
+    }
+    Ok(sources)
+}
+
+/// TPACKET_V3 halkası istenmişse fanout grubundaki her soket ayrı bir
+/// kaynaktır. Halka, filtre ya da alıcı tamponu kurulamazsa pnet kanalına
+/// dönülür; filtre her durumda kullanıcı alanında da uygulanır.
+#[cfg(target_os = "linux")]
+fn open_interface(
+    interface: &NetworkInterface,
+    options: &CaptureOptions,
+    config: &Config,
+    program: Option<&[bpf::Instruction]>,
+) -> io::Result<Vec<Box<dyn PacketSource + Send>>> {
+    // Çekirdek programı Ethernet başlığına göre derlenir
+    let link_type = crate::interfaces::link_type(interface);
+    let program = match program {
+        Some(_) if link_type != LinkType::Ethernet => {
+            println!(
+                "{} arayüzü Ethernet değil ({:?}), filtre kullanıcı alanında uygulanacak",
+                interface.name, link_type
+            );
+            None
+        }
+        program => program,
+    };
+
+    // Halka ve pnet kanalı ham soket açar; `any` yalnızca başlıksız
+    // (SOCK_DGRAM) okunabilir
+    if crate::interfaces::is_any(interface) {
+        return Ok(vec![Box::new(AfPacketSource::open(interface, options, None)?)]);
+    }
+
+    if let CaptureBackend::Ring(ring) = &config.backend {
+        // Arayüze özel tampon boyutu halka boyutunu belirler
+        let mut ring = *ring;
+        if let Some(buffer_size) = options.buffer_size {
+            ring.block_count = (buffer_size / ring.block_size).max(1);
+        }
+        // Her arayüzün fanout grubu ayrı olmalı
+        ring.fanout_group = ring.fanout_group.wrapping_add(interface.index as u16);
+
+        let sources = match open_rings(interface, &ring, options, program) {
+            Err(e) if program.is_some() => {
+                eprintln!("Çekirdek filtresi eklenemedi ({}), filtre kullanıcı alanında uygulanacak", e);
+                open_rings(interface, &ring, options, None)
+            }
+            result => result,
+        };
+        match sources {
+            Ok(sources) => {
+                println!(
+                    "TPACKET_V3 halkası ({}): {} x {} bayt blok, {} soket",
+                    interface.name, ring.block_count, ring.block_size, sources.len()
+                );
+                return Ok(sources);
+            }
+            Err(e) => eprintln!("TPACKET_V3 halkası kurulamadı ({}), pnet kanalına dönülüyor", e),
+        }
+    }
+
+    // pnet kanalı çekirdek filtresi ve alıcı tampon boyutu ayarlayamaz
+    if program.is_some() || options.buffer_size.is_some() {
+        match AfPacketSource::open(interface, options, program) {
+            Ok(source) => {
+                if let Some(program) = program {
+                    println!("Çekirdek filtresi eklendi ({}, {} komut)", interface.name, program.len());
+                }
+                return Ok(vec![Box::new(source)]);
+            }
+            Err(e) => eprintln!("AF_PACKET soketi kurulamadı ({}), filtre ve tampon ayarı kullanıcı alanında pnet ile sürdürülecek", e),
+        }
+    }
+
+    Ok(vec![Box::new(LiveSource::open(interface, options)?)])
+}
+
+#[cfg(target_os = "linux")]
+fn open_rings(
+    interface: &NetworkInterface,
+    ring: &RingConfig,
+    options: &CaptureOptions,
+    program: Option<&[bpf::Instruction]>,
+) -> io::Result<Vec<Box<dyn PacketSource + Send>>> {
+    let fanout = (ring.workers > 1).then_some(ring.fanout_group);
+    (0..ring.workers)
+        .map(|_| {
+            RingSource::open(interface, ring, options, program, fanout)
+                .map(|source| Box::new(source) as Box<dyn PacketSource + Send>)
+        })
+        .collect()
+}
+
+#[cfg(not(target_os = "linux"))]
+fn open_live_sources(
+    selected: &[(NetworkInterface, CaptureOptions)],
+    config: &Config,
+) -> io::Result<Vec<Box<dyn PacketSource + Send>>> {
+    if let CaptureBackend::Ring(_) = config.backend {
+        eprintln!("TPACKET_V3 halkası yalnızca Linux'ta destekleniyor, pnet kanalı kullanılacak");
+    }
+    selected
+        .iter()
+        .map(|(interface, options)| {
+            LiveSource::open(interface, options).map(|source| Box::new(source) as Box<dyn PacketSource + Send>)
+        })
+        .collect()
+}
+
+/// pcap/pcapng dosyasındaki çerçeveleri orijinal zamanlamaya `speed`
+/// katsayısıyla uyarak canlı yakalamayla aynı yoldan geçirir.
+pub async fn start_replay(
+    tx: broadcast::Sender<String>,
+    path: &Path,
+    speed: ReplaySpeed,
+    config: &Config,
+    stop: Arc<AtomicBool>,
+    stats: Arc<Stats>,
+) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    let source = FileSource::open(path, speed)
+        .map_err(|e| format!("Kayıt dosyası açılamadı ({}): {}", path.display(), e))?;
+    let reader = Arc::new(open_geoip_reader()?);
+
+    println!("Kayıt oynatma başladı: {} (hız: {:?})", path.display(), speed);
+
+    let count = run_pipeline(vec![Box::new(source)], reader, tx, config, stop, stats, None, false).await?;
+
+    println!("Kayıt oynatma tamamlandı: {} paket", count);
+    Ok(())
+}
+
+/// Sentetik akışları canlı yakalamayla aynı yoldan geçirir; `max_flows`
+/// verilmişse akışlar bitince tamamlanır.
+pub async fn start_synthetic(
+    tx: broadcast::Sender<String>,
+    synthetic: &SyntheticConfig,
+    config: &Config,
+    stop: Arc<AtomicBool>,
+    stats: Arc<Stats>,
+) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    // Adres örneklemesi ve çözümleme aynı veritabanı kopyasını kullanır
+    let reader = Arc::new(open_geoip_reader()?);
+    let source = SyntheticSource::new(synthetic, &reader)?;
+
+    println!("Sentetik trafik başladı: {} akış/sn", synthetic.rate);
+
+    let count = run_pipeline(vec![Box::new(source)], reader, tx, config, stop, stats, None, false).await?;
+
+    println!("Sentetik trafik tamamlandı: {} paket", count);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dns::DnsConfig;
+    use crate::source::MemorySource;
+    use crate::testutil::*;
+    use serde_json::Value;
+    use std::net::{Ipv4Addr, Ipv6Addr};
+
+    const CLIENT: Ipv4Addr = Ipv4Addr::new(10, 0, 0, 5);
+    const SERVER: Ipv4Addr = Ipv4Addr::new(151, 101, 1, 69);
+
+    fn test_config() -> Config {
+        Config { local_addrs: vec![IpAddr::V4(CLIENT)], ..config() }
+    }
+
+    /// Çerçeveleri işlemciden geçirip kaynağı kapatır; `flow_start`
+    /// dizileri açılarak olaylar gönderildikleri sırayla döner.
+    fn run(config: &Config, frames: Vec<(Duration, Vec<u8>)>) -> (Vec<Value>, Arc<Stats>) {
+        let (tx, mut rx) = mpsc::channel(EVENT_QUEUE_LEN);
+        let stats = Arc::new(Stats::default());
+        let mut processor = PacketProcessor::new(tx, geoip_reader(), config, stats.clone());
+        if let Some(dns) = config.dns {
+            processor.set_dns(Arc::new(DnsCache::new(dns)));
+        }
+        let mut source = MemorySource::new("test", LinkType::Ethernet, frames);
+        while let Some(frame) = source.next_frame().unwrap() {
+            processor.process(&frame);
+        }
+        processor.finish();
+        drop(processor);
+
+        let mut events = Vec::new();
+        while let Ok(message) = rx.try_recv() {
+            match serde_json::from_str(&message).unwrap() {
+                Value::Array(batch) => events.extend(batch),
+                event => events.push(event),
             }
         }
+        (events, stats)
+    }
+
+    fn of_type<'a>(events: &'a [Value], event_type: &str) -> Vec<&'a Value> {
+        events.iter().filter(|event| event["type"] == event_type).collect()
+    }
+
+    fn client_tcp(at: u64, seq: u32, ack: u32, flags: u8, payload: &[u8]) -> (Duration, Vec<u8>) {
+        let segment = tcp(50000, 443, seq, ack, flags, 65535, payload);
+        (ms(at), ethernet(CLIENT_MAC, SERVER_MAC, &ipv4(CLIENT, SERVER, 6, &segment)))
+    }
+
+    fn server_tcp(at: u64, seq: u32, ack: u32, flags: u8, payload: &[u8]) -> (Duration, Vec<u8>) {
+        let segment = tcp(443, 50000, seq, ack, flags, 65535, payload);
+        (ms(at), ethernet(SERVER_MAC, CLIENT_MAC, &ipv4(SERVER, CLIENT, 6, &segment)))
+    }
+
+    fn tcp_frame(src_port: u16, seq: u32, flags: u8) -> Vec<u8> {
+        let segment = tcp(src_port, 443, seq, 0, flags, 65535, &[]);
+        ethernet(CLIENT_MAC, SERVER_MAC, &ipv4(CLIENT, SERVER, 6, &segment))
+    }
+
+    #[test]
+    fn ipv4_tcp_connection_reports_start_and_end() {
+        let frames = vec![
+            client_tcp(0, 1000, 0, SYN, &[]),
+            server_tcp(10, 5000, 1001, SYN_ACK, &[]),
+            client_tcp(20, 1001, 5001, ACK, &[]),
+            client_tcp(30, 1001, 5001, PSH_ACK, &[0x42; 100]),
+            server_tcp(40, 5001, 1101, ACK, &[]),
+            client_tcp(50, 1101, 5001, FIN_ACK, &[]),
+            server_tcp(60, 5001, 1102, FIN_ACK, &[]),
+            client_tcp(70, 1102, 5002, ACK, &[]),
+        ];
+        let (events, stats) = run(&test_config(), frames);
+
+        let start = &events[0];
+        assert_eq!(start["type"], "flow_start");
+        assert_eq!(start["protocol"], "tcp");
+        assert_eq!(start["ip_version"], 4);
+        assert_eq!(start["direction"], "outbound");
+        assert_eq!(start["source"]["ip"], "10.0.0.5");
+        assert_eq!(start["source"]["port"], 50000);
+        assert_eq!(start["source"]["mac"], "02:00:00:00:00:01");
+        assert_eq!(start["source"]["latitude"], US.0);
+        assert_eq!(start["destination"]["ip"], "151.101.1.69");
+        assert_eq!(start["destination"]["port"], 443);
+        assert_eq!(start["destination"]["mac"], "02:00:00:00:00:02");
+        assert_eq!(start["destination"]["latitude"], DE.0);
+        assert_eq!(start["vlan_ids"], serde_json::json!([]));
+
+        let opened = &of_type(&events, "connection_opened")[0];
+        assert_eq!(opened["flow_id"], start["flow_id"]);
+        assert_eq!(opened["tcp_state"], "established");
+        let closed = &of_type(&events, "connection_closed")[0];
+        assert_eq!(closed["flow_id"], start["flow_id"]);
+
+        let end = events.last().unwrap();
+        assert_eq!(end["type"], "flow_end");
+        assert_eq!(end["flow_id"], start["flow_id"]);
+        assert_eq!(end["reason"], "closed");
+        assert_eq!(end["tcp_state"], "closed");
+        assert_eq!(end["duration_ms"], 70);
+        assert_eq!(end["source_to_destination"]["packets"], 5);
+        assert_eq!(end["source_to_destination"]["bytes"], 300);
+        assert_eq!(end["destination_to_source"]["packets"], 3);
+        assert_eq!(end["destination_to_source"]["bytes"], 120);
+
+        assert_eq!(of_type(&events, "flow_start").len(), 1);
+        assert_eq!(of_type(&events, "flow_end").len(), 1);
+        assert_eq!(stats.flows.started.get(), 1);
+        assert_eq!(stats.flows.merged.get(), 7);
+        assert_eq!(stats.flows.ended.get(), 1);
+    }
+
+    #[test]
+    fn ipv6_udp_flow_ends_at_shutdown() {
+        let client = Ipv6Addr::new(0x2001, 0x470, 0, 0, 0, 0, 0, 5);
+        let server = Ipv6Addr::new(0x2a00, 0x1450, 0, 0, 0, 0, 0, 0x200e);
+        let request = ipv6(client, server, 17, &udp(40000, 4433, &[1; 32]));
+        let response = ipv6(server, client, 17, &udp(4433, 40000, &[2; 64]));
+        let frames = vec![
+            (ms(0), ethernet(CLIENT_MAC, SERVER_MAC, &request)),
+            (ms(5), ethernet(CLIENT_MAC, SERVER_MAC, &request)),
+            (ms(20), ethernet(SERVER_MAC, CLIENT_MAC, &response)),
+        ];
+        let (events, _) = run(&test_config(), frames);
+        assert_eq!(events.len(), 2);
+
+        let start = &events[0];
+        assert_eq!(start["type"], "flow_start");
+        assert_eq!(start["protocol"], "udp");
+        assert_eq!(start["ip_version"], 6);
+        assert_eq!(start["direction"], "transit");
+        assert_eq!(start["source"]["ip"], "2001:470::5");
+        assert_eq!(start["destination"]["ip"], "2a00:1450::200e");
+        assert_eq!(start["destination"]["latitude"], US.0);
+        assert_eq!(start["tcp_state"], Value::Null);
+
+        let end = &events[1];
+        assert_eq!(end["type"], "flow_end");
+        assert_eq!(end["flow_id"], start["flow_id"]);
+        assert_eq!(end["reason"], "shutdown");
+        assert_eq!(end["source_to_destination"]["packets"], 2);
+        assert_eq!(end["source_to_destination"]["bytes"], 2 * (40 + 8 + 32));
+        assert_eq!(end["destination_to_source"]["packets"], 1);
+        assert_eq!(end["destination_to_source"]["bytes"], 40 + 8 + 64);
+    }
+
+    #[test]
+    fn syn_ack_seen_first_names_the_client_as_source() {
+        let frames = vec![
+            server_tcp(0, 5000, 1001, SYN_ACK, &[]),
+            client_tcp(10, 1001, 5001, ACK, &[]),
+        ];
+        let (events, _) = run(&test_config(), frames);
+
+        let starts = of_type(&events, "flow_start");
+        assert_eq!(starts.len(), 1);
+        assert_eq!(starts[0]["direction"], "outbound");
+        assert_eq!(starts[0]["source"]["ip"], "10.0.0.5");
+        assert_eq!(starts[0]["source"]["mac"], "02:00:00:00:00:01");
+        assert_eq!(starts[0]["destination"]["ip"], "151.101.1.69");
+        let end = &of_type(&events, "flow_end")[0];
+        assert_eq!(end["source_to_destination"]["packets"], 1);
+        assert_eq!(end["destination_to_source"]["packets"], 1);
+    }
+
+    #[test]
+    fn late_syn_swaps_hostnames_with_the_sides() {
+        let resolver = Ipv4Addr::new(192, 168, 1, 1);
+        let answer = dns_record(&[0xc0, 12], 1, 300, &SERVER.octets());
+        let response = udp(53, 53000, &dns_message(0x8180, "www.example.com", &[answer]));
+        let frames = vec![
+            (ms(0), ethernet(SERVER_MAC, CLIENT_MAC, &ipv4(resolver, CLIENT, 17, &response))),
+            // Yakalama bağlantının ortasında başladı; ilk paket sunucudan
+            server_tcp(10, 5001, 1001, PSH_ACK, &[0x42; 10]),
+            client_tcp(20, 1000, 0, SYN, &[]),
+        ];
+        let config = Config { dns: Some(DnsConfig::default()), ..test_config() };
+        let (events, stats) = run(&config, frames);
+        assert_eq!(stats.dns.addresses.get(), 1);
+
+        let start = &of_type(&events, "flow_start")[0];
+        assert_eq!(start["source"]["ip"], "151.101.1.69");
+        assert_eq!(start["source"]["hostname"], "www.example.com");
+        assert_eq!(start["destination"]["hostname"], Value::Null);
+
+        let end = &of_type(&events, "flow_end")[0];
+        assert_eq!(end["flow_id"], start["flow_id"]);
+        assert_eq!(end["direction"], "outbound");
+        assert_eq!(end["hostnames"]["source"], Value::Null);
+        assert_eq!(end["hostnames"]["destination"], "www.example.com");
+        assert_eq!(end["source_to_destination"]["packets"], 1);
+        assert_eq!(end["destination_to_source"]["packets"], 1);
+    }
+
+    #[test]
+    fn tcp_quality_is_reported_per_direction() {
+        let frames = vec![
+            client_tcp(0, 1000, 0, SYN, &[]),
+            server_tcp(40, 5000, 1001, SYN_ACK, &[]),
+            client_tcp(50, 1001, 5001, ACK, &[]),
+            client_tcp(60, 1001, 5001, PSH_ACK, &[0; 100]),
+            client_tcp(500, 1001, 5001, PSH_ACK, &[0; 100]),
+            server_tcp(510, 5001, 1101, PSH_ACK, &[0; 100]),
+            server_tcp(520, 5201, 1101, PSH_ACK, &[0; 100]),
+            server_tcp(530, 5101, 1101, PSH_ACK, &[0; 100]),
+            (ms(540), ethernet(CLIENT_MAC, SERVER_MAC, &ipv4(CLIENT, SERVER, 6, &tcp(50000, 443, 1101, 5301, ACK, 0, &[])))),
+        ];
+        let (events, _) = run(&test_config(), frames);
+
+        let zero_windows = of_type(&events, "tcp_zero_window");
+        assert_eq!(zero_windows.len(), 1);
+        assert_eq!(zero_windows[0]["side"], "source");
+
+        let end = &of_type(&events, "flow_end")[0];
+        assert_eq!(end["tcp"]["handshake_rtt_ms"], 50.0);
+        assert_eq!(end["tcp"]["source_to_destination"]["retransmissions"], 1);
+        assert_eq!(end["tcp"]["source_to_destination"]["out_of_order"], 0);
+        assert_eq!(end["tcp"]["source_to_destination"]["zero_windows"], 1);
+        assert_eq!(end["tcp"]["destination_to_source"]["retransmissions"], 0);
+        assert_eq!(end["tcp"]["destination_to_source"]["out_of_order"], 1);
+    }
+
+    #[test]
+    fn idle_flow_ends_before_shutdown() {
+        let first = ipv4(CLIENT, SERVER, 17, &udp(40000, 4433, &[0; 8]));
+        let second = ipv4(CLIENT, SERVER, 17, &udp(40001, 4433, &[0; 8]));
+        let frames = vec![
+            (ms(0), ethernet(CLIENT_MAC, SERVER_MAC, &first)),
+            (ms(61_000), ethernet(CLIENT_MAC, SERVER_MAC, &second)),
+        ];
+        let (events, _) = run(&test_config(), frames);
+
+        let starts = of_type(&events, "flow_start");
+        let ends = of_type(&events, "flow_end");
+        assert_eq!(starts.len(), 2);
+        assert_eq!(ends.len(), 2);
+        assert_eq!(ends[0]["flow_id"], starts[0]["flow_id"]);
+        assert_eq!(ends[0]["reason"], "idle");
+        assert_eq!(ends[1]["flow_id"], starts[1]["flow_id"]);
+        assert_eq!(ends[1]["reason"], "shutdown");
     }
-} 
\ No newline at end of file
+
+    #[test]
+    fn failed_handshakes_are_counted_in_stats() {
+        let other = ipv4(CLIENT, SERVER, 17, &udp(40000, 4433, &[0; 8]));
+        let frames = vec![
+            client_tcp(0, 1000, 0, SYN, &[]),
+            server_tcp(5, 0, 1001, RST | ACK, &[]),
+            (ms(10), tcp_frame(50001, 1000, SYN)),
+            // SYN-ACK gelmeden boşta kalan bağlantı
+            (ms(61_000), ethernet(CLIENT_MAC, SERVER_MAC, &other)),
+            // Kapanışta yarım kalan bağlantı başarısız sayılmaz
+            (ms(61_010), tcp_frame(50002, 1000, SYN)),
+        ];
+        let (events, stats) = run(&test_config(), frames);
+
+        let failed = of_type(&events, "connection_failed");
+        assert_eq!(failed.len(), 2);
+        assert_eq!(failed[0]["reason"], "refused");
+        assert_eq!(failed[0]["failed_handshakes"], 1);
+        assert_eq!(failed[1]["reason"], "timeout");
+        assert_eq!(failed[1]["failed_handshakes"], 2);
+        assert_eq!(stats.flows.failed_handshakes.get(), 2);
+        assert_eq!(stats.to_json()["flows"]["failed_handshakes"], 2);
+    }
+
+    #[test]
+    fn active_timeout_splits_long_flows() {
+        let mut config = test_config();
+        config.flows.active_timeout = Duration::from_secs(10);
+        let frames = vec![
+            client_tcp(0, 1000, 0, SYN, &[]),
+            server_tcp(10, 5000, 1001, SYN_ACK, &[]),
+            client_tcp(20, 1001, 5001, ACK, &[]),
+            client_tcp(5_000, 1001, 5001, PSH_ACK, &[0; 10]),
+            // Süre dolduktan sonra gelen ilk paket akışı böler
+            server_tcp(10_000, 5001, 1011, PSH_ACK, &[0; 10]),
+            client_tcp(10_010, 1011, 5011, FIN_ACK, &[]),
+            server_tcp(10_020, 5011, 1012, FIN_ACK, &[]),
+        ];
+        let (events, _) = run(&config, frames);
+
+        let starts = of_type(&events, "flow_start");
+        let ends = of_type(&events, "flow_end");
+        assert_eq!(starts.len(), 2);
+        assert_eq!(ends.len(), 2);
+        assert_eq!(ends[0]["flow_id"], starts[0]["flow_id"]);
+        assert_eq!(ends[0]["reason"], "active_timeout");
+        assert_eq!(ends[0]["tcp_state"], "established");
+        assert_eq!(ends[0]["source_to_destination"]["packets"], 3);
+
+        // Devam eden akış yönünü ve TCP durumunu korur
+        assert_ne!(starts[1]["flow_id"], starts[0]["flow_id"]);
+        assert_eq!(starts[1]["source"]["ip"], "10.0.0.5");
+        assert_eq!(starts[1]["source"]["mac"], "02:00:00:00:00:01");
+        assert_eq!(starts[1]["direction"], "outbound");
+        assert_eq!(starts[1]["tcp_state"], "established");
+        assert_eq!(ends[1]["flow_id"], starts[1]["flow_id"]);
+        assert_eq!(ends[1]["reason"], "closed");
+        assert_eq!(ends[1]["source_to_destination"]["packets"], 1);
+        assert_eq!(ends[1]["destination_to_source"]["packets"], 2);
+        assert_eq!(of_type(&events, "connection_opened").len(), 1);
+        assert_eq!(of_type(&events, "connection_closed").len(), 1);
+    }
+
+    #[test]
+    fn fragmented_datagram_opens_one_flow() {
+        let datagram = udp(40000, 4433, &[7; 40]);
+        let fragments = [
+            ipv4_fragment(CLIENT, SERVER, 17, 42, 24, false, &datagram[24..]),
+            ipv4_fragment(CLIENT, SERVER, 17, 42, 0, true, &datagram[..24]),
+            // Çakışan parça içeren ikinci datagram atılır
+            ipv4_fragment(CLIENT, SERVER, 17, 43, 0, true, &datagram[..24]),
+            ipv4_fragment(CLIENT, SERVER, 17, 43, 16, false, &datagram[16..]),
+        ];
+        let frames = fragments
+            .iter()
+            .enumerate()
+            .map(|(index, packet)| (ms(index as u64), ethernet(CLIENT_MAC, SERVER_MAC, packet)))
+            .collect();
+        let (events, stats) = run(&test_config(), frames);
+
+        let starts = of_type(&events, "flow_start");
+        assert_eq!(starts.len(), 1);
+        assert_eq!(starts[0]["source"]["port"], 40000);
+        assert_eq!(starts[0]["destination"]["port"], 4433);
+        assert_eq!(of_type(&events, "flow_end")[0]["source_to_destination"]["packets"], 1);
+
+        let decode = &stats.to_json()["decode"];
+        assert_eq!(decode["fragments"], 4);
+        assert_eq!(decode["reassembled"], 1);
+        assert_eq!(decode["fragments_overlapping"], 1);
+    }
+
+    const VTEP_A: Ipv4Addr = Ipv4Addr::new(93, 184, 216, 34);
+    const VTEP_B: Ipv4Addr = Ipv4Addr::new(151, 101, 2, 1);
+
+    fn vxlan(inner_frame: &[u8]) -> Vec<u8> {
+        let mut payload = vec![0x08, 0, 0, 0, 0, 0, 0x2a, 0];
+        payload.extend_from_slice(inner_frame);
+        ipv4(VTEP_A, VTEP_B, 17, &udp(50000, 4789, &payload))
+    }
+
+    fn gre(inner: &[u8]) -> Vec<u8> {
+        let mut payload = vec![0, 0, 0x08, 0x00];
+        payload.extend_from_slice(inner);
+        ipv4(VTEP_A, VTEP_B, 47, &payload)
+    }
+
+    #[test]
+    fn tunneled_flows_are_reported_with_their_layers() {
+        let inner = ipv4(CLIENT, SERVER, 17, &udp(40000, 4433, &[1; 8]));
+        let frame = ethernet(SERVER_MAC, CLIENT_MAC, &gre(&vxlan(&ethernet(CLIENT_MAC, SERVER_MAC, &inner))));
+        let (events, stats) = run(&test_config(), vec![(ms(0), frame)]);
+
+        let start = &of_type(&events, "flow_start")[0];
+        assert_eq!(start["source"]["ip"], "10.0.0.5");
+        assert_eq!(start["destination"]["port"], 4433);
+        assert_eq!(start["source"]["mac"], "02:00:00:00:00:01");
+        let tunnels = start["tunnels"].as_array().unwrap();
+        assert_eq!(tunnels.len(), 2);
+        assert_eq!(tunnels[0]["type"], "gre");
+        assert_eq!(tunnels[1]["type"], "vxlan");
+        assert_eq!(tunnels[1]["vni"], 42);
+        assert_eq!(stats.decode.tunneled.get(), 1);
+    }
+
+    #[test]
+    fn undecodable_and_too_deep_tunnels_fall_back_to_the_outer_flow() {
+        // Kesik iç Ethernet çerçevesi
+        let broken = ipv4(VTEP_A, VTEP_B, 17, &udp(50001, 4789, &[0x08, 0, 0, 0, 0, 0, 0x2a, 0, 1, 2, 3]));
+        // Sınırdan bir fazla iç içe VXLAN; en içteki UDP akışına ulaşılmaz
+        let mut nested = ipv4(CLIENT, SERVER, 17, &udp(40000, 4433, &[1; 8]));
+        for _ in 0..=MAX_TUNNEL_DEPTH {
+            nested = vxlan(&ethernet(CLIENT_MAC, SERVER_MAC, &nested));
+        }
+        let frames = vec![
+            (ms(0), ethernet(SERVER_MAC, CLIENT_MAC, &broken)),
+            (ms(1), ethernet(SERVER_MAC, CLIENT_MAC, &nested)),
+        ];
+        let (events, stats) = run(&test_config(), frames);
+
+        let starts = of_type(&events, "flow_start");
+        assert_eq!(starts.len(), 2);
+        assert_eq!(starts[0]["source"]["ip"], "93.184.216.34");
+        assert_eq!(starts[0]["source"]["port"], 50001);
+        assert_eq!(starts[0]["destination"]["port"], 4789);
+        assert_eq!(starts[0]["tunnels"], json!([]));
+        assert_eq!(starts[1]["source"]["ip"], "93.184.216.34");
+        assert_eq!(starts[1]["destination"]["port"], 4789);
+        assert_eq!(starts[1]["tunnels"].as_array().unwrap().len(), MAX_TUNNEL_DEPTH);
+        assert_eq!(stats.decode.tunnel_undecodable.get(), 1);
+        assert_eq!(stats.decode.tunnels_too_deep.get(), 1);
+    }
+
+    #[test]
+    fn fragments_inside_tunnels_are_reassembled() {
+        let datagram = udp(40000, 4433, &[7; 40]);
+        let first = ipv4_fragment(CLIENT, SERVER, 17, 9, 0, true, &datagram[..24]);
+        let last = ipv4_fragment(CLIENT, SERVER, 17, 9, 24, false, &datagram[24..]);
+        let frames = vec![
+            (ms(0), ethernet(SERVER_MAC, CLIENT_MAC, &gre(&first))),
+            (ms(1), ethernet(SERVER_MAC, CLIENT_MAC, &gre(&last))),
+        ];
+        let (events, stats) = run(&test_config(), frames);
+
+        let starts = of_type(&events, "flow_start");
+        assert_eq!(starts.len(), 1);
+        assert_eq!(starts[0]["source"]["ip"], "10.0.0.5");
+        assert_eq!(starts[0]["destination"]["port"], 4433);
+        assert_eq!(starts[0]["tunnels"][0]["type"], "gre");
+        assert_eq!(stats.decode.fragments.get(), 2);
+        assert_eq!(stats.decode.reassembled.get(), 1);
+    }
+
+    /// Okumaları hep zaman aşımına uğrayan ya da hep hata veren kaynak
+    struct StubSource {
+        name: &'static str,
+        error: io::ErrorKind,
+    }
+
+    impl PacketSource for StubSource {
+        fn name(&self) -> &str {
+            self.name
+        }
+
+        fn link_type(&self) -> LinkType {
+            LinkType::Ethernet
+        }
+
+        fn next_frame(&mut self) -> io::Result<Option<Frame<'_>>> {
+            std::thread::sleep(ms(5));
+            Err(io::Error::new(self.error, "test"))
+        }
+    }
+
+    #[tokio::test]
+    async fn failing_source_stops_the_capture_while_others_run() {
+        let (tx, _rx) = broadcast::channel(16);
+        let sources: Vec<Box<dyn PacketSource + Send>> = vec![
+            Box::new(StubSource { name: "eth0", error: io::ErrorKind::TimedOut }),
+            Box::new(StubSource { name: "eth1", error: io::ErrorKind::NotFound }),
+        ];
+        let stop = Arc::new(AtomicBool::new(false));
+        let stats = Arc::new(Stats::default());
+        let started = Instant::now();
+        let result = run_pipeline(sources, geoip_reader(), tx, &config(), stop.clone(), stats.clone(), None, true).await;
+
+        // Sağlam kaynak dururken bozulan kaynağın hatası döner; kapatma
+        // isteği yalnızca bu yakalamayı durdurur
+        let error = result.unwrap_err().to_string();
+        assert!(error.starts_with("eth1 okunamıyor"), "{}", error);
+        assert!(started.elapsed() < Duration::from_secs(5));
+        assert!(!stop.load(Ordering::Relaxed));
+        assert_eq!(stats.capture.read_errors.get(), 10);
+    }
+
+    #[test]
+    fn tracks_only_public_addresses() {
+        for ip in ["8.8.8.8", "151.101.1.69", "2a00:1450::1", "3ff0::1", "3ffe:ffff::1", "3fff:1000::1", "::ffff:8.8.4.4"] {
+            assert!(should_track_ip(ip.parse().unwrap()), "{}", ip);
+        }
+        for ip in [
+            "10.1.2.3", "192.168.1.1", "127.0.0.1", "255.255.255.255", "0.0.0.0", "::1", "fd00::1", "fe80::1",
+            "ff02::1", "2001:db8::1", "3fff::1", "3fff:fff::1", "::ffff:192.168.1.1", "4000::1",
+        ] {
+            assert!(!should_track_ip(ip.parse().unwrap()), "{}", ip);
+        }
+    }
+
+    #[test]
+    fn private_and_undecodable_traffic_is_not_reported() {
+        let private = ipv4(CLIENT, Ipv4Addr::new(192, 168, 1, 1), 17, &udp(40000, 53, &[0; 8]));
+        let frames = vec![
+            (ms(0), ethernet(CLIENT_MAC, SERVER_MAC, &private)),
+            (ms(1), vec![0xff; 10]),
+        ];
+        let (events, stats) = run(&test_config(), frames);
+
+        assert!(events.is_empty());
+        assert_eq!(stats.decode.frames.get(), 2);
+        assert_eq!(stats.decode.untracked.get(), 1);
+        assert_eq!(stats.decode.undecodable.get(), 1);
+        assert_eq!(stats.flows.started.get(), 0);
+    }
+}
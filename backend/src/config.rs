@@ -0,0 +1,310 @@
+use std::env;
+use std::net::IpAddr;
+use std::path::PathBuf;
+use std::str::FromStr;
+use std::time::Duration;
+
+use crate::dns::DnsConfig;
+use crate::filter::Filter;
+use crate::flow::FlowConfig;
+use crate::interfaces::{self, CaptureOptions, InterfaceSelection};
+use crate::reassembly::ReassemblyConfig;
+use crate::recorder::RecorderConfig;
+use crate::source::ReplaySpeed;
+use crate::synthetic::SyntheticConfig;
+
+#[derive(Clone)]
+pub enum CaptureMode {
+    Live,
+    Replay { path: PathBuf, speed: ReplaySpeed },
+    /// Yakalama yerine üretilen akışlar
+    Synthetic(SyntheticConfig),
+}
+
+/// Canlı yakalamada çerçevelerin çekirdekten nasıl alınacağı
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CaptureBackend {
+    /// pnet kanalı; her çağrıda tek çerçeve kopyalanır
+    Pnet,
+    /// Linux TPACKET_V3 bellek eşlemeli halka
+    Ring(RingConfig),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RingConfig {
+    /// Blok boyutu (bayt); sayfa boyutunun katı olmalı
+    pub block_size: usize,
+    pub block_count: usize,
+    /// Dolmayan blok en geç bu süre sonunda kullanıcı alanına verilir
+    pub block_timeout: Duration,
+    /// 1'den büyükse PACKET_FANOUT ile bu kadar soket ve çözümleme
+    /// thread'i açılır; akışlar çekirdekte iki yönlü hash ile dağıtılır
+    pub workers: usize,
+    pub fanout_group: u16,
+}
+
+impl Default for RingConfig {
+    fn default() -> Self {
+        Self {
+            block_size: 1 << 20,
+            block_count: 64,
+            block_timeout: Duration::from_millis(50),
+            workers: 1,
+            fanout_group: std::process::id() as u16,
+        }
+    }
+}
+
+// Halka blokları sayfa sınırında olmalı
+const RING_BLOCK_ALIGN: usize = 4096;
+const DEFAULT_STATS_INTERVAL: Duration = Duration::from_secs(5);
+const DEFAULT_METRICS_ADDR: &str = "127.0.0.1:8081";
+
+/// Ortam değişkenlerinden okunan uygulama yapılandırması
+pub struct Config {
+    pub mode: CaptureMode,
+    pub backend: CaptureBackend,
+    /// Boşsa ilk uygun arayüz seçilir
+    pub interfaces: Vec<InterfaceSelection>,
+    /// Seçimde ayrıca belirtilmeyen arayüzlerin seçenekleri
+    pub capture: CaptureOptions,
+    pub flows: FlowConfig,
+    pub fragments: ReassemblyConfig,
+    /// Arayüz adreslerine ek olarak yerel sayılacak adresler; kayıt
+    /// oynatmada akış yönünü belirlemek için
+    pub local_addrs: Vec<IpAddr>,
+    /// Yalnızca bu ifadeye uyan akışlar bildirilir
+    pub filter: Option<Filter>,
+    /// Çekirdeğe eklenen BPF programı başlangıçta yazdırılır
+    pub bpf_dump: bool,
+    /// `stats` mesajının yayın aralığı; `None` ise yalnızca istenince gönderilir
+    pub stats_interval: Option<Duration>,
+    /// Prometheus `/metrics` uç noktasının dinlediği adres
+    pub metrics_addr: String,
+    /// Verilmişse canlı yakalanan çerçeveler pcapng dosyalarına yazılır
+    pub record: Option<RecorderConfig>,
+    /// DNS yanıtlarından uçların adları öğrenilir; `None` ise kapalı
+    pub dns: Option<DnsConfig>,
+}
+
+impl Config {
+    pub fn from_env() -> Result<Self, String> {
+        let capture = capture_options_from_env()?;
+        Ok(Self {
+            mode: capture_mode_from_env()?,
+            backend: capture_backend_from_env()?,
+            interfaces: interfaces_from_env(capture)?,
+            capture,
+            flows: flow_config_from_env()?,
+            fragments: reassembly_config_from_env()?,
+            local_addrs: env_list("LOCAL_ADDRS")?,
+            filter: filter_from_env()?,
+            bpf_dump: env_flag("CAPTURE_BPF_DUMP", false)?,
+            stats_interval: Some(env_secs("STATS_INTERVAL", DEFAULT_STATS_INTERVAL)?).filter(|interval| !interval.is_zero()),
+            metrics_addr: env_value("METRICS_ADDR").unwrap_or_else(|| DEFAULT_METRICS_ADDR.to_string()),
+            record: recorder_config_from_env()?,
+            dns: dns_config_from_env()?,
+        })
+    }
+}
+
+/// Boş ya da tanımsız değişkenler için `None` döner.
+fn env_value(name: &str) -> Option<String> {
+    match env::var(name) {
+        Ok(value) if !value.trim().is_empty() => Some(value.trim().to_string()),
+        _ => None,
+    }
+}
+
+fn env_parse<T: FromStr>(name: &str, default: T) -> Result<T, String> {
+    match env_value(name) {
+        Some(value) => value
+            .parse()
+            .map_err(|_| format!("{} için geçersiz değer: {}", name, value)),
+        None => Ok(default),
+    }
+}
+
+/// 1/0, true/false, yes/no, on/off
+fn env_flag(name: &str, default: bool) -> Result<bool, String> {
+    match env_value(name) {
+        Some(value) => interfaces::parse_flag(&value).ok_or_else(|| format!("{} için geçersiz değer: {}", name, value)),
+        None => Ok(default),
+    }
+}
+
+/// Virgülle ayrılmış değer listesi
+fn env_list<T: FromStr>(name: &str) -> Result<Vec<T>, String> {
+    match env_value(name) {
+        Some(value) => value
+            .split(',')
+            .map(str::trim)
+            .filter(|item| !item.is_empty())
+            .map(|item| item.parse().map_err(|_| format!("{} için geçersiz değer: {}", name, item)))
+            .collect(),
+        None => Ok(Vec::new()),
+    }
+}
+
+/// Liste boşsa `default`
+fn env_list_or<T: FromStr>(name: &str, default: Vec<T>) -> Result<Vec<T>, String> {
+    let list = env_list(name)?;
+    Ok(if list.is_empty() { default } else { list })
+}
+
+fn env_secs(name: &str, default: Duration) -> Result<Duration, String> {
+    let secs = env_parse(name, default.as_secs_f64())?;
+    Duration::try_from_secs_f64(secs).map_err(|_| format!("{} için geçersiz süre: {}", name, secs))
+}
+
+/// Yakalama kaynağını ortam değişkenlerinden belirler. `REPLAY_FILE`
+/// verilmişse canlı arayüz yerine kayıt dosyası oynatılır, `SYNTHETIC`
+/// açıksa sentetik akışlar üretilir.
+fn capture_mode_from_env() -> Result<CaptureMode, String> {
+    let synthetic = env_flag("SYNTHETIC", false)?;
+    let path = match env_value("REPLAY_FILE") {
+        Some(_) if synthetic => return Err("REPLAY_FILE ve SYNTHETIC birlikte kullanılamaz".to_string()),
+        Some(path) => PathBuf::from(path),
+        None if synthetic => return Ok(CaptureMode::Synthetic(synthetic_config_from_env()?)),
+        None => return Ok(CaptureMode::Live),
+    };
+
+    let speed = match env_value("REPLAY_SPEED") {
+        Some(speed) => speed.parse()?,
+        None => ReplaySpeed::Multiplier(1.0),
+    };
+
+    Ok(CaptureMode::Replay { path, speed })
+}
+
+fn synthetic_config_from_env() -> Result<SyntheticConfig, String> {
+    let defaults = SyntheticConfig::default();
+    let config = SyntheticConfig {
+        rate: env_parse("SYNTHETIC_RATE", defaults.rate)?,
+        seed: env_parse("SYNTHETIC_SEED", defaults.seed)?,
+        peers: env_list_or("SYNTHETIC_PEERS", defaults.peers)?,
+        services: env_list_or("SYNTHETIC_PORTS", defaults.services)?,
+        local_networks: env_list_or("SYNTHETIC_LOCAL_NETS", defaults.local_networks)?,
+        max_flows: Some(env_parse("SYNTHETIC_FLOWS", 0)?).filter(|max_flows| *max_flows > 0),
+    };
+
+    if !(config.rate.is_finite() && config.rate > 0.0) {
+        return Err(format!("SYNTHETIC_RATE sıfırdan büyük olmalı: {}", config.rate));
+    }
+    Ok(config)
+}
+
+fn capture_options_from_env() -> Result<CaptureOptions, String> {
+    let defaults = CaptureOptions::default();
+    let snaplen = env_parse("CAPTURE_SNAPLEN", defaults.snaplen)?;
+    if snaplen == 0 {
+        return Err("CAPTURE_SNAPLEN sıfır olamaz".to_string());
+    }
+    Ok(CaptureOptions {
+        promiscuous: env_flag("CAPTURE_PROMISC", defaults.promiscuous)?,
+        snaplen,
+        buffer_size: env_value("CAPTURE_BUFFER_SIZE")
+            .map(|value| value.parse().map_err(|_| format!("CAPTURE_BUFFER_SIZE için geçersiz değer: {}", value)))
+            .transpose()?,
+    })
+}
+
+/// Virgülle ayrılmış seçimler; her seçimde ad, sıra (`#2`) ya da kalıp
+/// (`enp*`) ve isteğe bağlı `promisc=`, `snaplen=`, `buffer=` seçenekleri.
+/// `!` ile başlayan seçimler arayüzleri dışlar.
+fn interfaces_from_env(defaults: CaptureOptions) -> Result<Vec<InterfaceSelection>, String> {
+    match env_value("CAPTURE_INTERFACES") {
+        Some(value) => value
+            .split(',')
+            .filter(|entry| !entry.trim().is_empty())
+            .map(|entry| {
+                InterfaceSelection::parse(entry, defaults).map_err(|e| format!("CAPTURE_INTERFACES geçersiz, {}", e))
+            })
+            .collect(),
+        None => Ok(Vec::new()),
+    }
+}
+
+fn capture_backend_from_env() -> Result<CaptureBackend, String> {
+    match env_value("CAPTURE_BACKEND").map(|value| value.to_ascii_lowercase()).as_deref() {
+        None | Some("pnet") => Ok(CaptureBackend::Pnet),
+        Some("ring") => Ok(CaptureBackend::Ring(ring_config_from_env()?)),
+        Some(other) => Err(format!("CAPTURE_BACKEND için geçersiz değer: {} (pnet veya ring)", other)),
+    }
+}
+
+fn ring_config_from_env() -> Result<RingConfig, String> {
+    let defaults = RingConfig::default();
+    let config = RingConfig {
+        block_size: env_parse("RING_BLOCK_SIZE", defaults.block_size)?,
+        block_count: env_parse("RING_BLOCK_COUNT", defaults.block_count)?,
+        block_timeout: env_secs("RING_BLOCK_TIMEOUT", defaults.block_timeout)?,
+        workers: env_parse("RING_WORKERS", defaults.workers)?,
+        fanout_group: env_parse("RING_FANOUT_GROUP", defaults.fanout_group)?,
+    };
+
+    if config.block_size == 0 || !config.block_size.is_multiple_of(RING_BLOCK_ALIGN) {
+        return Err(format!("RING_BLOCK_SIZE {} baytın katı olmalı: {}", RING_BLOCK_ALIGN, config.block_size));
+    }
+    if config.block_count == 0 {
+        return Err("RING_BLOCK_COUNT sıfır olamaz".to_string());
+    }
+    if config.workers == 0 {
+        return Err("RING_WORKERS sıfır olamaz".to_string());
+    }
+    Ok(config)
+}
+
+fn flow_config_from_env() -> Result<FlowConfig, String> {
+    let defaults = FlowConfig::default();
+    Ok(FlowConfig {
+        idle_timeout: env_secs("FLOW_IDLE_TIMEOUT", defaults.idle_timeout)?,
+        active_timeout: env_secs("FLOW_ACTIVE_TIMEOUT", defaults.active_timeout)?,
+        update_interval: env_secs("FLOW_UPDATE_INTERVAL", defaults.update_interval)?,
+        max_flows: env_parse("FLOW_MAX_FLOWS", defaults.max_flows)?,
+    })
+}
+
+fn reassembly_config_from_env() -> Result<ReassemblyConfig, String> {
+    let defaults = ReassemblyConfig::default();
+    Ok(ReassemblyConfig {
+        timeout: env_secs("FRAGMENT_TIMEOUT", defaults.timeout)?,
+        max_datagrams: env_parse("FRAGMENT_MAX_DATAGRAMS", defaults.max_datagrams)?,
+        max_bytes: env_parse("FRAGMENT_MAX_BYTES", defaults.max_bytes)?,
+    })
+}
+
+/// `RECORD_DIR` verilmemişse kayıt kapalıdır.
+fn recorder_config_from_env() -> Result<Option<RecorderConfig>, String> {
+    let dir = match env_value("RECORD_DIR") {
+        Some(dir) => PathBuf::from(dir),
+        None => return Ok(None),
+    };
+    let defaults = RecorderConfig::default();
+    let rotate_interval = env_secs("RECORD_ROTATE_INTERVAL", defaults.rotate_interval.unwrap_or_default())?;
+    Ok(Some(RecorderConfig {
+        dir,
+        max_file_size: env_parse("RECORD_MAX_FILE_SIZE", defaults.max_file_size)?,
+        rotate_interval: Some(rotate_interval).filter(|interval| !interval.is_zero()),
+        max_files: env_parse("RECORD_MAX_FILES", defaults.max_files)?,
+    }))
+}
+
+/// `DNS_CACHE_SIZE=0` ad çözümlemeyi kapatır.
+fn dns_config_from_env() -> Result<Option<DnsConfig>, String> {
+    let defaults = DnsConfig::default();
+    let config = DnsConfig {
+        max_entries: env_parse("DNS_CACHE_SIZE", defaults.max_entries)?,
+        min_ttl: env_secs("DNS_MIN_TTL", defaults.min_ttl)?,
+    };
+    Ok(Some(config).filter(|config| config.max_entries > 0))
+}
+
+fn filter_from_env() -> Result<Option<Filter>, String> {
+    match env_value("CAPTURE_FILTER") {
+        Some(text) => Filter::parse(&text)
+            .map(Some)
+            .map_err(|e| format!("CAPTURE_FILTER geçersiz, {}", e)),
+        None => Ok(None),
+    }
+}
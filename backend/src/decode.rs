@@ -0,0 +1,1003 @@
+use pnet::packet::ethernet::{EtherType, EtherTypes, EthernetPacket};
+use pnet::packet::ip::{IpNextHeaderProtocol, IpNextHeaderProtocols};
+use pnet::packet::ipv4::{Ipv4Flags, Ipv4Packet};
+use pnet::packet::ipv6::Ipv6Packet;
+use pnet::packet::tcp::TcpPacket;
+use pnet::packet::udp::UdpPacket;
+use pnet::packet::vlan::VlanPacket;
+use pnet::util::MacAddr;
+use std::net::IpAddr;
+
+use crate::source::LinkType;
+
+// Bozuk ya da kasıtlı üretilmiş çerçevelerde sonsuz etiket zinciri olmasın
+const MAX_VLAN_TAGS: usize = 4;
+const MAX_IPV6_EXTENSION_HEADERS: usize = 8;
+
+/// Bağlantı katmanının ardından gelen ağ katmanı yükü
+#[derive(Clone, Copy)]
+pub enum NetworkLayer<'a> {
+    Ipv4(&'a [u8]),
+    Ipv6(&'a [u8]),
+}
+
+/// Bağlantı katmanı başlığı ve VLAN etiketleri ayrıştırılmış çerçeve
+pub struct LinkFrame<'a> {
+    /// Başlığında MAC adresi taşımayan bağlantı türlerinde (ham IP,
+    /// geri döngü ...) boş
+    pub src_mac: Option<MacAddr>,
+    pub dst_mac: Option<MacAddr>,
+    /// Dıştan içe doğru VLAN kimlikleri (QinQ'da önce servis etiketi)
+    pub vlan_ids: Vec<u16>,
+    pub network: NetworkLayer<'a>,
+}
+
+// Linux "cooked" başlıkları
+const SLL_HEADER_LEN: usize = 16;
+const SLL2_HEADER_LEN: usize = 20;
+// BSD geri döngü başlığındaki adres ailesi
+const NULL_HEADER_LEN: usize = 4;
+const AF_INET: u32 = 2;
+// AF_INET6 değeri işletim sistemine göre değişir (Linux, NetBSD/OpenBSD,
+// FreeBSD, macOS)
+const AF_INET6: [u32; 4] = [10, 24, 28, 30];
+// Radiotap bayrakları alanında çerçeve sonunda FCS olduğunu belirten bit
+const RADIOTAP_FLAGS_PRESENT: u32 = 1 << 1;
+const RADIOTAP_FLAGS_FCS: u8 = 0x10;
+const RADIOTAP_EXT_PRESENT: u32 = 1 << 31;
+const IEEE80211_FCS_LEN: usize = 4;
+const IEEE80211_HEADER_LEN: usize = 24;
+const LLC_SNAP_HEADER: [u8; 6] = [0xaa, 0xaa, 0x03, 0x00, 0x00, 0x00];
+
+fn is_vlan_tag(ethertype: EtherType) -> bool {
+    ethertype == EtherTypes::Vlan || ethertype == EtherTypes::PBridge || ethertype == EtherTypes::QinQ
+}
+
+/// Çerçeveyi bağlantı türüne göre çözer. Desteklenmeyen bağlantı türleri ve
+/// IP taşımayan çerçeveler için `None` döner.
+pub fn decode_link(link_type: LinkType, data: &[u8]) -> Option<LinkFrame<'_>> {
+    match link_type {
+        LinkType::Ethernet => decode_ethernet(data),
+        LinkType::LinuxSll => decode_sll(data),
+        LinkType::LinuxSll2 => decode_sll2(data),
+        LinkType::Raw => decode_raw(data),
+        LinkType::Null => {
+            let family = data.get(..NULL_HEADER_LEN)?;
+            let family = [family[0], family[1], family[2], family[3]];
+            // Yakalayan makinenin bayt sırası bilinmez; aile değerleri küçük
+            // olduğundan anlamlı olan sıra seçilir
+            let family = match u32::from_le_bytes(family) {
+                family if family <= u16::MAX as u32 => family,
+                _ => u32::from_be_bytes(family),
+            };
+            decode_address_family(family, &data[NULL_HEADER_LEN..])
+        }
+        LinkType::Loop => {
+            let family = data.get(..NULL_HEADER_LEN)?;
+            let family = u32::from_be_bytes([family[0], family[1], family[2], family[3]]);
+            decode_address_family(family, &data[NULL_HEADER_LEN..])
+        }
+        LinkType::Radiotap => decode_radiotap(data),
+        LinkType::Other(_) => None,
+    }
+}
+
+/// Ethernet başlığını çözer, tekli ve iç içe (802.1Q/802.1ad) VLAN
+/// etiketlerini açar. IP dışındaki EtherType'lar için `None` döner.
+pub fn decode_ethernet(data: &[u8]) -> Option<LinkFrame<'_>> {
+    let ethernet = EthernetPacket::new(data)?;
+    let (vlan_ids, network) = decode_ethertype(
+        ethernet.get_ethertype(),
+        &data[EthernetPacket::minimum_packet_size()..],
+    )?;
+
+    Some(LinkFrame {
+        src_mac: Some(ethernet.get_source()),
+        dst_mac: Some(ethernet.get_destination()),
+        vlan_ids,
+        network,
+    })
+}
+
+/// EtherType'ı izleyen VLAN etiketlerini açıp IP yükünü bulur.
+fn decode_ethertype(mut ethertype: EtherType, data: &[u8]) -> Option<(Vec<u16>, NetworkLayer<'_>)> {
+    let mut offset = 0;
+    let mut vlan_ids = Vec::new();
+
+    while is_vlan_tag(ethertype) {
+        if vlan_ids.len() >= MAX_VLAN_TAGS {
+            return None;
+        }
+        let vlan = VlanPacket::new(&data[offset..])?;
+        vlan_ids.push(vlan.get_vlan_identifier());
+        ethertype = vlan.get_ethertype();
+        offset += VlanPacket::minimum_packet_size();
+    }
+
+    let payload = &data[offset..];
+    let network = match ethertype {
+        EtherTypes::Ipv4 => NetworkLayer::Ipv4(payload),
+        EtherTypes::Ipv6 => NetworkLayer::Ipv6(payload),
+        _ => return None,
+    };
+    Some((vlan_ids, network))
+}
+
+/// Başlıktaki bağlantı adresi 6 baytlık bir MAC ise onu döndürür.
+fn link_address(len: u8, address: &[u8]) -> Option<MacAddr> {
+    match (len, address) {
+        (6, [a, b, c, d, e, f, ..]) => Some(MacAddr::new(*a, *b, *c, *d, *e, *f)),
+        _ => None,
+    }
+}
+
+/// Linux SLL: paket türü (2), ARPHRD türü (2), adres uzunluğu (2),
+/// adres (8), protokol (2). Yalnızca gönderenin adresi bulunur.
+fn decode_sll(data: &[u8]) -> Option<LinkFrame<'_>> {
+    let header = data.get(..SLL_HEADER_LEN)?;
+    let ethertype = EtherType::new(u16::from_be_bytes([header[14], header[15]]));
+    let (vlan_ids, network) = decode_ethertype(ethertype, &data[SLL_HEADER_LEN..])?;
+
+    Some(LinkFrame {
+        src_mac: link_address(header[5], &header[6..14]),
+        dst_mac: None,
+        vlan_ids,
+        network,
+    })
+}
+
+/// Linux SLL2: protokol (2), ayrılmış (2), arayüz sırası (4), ARPHRD türü
+/// (2), paket türü (1), adres uzunluğu (1), adres (8).
+fn decode_sll2(data: &[u8]) -> Option<LinkFrame<'_>> {
+    let header = data.get(..SLL2_HEADER_LEN)?;
+    let ethertype = EtherType::new(u16::from_be_bytes([header[0], header[1]]));
+    let (vlan_ids, network) = decode_ethertype(ethertype, &data[SLL2_HEADER_LEN..])?;
+
+    Some(LinkFrame {
+        src_mac: link_address(header[11], &header[12..20]),
+        dst_mac: None,
+        vlan_ids,
+        network,
+    })
+}
+
+/// Başlıksız IP; sürüm ilk baytın üst yarısından okunur.
+fn decode_raw(data: &[u8]) -> Option<LinkFrame<'_>> {
+    let network = match data.first()? >> 4 {
+        4 => NetworkLayer::Ipv4(data),
+        6 => NetworkLayer::Ipv6(data),
+        _ => return None,
+    };
+    Some(LinkFrame {
+        src_mac: None,
+        dst_mac: None,
+        vlan_ids: Vec::new(),
+        network,
+    })
+}
+
+fn decode_address_family(family: u32, data: &[u8]) -> Option<LinkFrame<'_>> {
+    let network = match family {
+        AF_INET => NetworkLayer::Ipv4(data),
+        family if AF_INET6.contains(&family) => NetworkLayer::Ipv6(data),
+        _ => return None,
+    };
+    Some(LinkFrame {
+        src_mac: None,
+        dst_mac: None,
+        vlan_ids: Vec::new(),
+        network,
+    })
+}
+
+/// Radiotap başlığını atlayıp 802.11 veri çerçevesini çözer. Şifreli
+/// (korumalı) çerçevelerin yükü okunamadığından atlanır.
+fn decode_radiotap(data: &[u8]) -> Option<LinkFrame<'_>> {
+    let header = data.get(..8)?;
+    let len = u16::from_le_bytes([header[2], header[3]]) as usize;
+    let present = u32::from_le_bytes([header[4], header[5], header[6], header[7]]);
+    let mut frame = data.get(len..)?;
+
+    // Bayraklar alanı varsa, ondan önceki tek alan 8 baytlık TSFT'dir; ek
+    // `present` sözcükleri alanları kaydırır
+    if present & RADIOTAP_FLAGS_PRESENT != 0 {
+        let mut offset = 8;
+        let mut word = present;
+        while word & RADIOTAP_EXT_PRESENT != 0 {
+            let next = data.get(offset..offset + 4)?;
+            word = u32::from_le_bytes([next[0], next[1], next[2], next[3]]);
+            offset += 4;
+        }
+        if present & 1 != 0 {
+            // TSFT 8 bayta hizalı
+            offset = (offset + 7) & !7;
+            offset += 8;
+        }
+        // Alan başlığın dışına taşıyorsa başlık bozuktur
+        let flags = *data[..len].get(offset)?;
+        if flags & RADIOTAP_FLAGS_FCS != 0 {
+            frame = frame.get(..frame.len().checked_sub(IEEE80211_FCS_LEN)?)?;
+        }
+    }
+
+    decode_ieee80211(frame)
+}
+
+fn decode_ieee80211(data: &[u8]) -> Option<LinkFrame<'_>> {
+    let header = data.get(..IEEE80211_HEADER_LEN)?;
+    let frame_control = header[0];
+    let flags = header[1];
+    // Yalnızca veri çerçeveleri (tür 2) IP taşır
+    if (frame_control >> 2) & 0x3 != 2 {
+        return None;
+    }
+    let subtype = frame_control >> 4;
+    // Yüksüz alt türler (null, CF-ack ...) 3. bit ile işaretlidir
+    if subtype & 0x4 != 0 {
+        return None;
+    }
+    // Korumalı çerçeve
+    if flags & 0x40 != 0 {
+        return None;
+    }
+
+    let to_ds = flags & 0x1 != 0;
+    let from_ds = flags & 0x2 != 0;
+    let address = |offset: usize| {
+        MacAddr::new(
+            data[offset],
+            data[offset + 1],
+            data[offset + 2],
+            data[offset + 3],
+            data[offset + 4],
+            data[offset + 5],
+        )
+    };
+
+    let mut offset = IEEE80211_HEADER_LEN;
+    let (src_mac, dst_mac) = match (to_ds, from_ds) {
+        (false, false) => (address(10), address(4)),
+        (true, false) => (address(10), address(16)),
+        (false, true) => (address(16), address(4)),
+        (true, true) => {
+            let address4 = data.get(offset..offset + 6)?;
+            offset += 6;
+            (
+                MacAddr::new(address4[0], address4[1], address4[2], address4[3], address4[4], address4[5]),
+                address(16),
+            )
+        }
+    };
+
+    // QoS veri çerçevelerinde 2 baytlık QoS alanı, sıra bayrağı varsa
+    // ardından 4 baytlık HT kontrol alanı gelir
+    if subtype & 0x8 != 0 {
+        offset += 2;
+        if flags & 0x80 != 0 {
+            offset += 4;
+        }
+    }
+
+    let llc = data.get(offset..offset + LLC_SNAP_HEADER.len() + 2)?;
+    if llc[..LLC_SNAP_HEADER.len()] != LLC_SNAP_HEADER {
+        return None;
+    }
+    let ethertype = EtherType::new(u16::from_be_bytes([llc[6], llc[7]]));
+    let (vlan_ids, network) = decode_ethertype(ethertype, &data[offset + llc.len()..])?;
+
+    Some(LinkFrame {
+        src_mac: Some(src_mac),
+        dst_mac: Some(dst_mac),
+        vlan_ids,
+        network,
+    })
+}
+
+/// Parçalanmış datagramın bir parçası
+#[derive(Debug, Clone, Copy)]
+pub struct Fragment {
+    pub id: u32,
+    /// Parçanın datagram içindeki bayt konumu
+    pub offset: usize,
+    /// Arkasından başka parça geliyor mu
+    pub more: bool,
+}
+
+/// IP başlığı (ve IPv6 uzantı başlıkları) çözülmüş paket
+pub struct IpPacket<'a> {
+    pub src: IpAddr,
+    pub dst: IpAddr,
+    /// Uzantı başlıkları atlandıktan sonraki taşıma katmanı protokolü
+    pub protocol: IpNextHeaderProtocol,
+    /// Başlıklar dahil IP paketinin uzunluğu
+    pub len: usize,
+    /// Taşıma katmanı başlığıyla başlayan yük
+    pub payload: &'a [u8],
+    /// Başlıkta bildirilen yük uzunluğu; kesilmiş yakalamalarda
+    /// `payload.len()` değerinden büyük olabilir
+    pub payload_len: usize,
+    /// Parçalardan biriyse `payload` taşıma başlığı yerine parça verisidir
+    pub fragment: Option<Fragment>,
+}
+
+pub fn decode_ip(network: NetworkLayer<'_>) -> Option<IpPacket<'_>> {
+    match network {
+        NetworkLayer::Ipv4(data) => decode_ipv4(data),
+        NetworkLayer::Ipv6(data) => decode_ipv6(data),
+    }
+}
+
+/// Birleştirilmiş parça verisinden, ilk parçanın başlık bilgileriyle tam
+/// paketi oluşturur.
+pub fn reassembled_ip<'a>(fragment: &IpPacket, data: &'a [u8]) -> Option<IpPacket<'a>> {
+    let header_len = fragment.len.saturating_sub(fragment.payload_len);
+    let (protocol, offset, header_len) = if fragment.src.is_ipv6() {
+        // Parça başlığından sonra da uzantı başlıkları olabilir; birleşen
+        // pakette parça başlığının kendisi yer almaz
+        match skip_ipv6_extensions(fragment.protocol, data, 0, data.len())? {
+            (protocol, offset, None) => (protocol, offset, header_len.saturating_sub(8)),
+            (_, _, Some(_)) => return None,
+        }
+    } else {
+        (fragment.protocol, 0, header_len)
+    };
+
+    Some(IpPacket {
+        src: fragment.src,
+        dst: fragment.dst,
+        protocol,
+        len: header_len + data.len(),
+        payload: &data[offset..],
+        payload_len: data.len() - offset,
+        fragment: None,
+    })
+}
+
+fn decode_ipv4(data: &[u8]) -> Option<IpPacket<'_>> {
+    let ip = Ipv4Packet::new(data)?;
+    let header_len = ip.get_header_length() as usize * 4;
+    let total_len = (ip.get_total_length() as usize).min(data.len());
+    if header_len < Ipv4Packet::minimum_packet_size() || header_len > total_len {
+        return None;
+    }
+    let more = ip.get_flags() & Ipv4Flags::MoreFragments != 0;
+    let offset = ip.get_fragment_offset() as usize * 8;
+
+    Some(IpPacket {
+        src: IpAddr::V4(ip.get_source()),
+        dst: IpAddr::V4(ip.get_destination()),
+        protocol: ip.get_next_level_protocol(),
+        len: ip.get_total_length() as usize,
+        payload: &data[header_len..total_len],
+        payload_len: (ip.get_total_length() as usize).saturating_sub(header_len),
+        fragment: (more || offset != 0).then_some(Fragment {
+            id: ip.get_identification() as u32,
+            offset,
+            more,
+        }),
+    })
+}
+
+fn decode_ipv6(data: &[u8]) -> Option<IpPacket<'_>> {
+    let ip = Ipv6Packet::new(data)?;
+    let header_len = Ipv6Packet::minimum_packet_size();
+    let len = header_len + ip.get_payload_length() as usize;
+    let end = len.min(data.len());
+    let (protocol, offset, fragment) = skip_ipv6_extensions(ip.get_next_header(), data, header_len, end)?;
+
+    Some(IpPacket {
+        src: IpAddr::V6(ip.get_source()),
+        dst: IpAddr::V6(ip.get_destination()),
+        protocol,
+        len,
+        payload: &data[offset..end],
+        payload_len: len.saturating_sub(offset),
+        fragment,
+    })
+}
+
+/// Uzantı başlıklarını taşıma katmanına ya da parça başlığına ulaşana kadar
+/// atlar; protokolü, yükün başladığı konumu ve varsa parça bilgisini döndürür.
+fn skip_ipv6_extensions(
+    mut next: IpNextHeaderProtocol,
+    data: &[u8],
+    mut offset: usize,
+    end: usize,
+) -> Option<(IpNextHeaderProtocol, usize, Option<Fragment>)> {
+    for _ in 0..MAX_IPV6_EXTENSION_HEADERS {
+        let header = data.get(offset..end)?;
+        let len = match next {
+            IpNextHeaderProtocols::Hopopt
+            | IpNextHeaderProtocols::Ipv6Route
+            | IpNextHeaderProtocols::Ipv6Opts
+            | IpNextHeaderProtocols::MobilityHeader
+            | IpNextHeaderProtocols::Hip
+            | IpNextHeaderProtocols::Shim6 => (*header.get(1)? as usize + 1) * 8,
+            IpNextHeaderProtocols::Ah => (*header.get(1)? as usize + 2) * 4,
+            IpNextHeaderProtocols::Ipv6Frag => {
+                let header = header.get(..8)?;
+                let offset_flags = u16::from_be_bytes([header[2], header[3]]);
+                let fragment = Fragment {
+                    id: u32::from_be_bytes([header[4], header[5], header[6], header[7]]),
+                    offset: (offset_flags >> 3) as usize * 8,
+                    more: offset_flags & 1 != 0,
+                };
+                // Tek parçalı (atomik) datagramlar normal paket gibi işlenir
+                if fragment.offset != 0 || fragment.more {
+                    return Some((IpNextHeaderProtocol::new(header[0]), offset + 8, Some(fragment)));
+                }
+                8
+            }
+            _ => return Some((next, offset, None)),
+        };
+
+        if header.len() < len {
+            return None;
+        }
+        next = IpNextHeaderProtocol::new(header[0]);
+        offset += len;
+    }
+
+    None
+}
+
+/// TCP başlığından akış takibi için gereken alanlar
+#[derive(Debug, Clone, Copy)]
+pub struct TcpHeader {
+    pub flags: u8,
+    pub sequence: u32,
+    pub window: u16,
+    /// Başlık hariç veri uzunluğu
+    pub payload_len: usize,
+}
+
+/// TCP/UDP başlığı çözülmüş paket
+pub struct Transport<'a> {
+    pub src_port: u16,
+    pub dst_port: u16,
+    /// UDP için `None`
+    pub tcp: Option<TcpHeader>,
+    /// Taşıma başlığından sonraki yakalanmış veri; kesilmiş yakalamalarda
+    /// `TcpHeader::payload_len` değerinden kısa olabilir
+    pub payload: &'a [u8],
+}
+
+pub fn decode_transport<'a>(ip: &IpPacket<'a>) -> Option<Transport<'a>> {
+    match ip.protocol {
+        IpNextHeaderProtocols::Tcp => {
+            let tcp = TcpPacket::new(ip.payload)?;
+            let header_len = tcp.get_data_offset() as usize * 4;
+            if header_len < TcpPacket::minimum_packet_size() || header_len > ip.payload.len() {
+                return None;
+            }
+
+            Some(Transport {
+                src_port: tcp.get_source(),
+                dst_port: tcp.get_destination(),
+                tcp: Some(TcpHeader {
+                    flags: tcp.get_flags(),
+                    sequence: tcp.get_sequence(),
+                    window: tcp.get_window(),
+                    payload_len: ip.payload_len.saturating_sub(header_len),
+                }),
+                payload: &ip.payload[header_len..],
+            })
+        }
+        IpNextHeaderProtocols::Udp => {
+            let udp = UdpPacket::new(ip.payload)?;
+            let end = (udp.get_length() as usize).clamp(UdpPacket::minimum_packet_size(), ip.payload.len());
+
+            Some(Transport {
+                src_port: udp.get_source(),
+                dst_port: udp.get_destination(),
+                tcp: None,
+                payload: &ip.payload[UdpPacket::minimum_packet_size()..end],
+            })
+        }
+        _ => None,
+    }
+}
+
+/// ICMP echo istek/yanıtı; tanımlayıcı akışta port yerine kullanılır
+#[derive(Debug, Clone, Copy)]
+pub struct IcmpEcho {
+    pub id: u16,
+    pub reply: bool,
+}
+
+/// ICMP hata mesajının içinde taşınan, hataya yol açan paketin başlıkları
+#[derive(Debug, Clone, Copy)]
+pub struct OriginalPacket {
+    pub src: IpAddr,
+    pub dst: IpAddr,
+    pub protocol: IpNextHeaderProtocol,
+    /// TCP/UDP portları ya da ICMP echo tanımlayıcısı
+    pub ports: Option<(u16, u16)>,
+}
+
+/// ICMP ya da ICMPv6 mesajı
+#[derive(Debug, Clone, Copy)]
+pub struct IcmpMessage {
+    pub v6: bool,
+    pub icmp_type: u8,
+    pub code: u8,
+    pub echo: Option<IcmpEcho>,
+    /// Yalnızca hata mesajlarında
+    pub original: Option<OriginalPacket>,
+}
+
+impl IcmpMessage {
+    pub fn is_error(&self) -> bool {
+        if self.v6 {
+            // ICMPv6'da 0-127 arası hata mesajları
+            self.icmp_type < 128
+        } else {
+            matches!(self.icmp_type, 3 | 4 | 5 | 11 | 12)
+        }
+    }
+
+    pub fn type_name(&self) -> &'static str {
+        match (self.v6, self.icmp_type) {
+            (false, 0) | (true, 129) => "echo_reply",
+            (false, 8) | (true, 128) => "echo_request",
+            (false, 3) | (true, 1) => "destination_unreachable",
+            (false, 4) => "source_quench",
+            (false, 5) => "redirect",
+            (false, 11) | (true, 3) => "time_exceeded",
+            (false, 12) | (true, 4) => "parameter_problem",
+            (true, 2) => "packet_too_big",
+            _ => "other",
+        }
+    }
+}
+
+pub fn decode_icmp(ip: &IpPacket) -> Option<IcmpMessage> {
+    let v6 = match ip.protocol {
+        IpNextHeaderProtocols::Icmp => false,
+        IpNextHeaderProtocols::Icmpv6 => true,
+        _ => return None,
+    };
+    // Tür, kod, sağlama toplamı ve türe bağlı 4 bayt
+    let header = ip.payload.get(..8)?;
+
+    let mut message = IcmpMessage {
+        v6,
+        icmp_type: header[0],
+        code: header[1],
+        echo: None,
+        original: None,
+    };
+
+    let reply = match (v6, message.icmp_type) {
+        (false, 8) | (true, 128) => Some(false),
+        (false, 0) | (true, 129) => Some(true),
+        _ => None,
+    };
+
+    if let Some(reply) = reply {
+        message.echo = Some(IcmpEcho {
+            id: u16::from_be_bytes([header[4], header[5]]),
+            reply,
+        });
+    } else if message.is_error() {
+        message.original = decode_original(v6, &ip.payload[8..]);
+    }
+
+    Some(message)
+}
+
+/// Hata mesajındaki orijinal IP başlığını ve taşıma katmanının ilk
+/// 8 baytını çözer; gömülü paket genellikle kesiktir.
+fn decode_original(v6: bool, data: &[u8]) -> Option<OriginalPacket> {
+    let ip = if v6 {
+        decode_ipv6(data)?
+    } else {
+        decode_ipv4(data)?
+    };
+
+    let ports = match ip.protocol {
+        // İlk olmayan parçada taşıma başlığı yok
+        _ if ip.fragment.is_some_and(|fragment| fragment.offset != 0) => None,
+        IpNextHeaderProtocols::Tcp | IpNextHeaderProtocols::Udp => {
+            let ports = ip.payload.get(..4)?;
+            Some((u16::from_be_bytes([ports[0], ports[1]]), u16::from_be_bytes([ports[2], ports[3]])))
+        }
+        IpNextHeaderProtocols::Icmp | IpNextHeaderProtocols::Icmpv6 => {
+            let header = ip.payload.get(..8)?;
+            let id = u16::from_be_bytes([header[4], header[5]]);
+            Some((id, id))
+        }
+        _ => None,
+    };
+
+    Some(OriginalPacket {
+        src: ip.src,
+        dst: ip.dst,
+        protocol: ip.protocol,
+        ports,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testutil::*;
+    use std::net::{Ipv4Addr, Ipv6Addr};
+
+    const CLIENT: Ipv4Addr = Ipv4Addr::new(192, 168, 1, 10);
+    const SERVER: Ipv4Addr = Ipv4Addr::new(93, 184, 216, 34);
+    const CLIENT_V6: Ipv6Addr = Ipv6Addr::new(0x2001, 0xdb8, 0, 0, 0, 0, 0, 1);
+    const SERVER_V6: Ipv6Addr = Ipv6Addr::new(0x2001, 0xdb8, 0, 0, 0, 0, 0, 2);
+    const AP_MAC: [u8; 6] = [0x02, 0, 0, 0, 0, 0x03];
+
+    fn packet_v4() -> Vec<u8> {
+        ipv4(CLIENT, SERVER, 17, &udp(40000, 53, &[1, 2, 3, 4]))
+    }
+
+    fn packet_v6() -> Vec<u8> {
+        ipv6(CLIENT_V6, SERVER_V6, 17, &udp(40000, 53, &[1, 2, 3, 4]))
+    }
+
+    fn sll(protocol: u16, address: &[u8], payload: &[u8]) -> Vec<u8> {
+        let mut frame = vec![0, 0, 0, 1];
+        frame.extend_from_slice(&(address.len() as u16).to_be_bytes());
+        let mut padded = address.to_vec();
+        padded.resize(8, 0);
+        frame.extend_from_slice(&padded);
+        frame.extend_from_slice(&protocol.to_be_bytes());
+        frame.extend_from_slice(payload);
+        frame
+    }
+
+    fn sll2(protocol: u16, address: &[u8], payload: &[u8]) -> Vec<u8> {
+        let mut frame = protocol.to_be_bytes().to_vec();
+        frame.extend_from_slice(&[0, 0, 0, 0, 0, 2, 0, 1, 0, address.len() as u8]);
+        let mut padded = address.to_vec();
+        padded.resize(8, 0);
+        frame.extend_from_slice(&padded);
+        frame.extend_from_slice(payload);
+        frame
+    }
+
+    fn family(header: [u8; 4], payload: &[u8]) -> Vec<u8> {
+        let mut frame = header.to_vec();
+        frame.extend_from_slice(payload);
+        frame
+    }
+
+    /// Dağıtım sisteminden gelen (FromDS) 802.11 veri çerçevesi
+    fn ieee80211(frame_control: u8, payload: &[u8]) -> Vec<u8> {
+        let mut frame = vec![frame_control, 0x02, 0, 0];
+        frame.extend_from_slice(&CLIENT_MAC);
+        frame.extend_from_slice(&AP_MAC);
+        frame.extend_from_slice(&SERVER_MAC);
+        frame.extend_from_slice(&[0, 0]);
+        // QoS alanı
+        if frame_control & 0x80 != 0 {
+            frame.extend_from_slice(&[0, 0]);
+        }
+        frame.extend_from_slice(&LLC_SNAP_HEADER);
+        let ethertype: u16 = if payload[0] >> 4 == 6 { 0x86dd } else { 0x0800 };
+        frame.extend_from_slice(&ethertype.to_be_bytes());
+        frame.extend_from_slice(payload);
+        frame
+    }
+
+    /// `fields` radiotap alanlarıdır; `fcs` verilirse çerçevenin sonuna eklenir.
+    fn radiotap(present: u32, fields: &[u8], frame: &[u8], fcs: bool) -> Vec<u8> {
+        let len = (8 + fields.len()) as u16;
+        let mut out = vec![0, 0];
+        out.extend_from_slice(&len.to_le_bytes());
+        out.extend_from_slice(&present.to_le_bytes());
+        out.extend_from_slice(fields);
+        out.extend_from_slice(frame);
+        if fcs {
+            out.extend_from_slice(&[0xde, 0xad, 0xbe, 0xef]);
+        }
+        out
+    }
+
+    /// Çözülen çerçeve: kaynak MAC, hedef MAC, VLAN'lar, IPv6 mı, IP paketi
+    type Expected = (Option<[u8; 6]>, Option<[u8; 6]>, Vec<u16>, bool, Vec<u8>);
+
+    fn decoded(link_type: LinkType, data: &[u8]) -> Option<Expected> {
+        let frame = decode_link(link_type, data)?;
+        let mac = |mac: MacAddr| mac.octets();
+        let (v6, network) = match frame.network {
+            NetworkLayer::Ipv4(network) => (false, network),
+            NetworkLayer::Ipv6(network) => (true, network),
+        };
+        Some((frame.src_mac.map(mac), frame.dst_mac.map(mac), frame.vlan_ids, v6, network.to_vec()))
+    }
+
+    #[test]
+    fn every_link_type_yields_the_ip_packet() {
+        let (v4, v6) = (packet_v4(), packet_v6());
+        let wifi = ieee80211(0x08, &v4);
+        let qos_wifi = ieee80211(0x88, &v6);
+        let cases: Vec<(&str, LinkType, Vec<u8>, Expected)> = vec![
+            (
+                "ethernet",
+                LinkType::Ethernet,
+                ethernet(CLIENT_MAC, SERVER_MAC, &v4),
+                (Some(CLIENT_MAC), Some(SERVER_MAC), vec![], false, v4.clone()),
+            ),
+            (
+                "sll",
+                LinkType::LinuxSll,
+                sll(0x86dd, &CLIENT_MAC, &v6),
+                (Some(CLIENT_MAC), None, vec![], true, v6.clone()),
+            ),
+            // MAC olmayan bağlantı adresleri (ör. tünel arayüzleri) yok sayılır
+            ("sll without mac", LinkType::LinuxSll, sll(0x0800, &[], &v4), (None, None, vec![], false, v4.clone())),
+            (
+                "sll2",
+                LinkType::LinuxSll2,
+                sll2(0x0800, &CLIENT_MAC, &v4),
+                (Some(CLIENT_MAC), None, vec![], false, v4.clone()),
+            ),
+            ("raw v4", LinkType::Raw, v4.clone(), (None, None, vec![], false, v4.clone())),
+            ("raw v6", LinkType::Raw, v6.clone(), (None, None, vec![], true, v6.clone())),
+            // Küçük sonlu makinede yakalanmış AF_INET ve büyük sonlu FreeBSD AF_INET6
+            ("null le", LinkType::Null, family([2, 0, 0, 0], &v4), (None, None, vec![], false, v4.clone())),
+            ("null be", LinkType::Null, family([0, 0, 0, 28], &v6), (None, None, vec![], true, v6.clone())),
+            ("loop", LinkType::Loop, family([0, 0, 0, 2], &v4), (None, None, vec![], false, v4.clone())),
+            (
+                "radiotap",
+                LinkType::Radiotap,
+                radiotap(0, &[], &wifi, false),
+                (Some(SERVER_MAC), Some(CLIENT_MAC), vec![], false, v4.clone()),
+            ),
+            // TSFT ve bayraklar alanı; FCS biti sondaki 4 baytı keser
+            (
+                "radiotap fcs",
+                LinkType::Radiotap,
+                radiotap(0b11, &[0, 0, 0, 0, 0, 0, 0, 0, RADIOTAP_FLAGS_FCS], &qos_wifi, true),
+                (Some(SERVER_MAC), Some(CLIENT_MAC), vec![], true, v6.clone()),
+            ),
+        ];
+
+        for (name, link_type, frame, expected) in cases {
+            assert_eq!(decoded(link_type, &frame), Some(expected), "{name}");
+        }
+    }
+
+    #[test]
+    fn truncated_and_non_ip_frames_are_skipped() {
+        let v4 = packet_v4();
+        let mut arp = ethernet(CLIENT_MAC, SERVER_MAC, &v4);
+        arp[12..14].copy_from_slice(&0x0806u16.to_be_bytes());
+        let mut protected = ieee80211(0x08, &v4);
+        protected[1] |= 0x40;
+        let cases: Vec<(&str, LinkType, Vec<u8>)> = vec![
+            ("ethernet", LinkType::Ethernet, ethernet(CLIENT_MAC, SERVER_MAC, &v4)[..13].to_vec()),
+            ("sll", LinkType::LinuxSll, sll(0x0800, &CLIENT_MAC, &[])[..SLL_HEADER_LEN - 1].to_vec()),
+            ("sll2", LinkType::LinuxSll2, sll2(0x0800, &CLIENT_MAC, &[])[..SLL2_HEADER_LEN - 1].to_vec()),
+            ("raw", LinkType::Raw, Vec::new()),
+            ("null", LinkType::Null, vec![2, 0, 0]),
+            ("loop", LinkType::Loop, vec![0, 0, 2]),
+            ("radiotap header", LinkType::Radiotap, radiotap(0, &[], &[], false)[..7].to_vec()),
+            // Başlık uzunluğu çerçeveden büyük
+            ("radiotap length", LinkType::Radiotap, vec![0, 0, 64, 0, 0, 0, 0, 0]),
+            // Bayraklar alanı bildirilmiş ama başlıkta yok
+            (
+                "radiotap flags",
+                LinkType::Radiotap,
+                radiotap(RADIOTAP_FLAGS_PRESENT, &[], &ieee80211(0x08, &v4), false),
+            ),
+            ("802.11 header", LinkType::Radiotap, radiotap(0, &[], &ieee80211(0x08, &v4)[..20], false)),
+            ("802.11 llc", LinkType::Radiotap, radiotap(0, &[], &ieee80211(0x08, &v4)[..28], false)),
+            ("arp", LinkType::Ethernet, arp),
+            ("sll arp", LinkType::LinuxSll, sll(0x0806, &CLIENT_MAC, &v4)),
+            ("raw version", LinkType::Raw, vec![0x50; 20]),
+            ("null family", LinkType::Null, family([7, 0, 0, 0], &v4)),
+            ("null data", LinkType::Radiotap, radiotap(0, &[], &ieee80211(0x48, &v4), false)),
+            ("management", LinkType::Radiotap, radiotap(0, &[], &ieee80211(0x80, &v4), false)),
+            ("protected", LinkType::Radiotap, radiotap(0, &[], &protected, false)),
+            ("other", LinkType::Other(147), v4.clone()),
+        ];
+
+        for (name, link_type, frame) in cases {
+            assert!(decode_link(link_type, &frame).is_none(), "{name}");
+        }
+    }
+
+    /// Her etiket `(TPID, VLAN kimliği)`; TCI'nin öncelik bitleri de doldurulur.
+    fn tagged(tags: &[(u16, u16)], payload: &[u8]) -> Vec<u8> {
+        let mut frame = SERVER_MAC.to_vec();
+        frame.extend_from_slice(&CLIENT_MAC);
+        for (tpid, _) in tags.iter().take(1) {
+            frame.extend_from_slice(&tpid.to_be_bytes());
+        }
+        for (index, (_, vid)) in tags.iter().enumerate() {
+            frame.extend_from_slice(&((5 << 13) | vid).to_be_bytes());
+            let next = tags.get(index + 1).map_or(0x0800, |(tpid, _)| *tpid);
+            frame.extend_from_slice(&next.to_be_bytes());
+        }
+        frame.extend_from_slice(payload);
+        frame
+    }
+
+    #[test]
+    fn vlan_and_qinq_tags_are_unwrapped_outermost_first() {
+        let v4 = packet_v4();
+        let vlan_ids = |frame: &[u8]| decode_ethernet(frame).map(|frame| frame.vlan_ids);
+
+        assert_eq!(vlan_ids(&tagged(&[(0x8100, 100)], &v4)), Some(vec![100]));
+        assert_eq!(vlan_ids(&tagged(&[(0x88a8, 10), (0x8100, 20)], &v4)), Some(vec![10, 20]));
+        // Eski 0x9100 QinQ etiketi
+        assert_eq!(vlan_ids(&tagged(&[(0x9100, 30), (0x8100, 4095)], &v4)), Some(vec![30, 4095]));
+        let four = [(0x88a8, 1), (0x8100, 2), (0x8100, 3), (0x8100, 4)];
+        assert_eq!(vlan_ids(&tagged(&four, &v4)), Some(vec![1, 2, 3, 4]));
+
+        let qinq = tagged(&[(0x88a8, 10), (0x8100, 20)], &v4);
+        let frame = decode_ethernet(&qinq).unwrap();
+        assert!(matches!(frame.network, NetworkLayer::Ipv4(network) if network == v4.as_slice()));
+
+        // Sınırı aşan etiket zinciri, kesik etiket ve IP taşımayan iç tür
+        let five = [(0x88a8, 1), (0x8100, 2), (0x8100, 3), (0x8100, 4), (0x8100, 5)];
+        assert_eq!(vlan_ids(&tagged(&five, &v4)), None);
+        assert_eq!(vlan_ids(&tagged(&[(0x8100, 100)], &[])[..16]), None);
+        let mut arp = tagged(&[(0x8100, 100)], &v4);
+        arp[16..18].copy_from_slice(&0x0806u16.to_be_bytes());
+        assert_eq!(vlan_ids(&arp), None);
+
+        // Cooked yakalamada da etiketler açılır
+        let cooked = sll(0x8100, &CLIENT_MAC, &tagged(&[(0, 7)], &v4)[14..]);
+        assert_eq!(decode_link(LinkType::LinuxSll, &cooked).map(|frame| frame.vlan_ids), Some(vec![7]));
+    }
+
+    /// `next` sonraki başlık, `len` sekizlik birim cinsinden uzunluk alanı
+    fn extension(next: u8, len: u8) -> Vec<u8> {
+        let mut header = vec![next, len];
+        header.resize((len as usize + 1) * 8, 0);
+        header
+    }
+
+    /// `offset` bayt cinsindendir ve 8'in katı olmalı.
+    fn fragment_header(next: u8, offset: usize, more: bool, id: u32) -> Vec<u8> {
+        let mut header = vec![next, 0];
+        header.extend_from_slice(&(offset as u16 | more as u16).to_be_bytes());
+        header.extend_from_slice(&id.to_be_bytes());
+        header
+    }
+
+    fn with_extensions(first: u8, headers: &[Vec<u8>], payload: &[u8]) -> Vec<u8> {
+        let mut body = headers.concat();
+        body.extend_from_slice(payload);
+        ipv6(CLIENT_V6, SERVER_V6, first, &body)
+    }
+
+    fn decode_v6(packet: &[u8]) -> Option<IpPacket<'_>> {
+        decode_ip(NetworkLayer::Ipv6(packet))
+    }
+
+    #[test]
+    fn ipv6_extension_headers_are_skipped_to_the_transport() {
+        let datagram = udp(40000, 53, &[1, 2, 3, 4]);
+
+        // Hop-by-hop, yönlendirme, hedef seçenekleri ve AH (4 baytlık birimler)
+        let mut ah = vec![17, 4];
+        ah.resize(24, 0);
+        let headers = [extension(43, 0), extension(60, 2), extension(51, 1), ah];
+        let packet = with_extensions(0, &headers, &datagram);
+        let ip = decode_v6(&packet).unwrap();
+        assert_eq!(ip.protocol, IpNextHeaderProtocols::Udp);
+        assert_eq!(ip.payload, datagram.as_slice());
+        assert_eq!(ip.payload_len, datagram.len());
+        assert_eq!(ip.len, 40 + 8 + 24 + 16 + 24 + datagram.len());
+        assert!(ip.fragment.is_none());
+
+        // Atomik parça başlığı da atlanır
+        let packet = with_extensions(44, &[fragment_header(17, 0, false, 9)], &datagram);
+        let ip = decode_v6(&packet).unwrap();
+        assert_eq!(ip.protocol, IpNextHeaderProtocols::Udp);
+        assert_eq!(ip.payload, datagram.as_slice());
+        assert!(ip.fragment.is_none());
+
+        // Gerçek parçada yük parça başlığından sonra başlar
+        let packet = with_extensions(0, &[extension(44, 0), fragment_header(17, 1448, true, 0xabcdef)], &datagram);
+        let ip = decode_v6(&packet).unwrap();
+        assert_eq!(ip.protocol, IpNextHeaderProtocols::Udp);
+        assert_eq!(ip.payload, datagram.as_slice());
+        let fragment = ip.fragment.unwrap();
+        assert_eq!((fragment.id, fragment.offset, fragment.more), (0xabcdef, 1448, true));
+
+        // Bilinmeyen sonraki başlık olduğu gibi bırakılır
+        let packet = with_extensions(59, &[], &[]);
+        let ip = decode_v6(&packet).unwrap();
+        assert_eq!(ip.protocol, IpNextHeaderProtocols::Ipv6NoNxt);
+    }
+
+    #[test]
+    fn truncated_or_endless_extension_chains_are_rejected() {
+        let datagram = udp(40000, 53, &[]);
+
+        // Uzunluk alanı paketin sonunu aşıyor
+        let mut short = extension(17, 2);
+        short.truncate(8);
+        assert!(decode_v6(&with_extensions(0, &[short], &[])).is_none());
+        // Uzunluk alanı bile yok
+        assert!(decode_v6(&with_extensions(60, &[vec![17]], &[])).is_none());
+        assert!(decode_v6(&with_extensions(44, &[vec![17, 0, 0, 1]], &[])).is_none());
+
+        let chain = |count: usize| {
+            let mut headers = vec![extension(60, 0); count];
+            headers.last_mut().unwrap()[0] = 17;
+            headers
+        };
+        assert!(decode_v6(&with_extensions(60, &chain(MAX_IPV6_EXTENSION_HEADERS - 1), &datagram)).is_some());
+        assert!(decode_v6(&with_extensions(60, &chain(MAX_IPV6_EXTENSION_HEADERS), &datagram)).is_none());
+    }
+
+    fn icmp(icmp_type: u8, code: u8, rest: [u8; 4], body: &[u8]) -> Vec<u8> {
+        let mut message = vec![icmp_type, code, 0, 0];
+        message.extend_from_slice(&rest);
+        message.extend_from_slice(body);
+        message
+    }
+
+    fn decode_icmp_v4(message: &[u8]) -> Option<IcmpMessage> {
+        let packet = ipv4(SERVER, CLIENT, 1, message);
+        decode_icmp(&decode_ip(NetworkLayer::Ipv4(&packet))?)
+    }
+
+    #[test]
+    fn icmp_errors_carry_the_original_flow() {
+        // Hata mesajlarında orijinal paketin yalnızca ilk 8 baytı bulunur
+        let original = ipv4(CLIENT, SERVER, 17, &udp(40000, 33434, &[0; 32]));
+        let message = decode_icmp_v4(&icmp(3, 3, [0; 4], &original[..28])).unwrap();
+        assert!(message.is_error());
+        assert_eq!(message.type_name(), "destination_unreachable");
+        assert!(message.echo.is_none());
+        let original = message.original.unwrap();
+        assert_eq!((original.src, original.dst), (IpAddr::V4(CLIENT), IpAddr::V4(SERVER)));
+        assert_eq!(original.protocol, IpNextHeaderProtocols::Udp);
+        assert_eq!(original.ports, Some((40000, 33434)));
+
+        // Süresi dolan echo isteği tanımlayıcısıyla eşleşir
+        let echo = ipv4(CLIENT, SERVER, 1, &icmp(8, 0, [0x12, 0x34, 0, 1], &[0; 8]));
+        let message = decode_icmp_v4(&icmp(11, 0, [0; 4], &echo[..28])).unwrap();
+        assert_eq!(message.type_name(), "time_exceeded");
+        assert_eq!(message.original.unwrap().ports, Some((0x1234, 0x1234)));
+
+        // İlk olmayan parçanın portu bilinemez
+        let fragment = ipv4_fragment(CLIENT, SERVER, 17, 7, 1480, false, &[0; 8]);
+        let original = decode_icmp_v4(&icmp(3, 4, [0; 4], &fragment)).unwrap().original.unwrap();
+        assert_eq!(original.ports, None);
+
+        // Port alanları kesikse orijinal paket kullanılmaz
+        let short = ipv4(CLIENT, SERVER, 6, &tcp(40000, 443, 0, 0, SYN, 0, &[]));
+        assert!(decode_icmp_v4(&icmp(3, 1, [0; 4], &short[..22])).unwrap().original.is_none());
+
+        // ICMPv6 paket çok büyük: IPv6 içinde TCP
+        let original = ipv6(CLIENT_V6, SERVER_V6, 6, &tcp(40000, 443, 1, 0, ACK, 0, &[0; 64]));
+        let packet = ipv6(SERVER_V6, CLIENT_V6, 58, &icmp(2, 0, 1280u32.to_be_bytes(), &original[..48]));
+        let message = decode_icmp(&decode_ip(NetworkLayer::Ipv6(&packet)).unwrap()).unwrap();
+        assert!(message.v6 && message.is_error());
+        assert_eq!(message.type_name(), "packet_too_big");
+        let original = message.original.unwrap();
+        assert_eq!((original.src, original.dst), (IpAddr::V6(CLIENT_V6), IpAddr::V6(SERVER_V6)));
+        assert_eq!(original.ports, Some((40000, 443)));
+    }
+
+    #[test]
+    fn icmp_echo_and_short_messages() {
+        let message = decode_icmp_v4(&icmp(8, 0, [0xbe, 0xef, 0, 1], &[0; 56])).unwrap();
+        assert_eq!(message.type_name(), "echo_request");
+        assert!(!message.is_error() && message.original.is_none());
+        let echo = message.echo.unwrap();
+        assert_eq!((echo.id, echo.reply), (0xbeef, false));
+
+        let packet = ipv6(SERVER_V6, CLIENT_V6, 58, &icmp(129, 0, [0xbe, 0xef, 0, 1], &[]));
+        let message = decode_icmp(&decode_ip(NetworkLayer::Ipv6(&packet)).unwrap()).unwrap();
+        assert_eq!(message.type_name(), "echo_reply");
+        assert!(message.echo.unwrap().reply);
+
+        // Türe bağlı alanlar eksik
+        assert!(decode_icmp_v4(&[8, 0, 0, 0, 0xbe, 0xef]).is_none());
+        // Hata mesajı orijinal paketsiz gelebilir
+        let message = decode_icmp_v4(&icmp(3, 1, [0; 4], &[])).unwrap();
+        assert!(message.is_error() && message.original.is_none());
+    }
+}
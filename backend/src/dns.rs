@@ -0,0 +1,459 @@
+use std::collections::{BTreeSet, HashMap};
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr};
+use std::sync::Mutex;
+use std::time::Duration;
+
+use pnet::packet::tcp::TcpFlags;
+
+use crate::decode::TcpHeader;
+use crate::flow::Endpoint;
+use crate::stream::StreamBuffer;
+
+pub const DNS_PORT: u16 = 53;
+
+const HEADER_LEN: usize = 12;
+const FLAG_RESPONSE: u16 = 0x8000;
+const OPCODE_MASK: u16 = 0x7800;
+const RCODE_MASK: u16 = 0x000f;
+const CLASS_IN: u16 = 1;
+const TYPE_A: u16 = 1;
+const TYPE_CNAME: u16 = 5;
+const TYPE_AAAA: u16 = 28;
+// Alan adı en fazla 255 bayt olabilir
+const MAX_NAME_LEN: usize = 255;
+// Sıkıştırma işaretçileri yalnızca geriye gösterebilir; yine de bozuk
+// mesajlarda zincir uzamasın
+const MAX_POINTER_JUMPS: usize = 32;
+const MAX_CNAME_CHAIN: usize = 16;
+// TCP üzerindeki mesajlar 2 baytlık uzunlukla başlar
+const TCP_MAX_MESSAGE_LEN: usize = 2 + 65_535;
+// Aynı anda birleştirilen TCP yanıt akışları; dolunca en eskisi atılır
+const MAX_TCP_STREAMS: usize = 256;
+const TCP_STREAM_TIMEOUT: Duration = Duration::from_secs(30);
+
+#[derive(Debug, Clone, Copy)]
+pub struct DnsConfig {
+    /// Önbellekteki en fazla adres; dolunca süresi ilk dolacak olan atılır
+    pub max_entries: usize,
+    /// Kısa TTL'li yanıtlar en az bu süre tutulur; bağlantılar çoğu zaman
+    /// yanıttan hemen sonra ama bazen TTL dolduktan sonra açılır
+    pub min_ttl: Duration,
+}
+
+impl Default for DnsConfig {
+    fn default() -> Self {
+        Self {
+            max_entries: 65_536,
+            min_ttl: Duration::from_secs(60),
+        }
+    }
+}
+
+/// Yanıttaki bir adresin sorulan ada çözümlenmesi
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Resolution {
+    pub ip: IpAddr,
+    /// Sorudaki ad; CNAME zinciri üzerinden gelen adresler de bu ada bağlanır
+    pub name: String,
+    /// Zincirdeki kayıtların en küçük TTL'i
+    pub ttl: Duration,
+}
+
+struct Record<'a> {
+    name: String,
+    record_type: u16,
+    ttl: u32,
+    rdata: &'a [u8],
+    // CNAME hedefi sıkıştırılmış olabileceğinden mesajdaki konumu gerekir
+    rdata_offset: usize,
+}
+
+/// Başarılı bir DNS yanıtındaki A, AAAA ve CNAME kayıtlarından adres→ad
+/// eşlemelerini çıkarır. Sorgu, hata yanıtı ya da soru içermeyen mesajda
+/// liste boştur; mesaj bozuksa `None` döner.
+pub fn parse_response(message: &[u8]) -> Option<Vec<Resolution>> {
+    let header = message.get(..HEADER_LEN)?;
+    let flags = u16::from_be_bytes([header[2], header[3]]);
+    let questions = u16::from_be_bytes([header[4], header[5]]);
+    let answers = u16::from_be_bytes([header[6], header[7]]);
+    if flags & FLAG_RESPONSE == 0 || flags & OPCODE_MASK != 0 || flags & RCODE_MASK != 0 || questions == 0 {
+        return Some(Vec::new());
+    }
+
+    // İlk soru sorulan addır; birden çok soru pratikte kullanılmaz
+    let mut offset = HEADER_LEN;
+    let mut queried = None;
+    for _ in 0..questions {
+        let (name, next) = read_name(message, offset)?;
+        message.get(next..next + 4)?;
+        offset = next + 4;
+        queried.get_or_insert(name);
+    }
+    let queried = queried?;
+    if queried.is_empty() {
+        return Some(Vec::new());
+    }
+
+    let mut records = Vec::new();
+    for _ in 0..answers {
+        let (name, next) = read_name(message, offset)?;
+        let fixed = message.get(next..next + 10)?;
+        let record_type = u16::from_be_bytes([fixed[0], fixed[1]]);
+        let class = u16::from_be_bytes([fixed[2], fixed[3]]);
+        let ttl = u32::from_be_bytes([fixed[4], fixed[5], fixed[6], fixed[7]]);
+        let rdata_len = u16::from_be_bytes([fixed[8], fixed[9]]) as usize;
+        let rdata_offset = next + 10;
+        let rdata = message.get(rdata_offset..rdata_offset + rdata_len)?;
+        offset = rdata_offset + rdata_len;
+        if class == CLASS_IN {
+            records.push(Record { name, record_type, ttl, rdata, rdata_offset });
+        }
+    }
+
+    // Sorulan addan CNAME'lerle ulaşılan adlar ve zincir boyunca en küçük TTL
+    let mut aliases = vec![(queried.clone(), u32::MAX)];
+    for _ in 0..MAX_CNAME_CHAIN {
+        let mut grown = false;
+        for record in records.iter().filter(|record| record.record_type == TYPE_CNAME) {
+            let ttl = match aliases.iter().find(|(alias, _)| *alias == record.name) {
+                Some((_, ttl)) => (*ttl).min(record.ttl),
+                None => continue,
+            };
+            let (target, _) = read_name(message, record.rdata_offset)?;
+            if !aliases.iter().any(|(alias, _)| *alias == target) {
+                aliases.push((target, ttl));
+                grown = true;
+            }
+        }
+        if !grown {
+            break;
+        }
+    }
+
+    let resolutions = records
+        .iter()
+        .filter_map(|record| {
+            let (_, chain_ttl) = aliases.iter().find(|(alias, _)| *alias == record.name)?;
+            let ip = match (record.record_type, record.rdata.len()) {
+                (TYPE_A, 4) => IpAddr::V4(Ipv4Addr::from(<[u8; 4]>::try_from(record.rdata).ok()?)),
+                (TYPE_AAAA, 16) => IpAddr::V6(Ipv6Addr::from(<[u8; 16]>::try_from(record.rdata).ok()?)),
+                _ => return None,
+            };
+            Some(Resolution {
+                ip,
+                name: queried.clone(),
+                ttl: Duration::from_secs(record.ttl.min(*chain_ttl) as u64),
+            })
+        })
+        .collect();
+    Some(resolutions)
+}
+
+/// `offset` konumundaki (sıkıştırılmış olabilen) adı küçük harfle ve
+/// sondaki nokta olmadan okur; adın ardındaki konumu da döndürür.
+fn read_name(message: &[u8], offset: usize) -> Option<(String, usize)> {
+    let mut name = String::new();
+    let mut position = offset;
+    // İlk işaretçiden sonra okuma başka yerde sürer; ad buradan sonra biter
+    let mut end = None;
+    let mut jumps = 0;
+
+    loop {
+        let len = *message.get(position)? as usize;
+        match len & 0xc0 {
+            0x00 if len == 0 => {
+                return Some((name, end.unwrap_or(position + 1)));
+            }
+            0x00 => {
+                let label = message.get(position + 1..position + 1 + len)?;
+                if name.len() + len + 1 > MAX_NAME_LEN {
+                    return None;
+                }
+                if !name.is_empty() {
+                    name.push('.');
+                }
+                for &byte in label {
+                    // Yazdırılamayan baytlar ve ad içindeki noktalar kaçışlanır
+                    match byte {
+                        b'.' | b'\\' => name.push_str(&format!("\\{}", byte as char)),
+                        0x21..=0x7e => name.push(byte.to_ascii_lowercase() as char),
+                        _ => name.push_str(&format!("\\{:03}", byte)),
+                    }
+                }
+                position += 1 + len;
+            }
+            0xc0 => {
+                let low = *message.get(position + 1)? as usize;
+                let target = (len & 0x3f) << 8 | low;
+                jumps += 1;
+                if target >= position || jumps > MAX_POINTER_JUMPS {
+                    return None;
+                }
+                end.get_or_insert(position + 2);
+                position = target;
+            }
+            // Genişletilmiş etiket türleri kullanılmıyor
+            _ => return None,
+        }
+    }
+}
+
+struct Entries {
+    names: HashMap<IpAddr, (String, Duration)>,
+    // Sona erme zamanına göre sıralı adresler
+    expiry: BTreeSet<(Duration, IpAddr)>,
+}
+
+/// DNS yanıtlarından öğrenilen adres→ad önbelleği. Tüm çözümleme
+/// thread'lerince paylaşılır; yanıt ile bağlantı farklı arayüzlerden ya da
+/// fanout'ta farklı thread'lerden geçebilir. Zaman olarak paketlerin
+/// yakalama zamanı kullanılır.
+pub struct DnsCache {
+    config: DnsConfig,
+    entries: Mutex<Entries>,
+}
+
+impl DnsCache {
+    pub fn new(config: DnsConfig) -> Self {
+        Self {
+            config,
+            entries: Mutex::new(Entries { names: HashMap::new(), expiry: BTreeSet::new() }),
+        }
+    }
+
+    /// Adresin adını kaydeder; adres başka bir ada çözümlenmişse en son
+    /// sorulan ad geçerli olur.
+    pub fn insert(&self, resolution: Resolution, now: Duration) {
+        let mut entries = self.entries.lock().unwrap();
+        let entries = &mut *entries;
+
+        while let Some(&(expires, ip)) = entries.expiry.first() {
+            if expires > now {
+                break;
+            }
+            entries.expiry.pop_first();
+            entries.names.remove(&ip);
+        }
+
+        if let Some((_, expires)) = entries.names.remove(&resolution.ip) {
+            entries.expiry.remove(&(expires, resolution.ip));
+        }
+        while entries.names.len() >= self.config.max_entries.max(1) {
+            match entries.expiry.pop_first() {
+                Some((_, ip)) => entries.names.remove(&ip),
+                None => break,
+            };
+        }
+
+        let expires = now + resolution.ttl.max(self.config.min_ttl);
+        entries.expiry.insert((expires, resolution.ip));
+        entries.names.insert(resolution.ip, (resolution.name, expires));
+    }
+
+    /// Süresi dolmamışsa adresin en son sorulan adı
+    pub fn lookup(&self, ip: IpAddr, now: Duration) -> Option<String> {
+        let entries = self.entries.lock().unwrap();
+        entries
+            .names
+            .get(&ip)
+            .filter(|(_, expires)| *expires > now)
+            .map(|(name, _)| name.clone())
+    }
+}
+
+struct TcpStream {
+    buffer: StreamBuffer,
+    last_seen: Duration,
+}
+
+/// TCP üzerinden gelen DNS yanıtlarını (sunucudan istemciye yön) birleştirir;
+/// büyük yanıtlar birden çok segmente bölünür.
+#[derive(Default)]
+pub struct TcpStreams {
+    // Sunucu ve istemci uçlarıyla
+    streams: HashMap<(Endpoint, Endpoint), TcpStream>,
+}
+
+impl TcpStreams {
+    /// Sunucudan gelen segmenti ekler ve tamamlanan mesajları döndürür.
+    /// SYN segmentinde yalnızca başlangıç sıra numarası alınır; FIN ya da
+    /// RST ile akış atılır.
+    pub fn push(&mut self, server: Endpoint, client: Endpoint, tcp: TcpHeader, payload: &[u8], now: Duration) -> Vec<Vec<u8>> {
+        let key = (server, client);
+        let sequence = tcp.sequence;
+        let syn = tcp.flags & TcpFlags::SYN != 0;
+        if syn {
+            self.streams.remove(&key);
+        }
+        if !self.streams.contains_key(&key) && (syn || !payload.is_empty()) {
+            if self.streams.len() >= MAX_TCP_STREAMS {
+                let oldest = self.streams.iter().min_by_key(|(_, stream)| stream.last_seen).map(|(key, _)| *key);
+                if let Some(oldest) = oldest {
+                    self.streams.remove(&oldest);
+                }
+            }
+            // SYN görülmediyse yakalama bir mesaj sınırında başlamış sayılır
+            let start = if syn { sequence.wrapping_add(1) } else { sequence };
+            self.streams.insert(key, TcpStream { buffer: StreamBuffer::new(start, TCP_MAX_MESSAGE_LEN), last_seen: now });
+        }
+
+        let mut messages = Vec::new();
+        if let Some(stream) = self.streams.get_mut(&key) {
+            stream.last_seen = now;
+            if !syn && stream.buffer.push(sequence, payload) {
+                loop {
+                    let data = stream.buffer.data();
+                    let len = match data.get(..2) {
+                        Some(prefix) => 2 + u16::from_be_bytes([prefix[0], prefix[1]]) as usize,
+                        None => break,
+                    };
+                    if data.len() < len {
+                        break;
+                    }
+                    messages.push(data[2..len].to_vec());
+                    stream.buffer.consume(len);
+                }
+            }
+        }
+        if tcp.flags & (TcpFlags::FIN | TcpFlags::RST) != 0 {
+            self.streams.remove(&key);
+        }
+        messages
+    }
+
+    /// Uzun süredir segment gelmeyen akışları atar.
+    pub fn expire(&mut self, now: Duration) {
+        self.streams.retain(|_, stream| now.saturating_sub(stream.last_seen) < TCP_STREAM_TIMEOUT);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testutil::{dns_message, dns_name, dns_record};
+
+    const RESPONSE: u16 = 0x8180;
+    // Sorudaki "www.example.com" adına ve "example.com" kısmına işaretçiler
+    const QUESTION: [u8; 2] = [0xc0, 12];
+    const EXAMPLE_COM: [u8; 2] = [0xc0, 16];
+
+    fn secs(secs: u64) -> Duration {
+        Duration::from_secs(secs)
+    }
+
+    #[test]
+    fn a_and_aaaa_answers_resolve_to_queried_name() {
+        let message = dns_message(
+            RESPONSE,
+            "WWW.Example.com",
+            &[
+                dns_record(&QUESTION, TYPE_A, 300, &[93, 184, 216, 34]),
+                dns_record(&QUESTION, TYPE_AAAA, 200, &"2606:2800:220:1::248".parse::<Ipv6Addr>().unwrap().octets()),
+            ],
+        );
+        let resolutions = parse_response(&message).unwrap();
+        assert_eq!(
+            resolutions,
+            vec![
+                Resolution { ip: "93.184.216.34".parse().unwrap(), name: "www.example.com".into(), ttl: secs(300) },
+                Resolution { ip: "2606:2800:220:1::248".parse().unwrap(), name: "www.example.com".into(), ttl: secs(200) },
+            ]
+        );
+    }
+
+    #[test]
+    fn cname_chain_resolves_to_queried_name_with_smallest_ttl() {
+        // Kayıtların sırası zincirin sırasıyla aynı olmak zorunda değil
+        let message = dns_message(
+            RESPONSE,
+            "www.example.com",
+            &[
+                dns_record(&dns_name("a.cdn.net"), TYPE_A, 120, &[151, 101, 1, 69]),
+                dns_record(&dns_name("other.org"), TYPE_A, 600, &[192, 0, 2, 1]),
+                dns_record(&dns_name("edge.example.com"), TYPE_CNAME, 60, &dns_name("a.cdn.net")),
+                dns_record(&QUESTION, TYPE_CNAME, 300, &[&b"\x04edge"[..], &EXAMPLE_COM].concat()),
+            ],
+        );
+        let resolutions = parse_response(&message).unwrap();
+        assert_eq!(
+            resolutions,
+            vec![Resolution { ip: "151.101.1.69".parse().unwrap(), name: "www.example.com".into(), ttl: secs(60) }]
+        );
+    }
+
+    #[test]
+    fn error_responses_and_queries_yield_nothing() {
+        let answers = [dns_record(&QUESTION, TYPE_A, 300, &[93, 184, 216, 34])];
+        // NXDOMAIN
+        assert_eq!(parse_response(&dns_message(0x8183, "www.example.com", &answers)), Some(Vec::new()));
+        // Sorgu
+        assert_eq!(parse_response(&dns_message(0x0100, "www.example.com", &answers)), Some(Vec::new()));
+    }
+
+    #[test]
+    fn truncated_messages_are_malformed() {
+        let message = dns_message(RESPONSE, "www.example.com", &[dns_record(&QUESTION, TYPE_A, 300, &[93, 184, 216, 34])]);
+        assert!(parse_response(&message).is_some());
+        assert_eq!(parse_response(&message[..message.len() - 2]), None);
+        assert_eq!(parse_response(&message[..HEADER_LEN - 1]), None);
+        // Sorudaki ad yarıda kesilmiş
+        assert_eq!(parse_response(&message[..HEADER_LEN + 6]), None);
+    }
+
+    #[test]
+    fn compression_pointers_are_followed_backwards_only() {
+        let mut message = dns_message(RESPONSE, "www.example.com", &[]);
+        let offset = message.len();
+        message.extend_from_slice(b"\x03foo");
+        message.extend_from_slice(&EXAMPLE_COM);
+        // Ad ilk işaretçinin ardında biter
+        assert_eq!(read_name(&message, offset), Some(("foo.example.com".to_string(), offset + 6)));
+
+        // Kendini gösteren işaretçi
+        let mut message = dns_message(RESPONSE, "www.example.com", &[]);
+        message[6..8].copy_from_slice(&1u16.to_be_bytes());
+        let offset = message.len();
+        message.extend(dns_record(&[0xc0, offset as u8], TYPE_A, 300, &[93, 184, 216, 34]));
+        assert_eq!(read_name(&message, offset), None);
+        assert_eq!(parse_response(&message), None);
+
+        // Birbirini gösteren iki işaretçi: geriye giden ilk sıçrama izlenir,
+        // ileriye giden ikincisi döngüyü keser
+        let mut message = dns_message(RESPONSE, "www.example.com", &[]);
+        let offset = message.len();
+        message.extend_from_slice(&[0xc0, offset as u8 + 2, 0xc0, offset as u8]);
+        assert_eq!(read_name(&message, offset + 2), None);
+        assert_eq!(read_name(&message, offset), None);
+    }
+
+    #[test]
+    fn names_are_lowercased_escaped_and_length_limited() {
+        let message = [&[5][..], b"A.b C", &[3], b"Com", &[0]].concat();
+        assert_eq!(read_name(&message, 0), Some(("a\\.b\\032c.com".to_string(), message.len())));
+
+        let label = [&[63][..], &[b'a'; 63]].concat();
+        let long = [label.repeat(5), vec![0]].concat();
+        assert_eq!(read_name(&long, 0), None);
+        assert_eq!(read_name(&[0xc0], 0), None);
+        assert_eq!(read_name(&[0x40, 0], 0), None);
+    }
+
+    #[test]
+    fn cache_keeps_short_ttls_for_min_ttl_and_evicts_soonest_expiring() {
+        let cache = DnsCache::new(DnsConfig { max_entries: 2, min_ttl: secs(60) });
+        let resolution = |ip: &str, name: &str, ttl: u64| Resolution { ip: ip.parse().unwrap(), name: name.into(), ttl: secs(ttl) };
+        cache.insert(resolution("192.0.2.1", "a.example", 10), secs(0));
+        cache.insert(resolution("192.0.2.2", "b.example", 300), secs(0));
+        assert_eq!(cache.lookup("192.0.2.1".parse().unwrap(), secs(59)), Some("a.example".into()));
+        assert_eq!(cache.lookup("192.0.2.1".parse().unwrap(), secs(60)), None);
+
+        // Dolu önbellekte süresi ilk dolacak olan atılır
+        cache.insert(resolution("192.0.2.3", "c.example", 120), secs(1));
+        assert_eq!(cache.lookup("192.0.2.1".parse().unwrap(), secs(1)), None);
+        assert_eq!(cache.lookup("192.0.2.2".parse().unwrap(), secs(1)), Some("b.example".into()));
+
+        // Aynı adres en son sorulan ada bağlanır
+        cache.insert(resolution("192.0.2.2", "d.example", 300), secs(2));
+        assert_eq!(cache.lookup("192.0.2.2".parse().unwrap(), secs(2)), Some("d.example".into()));
+    }
+}
@@ -0,0 +1,562 @@
+use std::fmt;
+use std::net::IpAddr;
+
+use crate::flow::Direction;
+
+// İfadeler WebSocket istemcilerinden de gelir; ayrıştırma ve değerlendirme
+// özyinelemeli olduğundan iç içe geçme ve koşul sayısı sınırlanır
+const MAX_NESTING: usize = 64;
+const MAX_TERMS: usize = 1024;
+
+/// Filtrenin değerlendirildiği alanlar. Yakalamada akışın ilk paketinden ve
+/// GeoIP sonucundan, WebSocket tarafında `flow_start` olayından doldurulur.
+#[derive(Debug, Clone)]
+pub struct Subject {
+    pub protocol: u8,
+    pub src: IpAddr,
+    pub dst: IpAddr,
+    /// TCP/UDP dışında `None`
+    pub src_port: Option<u16>,
+    pub dst_port: Option<u16>,
+    /// ISO 3166 ülke kodu
+    pub src_country: Option<String>,
+    pub dst_country: Option<String>,
+    pub direction: Option<Direction>,
+    pub vlan_ids: Vec<u16>,
+    pub tunneled: bool,
+    /// Tünellerin VNI/anahtar değerleri
+    pub tunnel_ids: Vec<u32>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Side {
+    Src,
+    Dst,
+    Any,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Predicate {
+    Protocol(u8),
+    Ipv4,
+    Ipv6,
+    Host(Side, IpAddr),
+    Net(Side, IpAddr, u8),
+    PortRange(Side, u16, u16),
+    Country(Side, String),
+    Direction(Direction),
+    Vlan(u16),
+    Tunnel,
+    TunnelId(u32),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Expr {
+    And(Box<Expr>, Box<Expr>),
+    Or(Box<Expr>, Box<Expr>),
+    Not(Box<Expr>),
+    Predicate(Predicate),
+}
+
+/// Ayrıştırma hatası; `position` ifadedeki 1'den başlayan karakter sırası
+#[derive(Debug, Clone)]
+pub struct FilterError {
+    pub position: usize,
+    pub message: String,
+}
+
+impl fmt::Display for FilterError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}. karakterde: {}", self.position, self.message)
+    }
+}
+
+impl std::error::Error for FilterError {}
+
+/// tcpdump/Wireshark benzeri filtre ifadesi, ör.
+/// `tcp and port 443 and not net 10.0.0.0/8 and country != TR`.
+/// Başlangıçta bir kez ayrıştırılır, her akış için sözdizimi ağacı üzerinde
+/// değerlendirilir.
+#[derive(Debug, Clone)]
+pub struct Filter {
+    text: String,
+    expr: Expr,
+}
+
+impl Filter {
+    pub fn parse(text: &str) -> Result<Self, FilterError> {
+        let tokens = tokenize(text)?;
+        let mut parser = Parser { tokens, pos: 0, end: text.chars().count() + 1, depth: 0, terms: 0 };
+        let expr = parser.parse_or()?;
+        if let Some(token) = parser.tokens.get(parser.pos) {
+            return Err(token.error(format!("beklenmeyen '{}'", token.text)));
+        }
+        Ok(Self { text: text.trim().to_string(), expr })
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.text
+    }
+
+    pub fn expr(&self) -> &Expr {
+        &self.expr
+    }
+
+    pub fn matches(&self, subject: &Subject) -> bool {
+        eval(&self.expr, subject)
+    }
+}
+
+fn eval(expr: &Expr, subject: &Subject) -> bool {
+    match expr {
+        Expr::And(left, right) => eval(left, subject) && eval(right, subject),
+        Expr::Or(left, right) => eval(left, subject) || eval(right, subject),
+        Expr::Not(inner) => !eval(inner, subject),
+        Expr::Predicate(predicate) => eval_predicate(predicate, subject),
+    }
+}
+
+fn on_side<T>(side: Side, src: T, dst: T, test: impl Fn(T) -> bool) -> bool {
+    match side {
+        Side::Src => test(src),
+        Side::Dst => test(dst),
+        Side::Any => test(src) || test(dst),
+    }
+}
+
+fn eval_predicate(predicate: &Predicate, subject: &Subject) -> bool {
+    match predicate {
+        Predicate::Protocol(protocol) => subject.protocol == *protocol,
+        Predicate::Ipv4 => subject.src.is_ipv4(),
+        Predicate::Ipv6 => subject.src.is_ipv6(),
+        Predicate::Host(side, ip) => on_side(*side, subject.src, subject.dst, |addr| addr == *ip),
+        Predicate::Net(side, net, prefix) => {
+            on_side(*side, subject.src, subject.dst, |addr| net_contains(*net, *prefix, addr))
+        }
+        Predicate::PortRange(side, low, high) => on_side(*side, subject.src_port, subject.dst_port, |port| {
+            port.is_some_and(|port| (*low..=*high).contains(&port))
+        }),
+        Predicate::Country(side, code) => on_side(
+            *side,
+            subject.src_country.as_deref(),
+            subject.dst_country.as_deref(),
+            |country| country.is_some_and(|country| country.eq_ignore_ascii_case(code)),
+        ),
+        Predicate::Direction(direction) => subject.direction == Some(*direction),
+        Predicate::Vlan(id) => subject.vlan_ids.contains(id),
+        Predicate::Tunnel => subject.tunneled,
+        Predicate::TunnelId(id) => subject.tunnel_ids.contains(id),
+    }
+}
+
+pub fn net_contains(net: IpAddr, prefix: u8, addr: IpAddr) -> bool {
+    match (net, addr) {
+        (IpAddr::V4(net), IpAddr::V4(addr)) => {
+            let mask = u32::MAX.checked_shl(32 - prefix as u32).unwrap_or(0);
+            u32::from(net) & mask == u32::from(addr) & mask
+        }
+        (IpAddr::V6(net), IpAddr::V6(addr)) => {
+            let mask = u128::MAX.checked_shl(128 - prefix as u32).unwrap_or(0);
+            u128::from(net) & mask == u128::from(addr) & mask
+        }
+        _ => false,
+    }
+}
+
+#[derive(Debug, Clone)]
+struct Token {
+    text: String,
+    position: usize,
+}
+
+impl Token {
+    fn error(&self, message: String) -> FilterError {
+        FilterError { position: self.position, message }
+    }
+}
+
+fn is_word_char(c: char) -> bool {
+    c.is_ascii_alphanumeric() || matches!(c, '.' | ':' | '/' | '-' | '_')
+}
+
+fn tokenize(text: &str) -> Result<Vec<Token>, FilterError> {
+    let chars: Vec<char> = text.chars().collect();
+    let mut tokens = Vec::new();
+    let mut i = 0;
+
+    while i < chars.len() {
+        let c = chars[i];
+        let position = i + 1;
+        if c.is_whitespace() {
+            i += 1;
+            continue;
+        }
+
+        let len = if is_word_char(c) {
+            chars[i..].iter().take_while(|c| is_word_char(**c)).count()
+        } else {
+            let pair: String = chars[i..chars.len().min(i + 2)].iter().collect();
+            match pair.as_str() {
+                "&&" | "||" | "==" | "!=" => 2,
+                _ if matches!(c, '(' | ')' | '!' | '=') => 1,
+                _ => return Err(FilterError { position, message: format!("geçersiz karakter '{}'", c) }),
+            }
+        };
+
+        tokens.push(Token { text: chars[i..i + len].iter().collect(), position });
+        i += len;
+    }
+
+    Ok(tokens)
+}
+
+struct Parser {
+    tokens: Vec<Token>,
+    pos: usize,
+    // İfade sonu hataları için konum
+    end: usize,
+    // Açık parantez ve `not` sayısı
+    depth: usize,
+    terms: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<&str> {
+        self.tokens.get(self.pos).map(|token| token.text.as_str())
+    }
+
+    fn accept(&mut self, words: &[&str]) -> bool {
+        match self.peek() {
+            Some(text) if words.iter().any(|word| word.eq_ignore_ascii_case(text)) => {
+                self.pos += 1;
+                true
+            }
+            _ => false,
+        }
+    }
+
+    fn next(&mut self, expected: &str) -> Result<Token, FilterError> {
+        match self.tokens.get(self.pos) {
+            Some(token) => {
+                self.pos += 1;
+                Ok(token.clone())
+            }
+            None => Err(FilterError {
+                position: self.end,
+                message: format!("ifade bitti, {} bekleniyordu", expected),
+            }),
+        }
+    }
+
+    fn parse_or(&mut self) -> Result<Expr, FilterError> {
+        let mut expr = self.parse_and()?;
+        while self.accept(&["or", "||"]) {
+            expr = Expr::Or(Box::new(expr), Box::new(self.parse_and()?));
+        }
+        Ok(expr)
+    }
+
+    fn parse_and(&mut self) -> Result<Expr, FilterError> {
+        let mut expr = self.parse_unary()?;
+        while self.accept(&["and", "&&"]) {
+            expr = Expr::And(Box::new(expr), Box::new(self.parse_unary()?));
+        }
+        Ok(expr)
+    }
+
+    fn parse_unary(&mut self) -> Result<Expr, FilterError> {
+        if self.accept(&["not", "!"]) {
+            self.enter()?;
+            let expr = Expr::Not(Box::new(self.parse_unary()?));
+            self.depth -= 1;
+            return Ok(expr);
+        }
+        if self.accept(&["("]) {
+            self.enter()?;
+            let expr = self.parse_or()?;
+            let token = self.next("')'")?;
+            if token.text != ")" {
+                return Err(token.error(format!("')' bekleniyordu, '{}' bulundu", token.text)));
+            }
+            self.depth -= 1;
+            return Ok(expr);
+        }
+        self.terms += 1;
+        if self.terms > MAX_TERMS {
+            let token = self.next("bir koşul")?;
+            return Err(token.error(format!("ifadede en fazla {} koşul olabilir", MAX_TERMS)));
+        }
+        self.parse_primitive()
+    }
+
+    /// Az önce okunan `(` ya da `not` ile bir seviye içeri girer.
+    fn enter(&mut self) -> Result<(), FilterError> {
+        self.depth += 1;
+        if self.depth > MAX_NESTING {
+            let token = &self.tokens[self.pos - 1];
+            return Err(token.error(format!("ifade en fazla {} seviye iç içe olabilir", MAX_NESTING)));
+        }
+        Ok(())
+    }
+
+    fn parse_primitive(&mut self) -> Result<Expr, FilterError> {
+        let side = if self.accept(&["src"]) {
+            Side::Src
+        } else if self.accept(&["dst"]) {
+            Side::Dst
+        } else {
+            Side::Any
+        };
+
+        let token = self.next("bir koşul")?;
+        let keyword = token.text.to_ascii_lowercase();
+        let predicate = match keyword.as_str() {
+            "host" => Predicate::Host(side, self.parse_value("IP adresi")?),
+            "net" => {
+                let value = self.next("ağ adresi")?;
+                let (net, prefix) = parse_cidr(&value.text).ok_or_else(|| {
+                    value.error(format!("geçersiz ağ '{}', ör. 10.0.0.0/8", value.text))
+                })?;
+                Predicate::Net(side, net, prefix)
+            }
+            "port" => {
+                let port = self.parse_value("port")?;
+                Predicate::PortRange(side, port, port)
+            }
+            "portrange" => {
+                let value = self.next("port aralığı")?;
+                let range = value.text.split_once('-').and_then(|(low, high)| {
+                    Some((low.parse::<u16>().ok()?, high.parse::<u16>().ok()?))
+                });
+                match range {
+                    Some((low, high)) if low <= high => Predicate::PortRange(side, low, high),
+                    _ => return Err(value.error(format!("geçersiz port aralığı '{}', ör. 1000-2000", value.text))),
+                }
+            }
+            "country" => {
+                let negate = self.parse_comparison()?;
+                let value = self.next("ülke kodu")?;
+                if value.text.len() != 2 || !value.text.chars().all(|c| c.is_ascii_alphabetic()) {
+                    return Err(value.error(format!("geçersiz ülke kodu '{}', ör. TR", value.text)));
+                }
+                let expr = Expr::Predicate(Predicate::Country(side, value.text.to_ascii_uppercase()));
+                // `country != TR`: iki uç da TR değil
+                return Ok(if negate { Expr::Not(Box::new(expr)) } else { expr });
+            }
+            _ if side != Side::Any => {
+                return Err(token.error(format!(
+                    "'{}' src/dst ile kullanılamaz; host, net, port, portrange ya da country bekleniyordu",
+                    token.text
+                )));
+            }
+            "tcp" => Predicate::Protocol(6),
+            "udp" => Predicate::Protocol(17),
+            "icmp" => Predicate::Protocol(1),
+            "icmp6" | "icmpv6" => Predicate::Protocol(58),
+            "ip" | "ip4" => Predicate::Ipv4,
+            "ip6" => Predicate::Ipv6,
+            "tunnel" => Predicate::Tunnel,
+            "vlan" => Predicate::Vlan(self.parse_value("VLAN kimliği")?),
+            "vni" => Predicate::TunnelId(self.parse_value("VNI")?),
+            "direction" => {
+                let negate = self.parse_comparison()?;
+                let value = self.next("yön")?;
+                let direction = match value.text.to_ascii_lowercase().as_str() {
+                    "inbound" => Direction::Inbound,
+                    "outbound" => Direction::Outbound,
+                    "transit" => Direction::Transit,
+                    _ => {
+                        return Err(value.error(format!(
+                            "geçersiz yön '{}'; inbound, outbound ya da transit olmalı",
+                            value.text
+                        )));
+                    }
+                };
+                let expr = Expr::Predicate(Predicate::Direction(direction));
+                return Ok(if negate { Expr::Not(Box::new(expr)) } else { expr });
+            }
+            _ => return Err(token.error(format!("bilinmeyen koşul '{}'", token.text))),
+        };
+
+        Ok(Expr::Predicate(predicate))
+    }
+
+    /// `==`/`=` için `false`, `!=` için `true` döner.
+    fn parse_comparison(&mut self) -> Result<bool, FilterError> {
+        let token = self.next("'==' ya da '!='")?;
+        match token.text.as_str() {
+            "==" | "=" => Ok(false),
+            "!=" => Ok(true),
+            _ => Err(token.error(format!("'==' ya da '!=' bekleniyordu, '{}' bulundu", token.text))),
+        }
+    }
+
+    fn parse_value<T: std::str::FromStr>(&mut self, expected: &str) -> Result<T, FilterError> {
+        let token = self.next(expected)?;
+        token.text.parse().map_err(|_| token.error(format!("geçersiz {} '{}'", expected, token.text)))
+    }
+}
+
+fn parse_cidr(text: &str) -> Option<(IpAddr, u8)> {
+    let (addr, prefix) = match text.split_once('/') {
+        Some((addr, prefix)) => (addr.parse::<IpAddr>().ok()?, prefix.parse::<u8>().ok()?),
+        None => {
+            let addr = text.parse::<IpAddr>().ok()?;
+            (addr, if addr.is_ipv4() { 32 } else { 128 })
+        }
+    };
+    let max = if addr.is_ipv4() { 32 } else { 128 };
+    (prefix <= max).then_some((addr, prefix))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn subject() -> Subject {
+        Subject {
+            protocol: 6,
+            src: "10.0.0.5".parse().unwrap(),
+            dst: "151.101.1.69".parse().unwrap(),
+            src_port: Some(50000),
+            dst_port: Some(443),
+            src_country: None,
+            dst_country: Some("DE".to_string()),
+            direction: Some(Direction::Outbound),
+            vlan_ids: vec![100],
+            tunneled: true,
+            tunnel_ids: vec![4242],
+        }
+    }
+
+    fn matches(text: &str, subject: &Subject) -> bool {
+        Filter::parse(text).unwrap_or_else(|e| panic!("{}: {}", text, e)).matches(subject)
+    }
+
+    fn error(text: &str) -> (usize, String) {
+        let error = Filter::parse(text).expect_err(text);
+        (error.position, error.message)
+    }
+
+    #[test]
+    fn evaluates_predicates_against_subject() {
+        let subject = subject();
+        for text in [
+            "tcp and port 443 and not net 192.168.0.0/16 and country != TR",
+            "tcp",
+            "ip",
+            "src host 10.0.0.5",
+            "dst host 151.101.1.69",
+            "host 10.0.0.5",
+            "net 151.101.0.0/16",
+            "src net 10.0.0.0/8",
+            "dst port 443",
+            "src portrange 49152-65535",
+            "country == de",
+            "dst country = DE",
+            "direction == outbound",
+            "direction != inbound",
+            "vlan 100",
+            "vni 4242",
+            "tunnel",
+            "TCP AND PORT 443",
+            "tcp && !udp || icmp",
+        ] {
+            assert!(matches(text, &subject), "{}", text);
+        }
+        for text in [
+            "udp",
+            "ip6",
+            "icmp",
+            "dst host 10.0.0.5",
+            "src port 443",
+            "portrange 1-100",
+            "net 192.168.0.0/16",
+            "src country == DE",
+            "country == TR",
+            "direction == transit",
+            "vlan 200",
+            "vni 1",
+            "not tunnel",
+        ] {
+            assert!(!matches(text, &subject), "{}", text);
+        }
+    }
+
+    #[test]
+    fn country_inequality_requires_neither_side_to_match() {
+        let subject = subject();
+        assert!(!matches("country != DE", &subject));
+        assert!(matches("src country != DE", &subject));
+
+        // ICMP gibi portsuz paketlerde port koşulu eşleşmez
+        let icmp = Subject { protocol: 1, src_port: None, dst_port: None, ..subject };
+        assert!(!matches("port 443", &icmp));
+        assert!(matches("not port 443", &icmp));
+    }
+
+    #[test]
+    fn and_binds_tighter_than_or() {
+        let filter = Filter::parse("udp or tcp and port 80").unwrap();
+        assert!(matches!(filter.expr(), Expr::Or(left, _) if **left == Expr::Predicate(Predicate::Protocol(17))));
+        assert!(!filter.matches(&subject()));
+        assert!(matches("(udp or tcp) and port 443", &subject()));
+        assert!(!matches("not tcp or udp", &subject()));
+        assert!(matches("not (udp or icmp)", &subject()));
+        assert_eq!(Filter::parse("  tcp  ").unwrap().as_str(), "tcp");
+    }
+
+    #[test]
+    fn reports_error_positions() {
+        assert_eq!(error("tcp and").0, 8);
+        assert_eq!(error("tcp and port abc"), (14, "geçersiz port 'abc'".to_string()));
+        assert_eq!(error("tcp $").0, 5);
+        assert_eq!(error("tcp udp"), (5, "beklenmeyen 'udp'".to_string()));
+        assert_eq!(error("(tcp").0, 5);
+        assert_eq!(error("(tcp udp)").0, 6);
+        assert_eq!(error("src tcp").0, 5);
+        assert_eq!(error("bogus").1, "bilinmeyen koşul 'bogus'");
+        assert_eq!(error("country TR").0, 9);
+        assert_eq!(error("country == TUR").0, 12);
+        assert_eq!(error("net 10.0.0.0/33").0, 5);
+        assert_eq!(error("portrange 2000-1000").0, 11);
+        assert_eq!(error("direction == sideways").0, 14);
+        assert_eq!(error("port 70000").0, 6);
+        assert!(error("").1.contains("bekleniyordu"));
+    }
+
+    #[test]
+    fn deep_nesting_and_long_expressions_are_rejected() {
+        let nested = format!("{}tcp{}", "(".repeat(MAX_NESTING), ")".repeat(MAX_NESTING));
+        assert!(matches(&nested, &subject()));
+        let nested = format!("not {}", nested);
+        assert_eq!(error(&nested), (5 + MAX_NESTING - 1, format!("ifade en fazla {} seviye iç içe olabilir", MAX_NESTING)));
+
+        // Yığını taşıracak uzunlukta girdiler de hatayla döner
+        let (position, message) = error(&"(".repeat(200_000));
+        assert_eq!(position, MAX_NESTING + 1);
+        assert!(message.contains("iç içe"));
+        assert_eq!(error(&"not ".repeat(100_000)).0, 4 * MAX_NESTING + 1);
+
+        let terms = |count: usize| vec!["tcp"; count].join(" or ");
+        assert!(matches(&terms(MAX_TERMS), &subject()));
+        assert_eq!(
+            error(&terms(MAX_TERMS + 1)),
+            (7 * MAX_TERMS + 1, format!("ifadede en fazla {} koşul olabilir", MAX_TERMS))
+        );
+    }
+
+    #[test]
+    fn net_contains_handles_prefix_edges() {
+        let ip = |text: &str| text.parse::<IpAddr>().unwrap();
+        assert!(net_contains(ip("10.0.0.0"), 8, ip("10.255.0.1")));
+        assert!(!net_contains(ip("10.0.0.0"), 8, ip("11.0.0.1")));
+        assert!(net_contains(ip("0.0.0.0"), 0, ip("203.0.113.9")));
+        assert!(net_contains(ip("1.2.3.4"), 32, ip("1.2.3.4")));
+        assert!(!net_contains(ip("1.2.3.4"), 32, ip("1.2.3.5")));
+        assert!(net_contains(ip("2001:db8::"), 32, ip("2001:db8:ffff::1")));
+        assert!(!net_contains(ip("2001:db8::"), 33, ip("2001:db8:ffff::1")));
+        assert!(net_contains(ip("::"), 0, ip("2a00::1")));
+        assert!(!net_contains(ip("::"), 0, ip("10.0.0.1")));
+    }
+}
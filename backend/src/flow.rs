@@ -0,0 +1,624 @@
+use std::collections::{BTreeMap, HashMap};
+use std::net::IpAddr;
+use std::time::Duration;
+
+use crate::tcp::{TcpState, TcpTracker};
+use crate::tls::{ClientHello, HelloReader};
+
+// FIN/RST ile kapanan TCP akışları, geç gelen son ACK'ler yeni akış
+// açmasın diye bu süre kadar tabloda tutulur
+const CLOSE_LINGER: Duration = Duration::from_secs(2);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Endpoint {
+    pub ip: IpAddr,
+    pub port: u16,
+}
+
+/// Protokol ve iki uç nokta ile tanımlanan, yönden bağımsız akış anahtarı.
+/// A→B ve B→A paketleri aynı anahtara düşer.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct FlowKey {
+    pub protocol: u8,
+    pub low: Endpoint,
+    pub high: Endpoint,
+}
+
+impl FlowKey {
+    pub fn new(protocol: u8, src: Endpoint, dst: Endpoint) -> Self {
+        let (low, high) = if src <= dst { (src, dst) } else { (dst, src) };
+        Self { protocol, low, high }
+    }
+}
+
+/// Akışın yakalama yapılan makineye göre yönü
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Direction {
+    /// Dışarıdan yerel adrese başlatılan
+    Inbound,
+    /// Yerel adresten dışarıya başlatılan
+    Outbound,
+    /// İki ucu da yerel olmayan (ayna port, köprü ...)
+    Transit,
+}
+
+impl Direction {
+    pub fn classify(initiator: IpAddr, responder: IpAddr, local_addrs: &[IpAddr]) -> Self {
+        if local_addrs.contains(&initiator) {
+            Direction::Outbound
+        } else if local_addrs.contains(&responder) {
+            Direction::Inbound
+        } else {
+            Direction::Transit
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Direction::Inbound => "inbound",
+            Direction::Outbound => "outbound",
+            Direction::Transit => "transit",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct FlowConfig {
+    /// Bu süre boyunca paket görülmeyen akış sonlanır
+    pub idle_timeout: Duration,
+    /// Bu süreden uzun akışlar sonraki paketlerinde sonlanır ve aynı
+    /// bağlantının devamı olarak yeni bir akışla sürer
+    pub active_timeout: Duration,
+    /// Süren akışlar için en fazla bu aralıkla `flow_update` gönderilir;
+    /// akışın ömrünü sınırlamaz
+    pub update_interval: Duration,
+    /// Tablodaki en fazla akış sayısı; aşılınca en eski kullanılan atılır
+    pub max_flows: usize,
+}
+
+impl Default for FlowConfig {
+    fn default() -> Self {
+        Self {
+            idle_timeout: Duration::from_secs(60),
+            active_timeout: Duration::from_secs(30 * 60),
+            update_interval: Duration::from_secs(30),
+            max_flows: 65_536,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EndReason {
+    Idle,
+    /// Akış `active_timeout` süresini aştı; bağlantı yeni akışla sürer
+    ActiveTimeout,
+    Evicted,
+    Shutdown,
+    /// TCP bağlantısı FIN ile kapandı
+    Closed,
+    /// TCP bağlantısı RST ile kesildi
+    Reset,
+}
+
+impl EndReason {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            EndReason::Idle => "idle",
+            EndReason::ActiveTimeout => "active_timeout",
+            EndReason::Evicted => "evicted",
+            EndReason::Shutdown => "shutdown",
+            EndReason::Closed => "closed",
+            EndReason::Reset => "reset",
+        }
+    }
+}
+
+/// Tek yöndeki paket ve bayt sayaçları
+#[derive(Debug, Clone, Copy, Default)]
+pub struct FlowCounters {
+    pub packets: u64,
+    /// IP başlığı dahil bayt sayısı
+    pub bytes: u64,
+}
+
+/// Haritada akışın iki ucunu gösteren koordinatlar ve ülke kodları
+#[derive(Debug, Clone)]
+pub struct FlowGeo {
+    pub src: (f64, f64),
+    pub dst: (f64, f64),
+    pub src_country: Option<String>,
+    pub dst_country: Option<String>,
+}
+
+/// DNS yanıtlarından öğrenilen uç adları
+#[derive(Debug, Clone, Default)]
+pub struct FlowNames {
+    pub src: Option<String>,
+    pub dst: Option<String>,
+}
+
+pub struct Flow {
+    pub id: u64,
+    pub key: FlowKey,
+    /// Bağlantıyı başlatan uç (TCP'de SYN gönderen, yoksa ilk görülen kaynak)
+    pub initiator: Endpoint,
+    /// Başlatan uç SYN ile mi belirlendi, yoksa ilk paketten mi tahmin edildi
+    pub initiator_from_syn: bool,
+    pub direction: Direction,
+    /// Başlatandan karşı uca
+    pub forward: FlowCounters,
+    /// Karşı uçtan başlatana
+    pub reverse: FlowCounters,
+    pub first_seen: Duration,
+    pub last_seen: Duration,
+    pub last_reported: Duration,
+    /// Konumu bulunamayan akışlar istemcilere bildirilmez
+    pub geo: Option<FlowGeo>,
+    /// Akış başında bilinmeyen adlar güncellemelerde yeniden aranır
+    pub names: FlowNames,
+    /// Yalnızca TCP akışlarında
+    pub tcp: Option<TcpTracker>,
+    /// Başlatan tarafın ilk verisi TLS ClientHello ise sunduğu parametreler
+    pub tls: Option<ClientHello>,
+    pub hello: HelloReader,
+    /// Bu akışın paketlerine dönen ICMP hata mesajları
+    pub icmp_errors: u64,
+    lru_seq: u64,
+}
+
+impl Flow {
+    pub fn responder(&self) -> Endpoint {
+        if self.initiator == self.key.low { self.key.high } else { self.key.low }
+    }
+
+    /// Başlatan uç değişirse yön sayaçları, uç adları ve konumları da yer
+    /// değiştirir.
+    pub fn set_initiator(&mut self, initiator: Endpoint) {
+        if initiator != self.initiator {
+            self.initiator = initiator;
+            std::mem::swap(&mut self.forward, &mut self.reverse);
+            std::mem::swap(&mut self.names.src, &mut self.names.dst);
+            if let Some(geo) = self.geo.as_mut() {
+                std::mem::swap(&mut geo.src, &mut geo.dst);
+                std::mem::swap(&mut geo.src_country, &mut geo.dst_country);
+            }
+            if let Some(tcp) = self.tcp.as_mut() {
+                tcp.swap_sides();
+            }
+        }
+    }
+
+    /// `src` ucundan gelen paketi sayaçlara işler.
+    pub fn record(&mut self, src: Endpoint, bytes: u64) {
+        let counters = if src == self.initiator { &mut self.forward } else { &mut self.reverse };
+        counters.packets += 1;
+        counters.bytes += bytes;
+    }
+
+    pub fn duration(&self) -> Duration {
+        self.last_seen.saturating_sub(self.first_seen)
+    }
+
+    pub fn tcp_state(&self) -> Option<TcpState> {
+        self.tcp.as_ref().map(|tcp| tcp.state)
+    }
+
+    /// FIN ya da RST ile kapanmış TCP akışının bitiş nedeni
+    pub fn close_reason(&self) -> Option<EndReason> {
+        match self.tcp_state()? {
+            TcpState::Closed => Some(EndReason::Closed),
+            TcpState::Reset => Some(EndReason::Reset),
+            _ => None,
+        }
+    }
+}
+
+/// Akış tablosu. Zaman olarak paketlerin yakalama zamanı kullanılır, böylece
+/// kayıt oynatmada da zaman aşımları gerçek trafikteki gibi işler.
+pub struct FlowTable {
+    config: FlowConfig,
+    flows: HashMap<FlowKey, Flow>,
+    // Son kullanım sırasına göre anahtarlar; en baştaki en eski
+    lru: BTreeMap<u64, FlowKey>,
+    // Kapanmış TCP akışları ve tablodan çıkarılacakları zaman
+    closing: Vec<(Duration, FlowKey)>,
+    next_seq: u64,
+    next_id: u64,
+    // Birden çok tablo aynı olay akışına yazdığında kimlikler çakışmasın
+    id_step: u64,
+}
+
+impl FlowTable {
+    pub fn new(config: FlowConfig) -> Self {
+        Self {
+            config,
+            flows: HashMap::new(),
+            lru: BTreeMap::new(),
+            closing: Vec::new(),
+            next_seq: 0,
+            next_id: 1,
+            id_step: 1,
+        }
+    }
+
+    /// `count` tablonun `index`. sırada olanı yalnızca kendi payına düşen
+    /// kimlikleri (index + 1, index + 1 + count, ...) dağıtır.
+    pub fn set_id_stride(&mut self, index: usize, count: usize) {
+        self.next_id = index as u64 + 1;
+        self.id_step = count.max(1) as u64;
+    }
+
+    /// Paketi akışına işler. Yeni akış açıldıysa `true` döner; tablo
+    /// doluysa atılan ya da aktif zaman aşımına uğrayan akış `ended`
+    /// listesine eklenir.
+    pub fn observe(
+        &mut self,
+        key: FlowKey,
+        initiator: Endpoint,
+        now: Duration,
+        ended: &mut Vec<(Flow, EndReason)>,
+    ) -> (&mut Flow, bool) {
+        let seq = self.next_seq;
+        self.next_seq += 1;
+
+        if let Some(flow) = self.flows.get(&key) {
+            if now.saturating_sub(flow.first_seen) >= self.config.active_timeout {
+                let mut previous = self.flows.remove(&key).expect("akış az önce bulundu");
+                self.lru.remove(&previous.lru_seq);
+
+                // Bağlantının yönü, durumu ve öğrenilen bilgileri devam
+                // eden akışa geçer; sayaçlar sıfırdan başlar
+                let mut flow = self.open(key, previous.initiator, now, seq);
+                flow.initiator_from_syn = previous.initiator_from_syn;
+                flow.direction = previous.direction;
+                flow.names = previous.names.clone();
+                flow.tcp = previous.tcp.as_ref().map(TcpTracker::resume);
+                flow.tls = previous.tls.clone();
+                flow.hello = std::mem::take(&mut previous.hello);
+                ended.push((previous, EndReason::ActiveTimeout));
+                return (self.flows.entry(key).or_insert(flow), true);
+            }
+
+            let flow = self.flows.get_mut(&key).expect("akış az önce bulundu");
+            self.lru.remove(&flow.lru_seq);
+            self.lru.insert(seq, key);
+            flow.lru_seq = seq;
+            flow.last_seen = flow.last_seen.max(now);
+            return (flow, false);
+        }
+
+        while self.flows.len() >= self.config.max_flows.max(1) {
+            match self.pop_oldest() {
+                Some(flow) => ended.push((flow, EndReason::Evicted)),
+                None => break,
+            }
+        }
+
+        let flow = self.open(key, initiator, now, seq);
+        (self.flows.entry(key).or_insert(flow), true)
+    }
+
+    fn open(&mut self, key: FlowKey, initiator: Endpoint, now: Duration, seq: u64) -> Flow {
+        let flow = Flow {
+            id: self.next_id,
+            key,
+            initiator,
+            initiator_from_syn: false,
+            direction: Direction::Transit,
+            forward: FlowCounters::default(),
+            reverse: FlowCounters::default(),
+            first_seen: now,
+            last_seen: now,
+            last_reported: now,
+            geo: None,
+            names: FlowNames::default(),
+            tcp: None,
+            tls: None,
+            hello: HelloReader::default(),
+            icmp_errors: 0,
+            lru_seq: seq,
+        };
+        self.next_id += self.id_step;
+        self.lru.insert(seq, key);
+        flow
+    }
+
+    /// Paket işlemeden, son kullanım sırasını değiştirmeden akışa erişir.
+    pub fn get_mut(&mut self, key: &FlowKey) -> Option<&mut Flow> {
+        self.flows.get_mut(key)
+    }
+
+    /// Kapanan TCP akışını kısa bir bekleme sonrası tablodan çıkarılmak
+    /// üzere işaretler.
+    pub fn schedule_close(&mut self, key: FlowKey, now: Duration) {
+        self.closing.push((now + CLOSE_LINGER, key));
+    }
+
+    /// Kapanmış TCP akışını beklemeden sonlandırır; aynı uç noktalar
+    /// arasında yeni bir SYN görüldüğünde kullanılır.
+    pub fn end_closed(&mut self, key: &FlowKey, ended: &mut Vec<(Flow, EndReason)>) {
+        let reason = match self.flows.get(key).and_then(Flow::close_reason) {
+            Some(reason) => reason,
+            None => return,
+        };
+        if let Some(flow) = self.flows.remove(key) {
+            self.lru.remove(&flow.lru_seq);
+            ended.push((flow, reason));
+        }
+    }
+
+    /// Boşta kalma süresini aşan ve kapanış beklemesi dolan akışları
+    /// tablodan çıkarır.
+    pub fn expire(&mut self, now: Duration, ended: &mut Vec<(Flow, EndReason)>) {
+        let (due, pending): (Vec<_>, Vec<_>) = std::mem::take(&mut self.closing)
+            .into_iter()
+            .partition(|(deadline, _)| *deadline <= now);
+        self.closing = pending;
+        for (_, key) in due {
+            self.end_closed(&key, ended);
+        }
+
+        while let Some((_, key)) = self.lru.first_key_value() {
+            let idle = match self.flows.get(key) {
+                Some(flow) => now.saturating_sub(flow.last_seen) >= self.config.idle_timeout,
+                None => true,
+            };
+            if !idle {
+                break;
+            }
+            if let Some(flow) = self.pop_oldest() {
+                let reason = flow.close_reason().unwrap_or(EndReason::Idle);
+                ended.push((flow, reason));
+            }
+        }
+    }
+
+    /// Son bildirimden bu yana yeni paket görmüş ve güncelleme aralığı
+    /// dolan akışları işaretler ve döndürür.
+    pub fn due_updates(&mut self, now: Duration) -> Vec<&mut Flow> {
+        let update_interval = self.config.update_interval;
+        let mut due = Vec::new();
+        for flow in self.flows.values_mut() {
+            if flow.last_seen > flow.last_reported && now.saturating_sub(flow.last_reported) >= update_interval {
+                flow.last_reported = now;
+                due.push(flow);
+            }
+        }
+        due
+    }
+
+    /// Tablodaki tüm akışları sonlandırır (kaynak tükendiğinde).
+    pub fn drain(&mut self, ended: &mut Vec<(Flow, EndReason)>) {
+        self.lru.clear();
+        self.closing.clear();
+        ended.extend(self.flows.drain().map(|(_, flow)| {
+            let reason = flow.close_reason().unwrap_or(EndReason::Shutdown);
+            (flow, reason)
+        }));
+    }
+
+    fn pop_oldest(&mut self) -> Option<Flow> {
+        let (_, key) = self.lru.pop_first()?;
+        self.flows.remove(&key)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testutil::*;
+    use std::net::Ipv4Addr;
+
+    const CLIENT: Endpoint = endpoint(1, 40000);
+    const SERVER: Endpoint = endpoint(2, 443);
+
+    const fn endpoint(host: u8, port: u16) -> Endpoint {
+        Endpoint {
+            ip: IpAddr::V4(Ipv4Addr::new(10, 0, 0, host)),
+            port,
+        }
+    }
+
+    fn table(max_flows: usize) -> FlowTable {
+        FlowTable::new(FlowConfig {
+            idle_timeout: ms(10_000),
+            active_timeout: ms(60_000),
+            update_interval: ms(5_000),
+            max_flows,
+        })
+    }
+
+    /// `client` istemcisinden `SERVER` sunucusuna akış anahtarı
+    fn key(client: Endpoint) -> FlowKey {
+        FlowKey::new(6, client, SERVER)
+    }
+
+    fn observe(table: &mut FlowTable, client: Endpoint, now: u64) -> (u64, bool, Vec<(Flow, EndReason)>) {
+        let mut ended = Vec::new();
+        let (flow, opened) = table.observe(key(client), client, ms(now), &mut ended);
+        (flow.id, opened, ended)
+    }
+
+    fn ended_ids(ended: &[(Flow, EndReason)]) -> Vec<(u64, EndReason)> {
+        ended.iter().map(|(flow, reason)| (flow.id, *reason)).collect()
+    }
+
+    /// El sıkışmayı ve ardından `closing` segmentlerini işler.
+    fn tracker(closing: &[(bool, u8)]) -> TcpTracker {
+        let mut tcp = TcpTracker::default();
+        for (from_initiator, flags) in [(true, SYN), (false, SYN_ACK), (true, ACK)].iter().chain(closing) {
+            tcp.on_segment(ms(0), *from_initiator, *flags);
+        }
+        tcp
+    }
+
+    #[test]
+    fn full_table_evicts_the_least_recently_used_flow() {
+        let mut table = table(2);
+        let (a, b, c) = (endpoint(1, 1000), endpoint(1, 1001), endpoint(1, 1002));
+
+        let (a_id, opened, _) = observe(&mut table, a, 0);
+        assert!(opened);
+        let (b_id, _, _) = observe(&mut table, b, 1);
+        // A yeniden kullanıldı; en eski artık B
+        let (id, opened, ended) = observe(&mut table, a, 2);
+        assert_eq!((id, opened), (a_id, false));
+        assert!(ended.is_empty());
+
+        let (c_id, opened, ended) = observe(&mut table, c, 3);
+        assert!(opened);
+        assert_eq!(ended_ids(&ended), [(b_id, EndReason::Evicted)]);
+        assert!(table.get_mut(&key(a)).is_some());
+        assert!(table.get_mut(&key(b)).is_none());
+
+        // Atılan akış geri gelirse yeni kimlikle açılır ve sıradaki en eski atılır
+        let (id, opened, ended) = observe(&mut table, b, 4);
+        assert!(opened && id > c_id);
+        assert_eq!(ended_ids(&ended), [(a_id, EndReason::Evicted)]);
+    }
+
+    #[test]
+    fn idle_flows_expire_in_last_seen_order() {
+        let mut table = table(16);
+        let (a, b) = (endpoint(1, 1000), endpoint(1, 1001));
+        let (a_id, _, _) = observe(&mut table, a, 0);
+        let (b_id, _, _) = observe(&mut table, b, 5_000);
+
+        let mut ended = Vec::new();
+        table.expire(ms(9_999), &mut ended);
+        assert!(ended.is_empty());
+        table.expire(ms(10_000), &mut ended);
+        assert_eq!(ended_ids(&ended), [(a_id, EndReason::Idle)]);
+
+        // Paket görmek boşta kalma süresini yeniler
+        observe(&mut table, b, 12_000);
+        ended.clear();
+        table.expire(ms(21_999), &mut ended);
+        assert!(ended.is_empty());
+        table.expire(ms(22_000), &mut ended);
+        assert_eq!(ended_ids(&ended), [(b_id, EndReason::Idle)]);
+
+        // Kapanmış ama kapanış beklemesi planlanmamış akış nedeniyle raporlanır
+        let (c_id, _, _) = observe(&mut table, a, 30_000);
+        table.get_mut(&key(a)).unwrap().tcp = Some(tracker(&[(false, RST)]));
+        ended.clear();
+        table.expire(ms(40_000), &mut ended);
+        assert_eq!(ended_ids(&ended), [(c_id, EndReason::Reset)]);
+    }
+
+    #[test]
+    fn active_timeout_continues_the_connection_in_a_new_flow() {
+        let mut table = table(16);
+        let (id, _, _) = observe(&mut table, CLIENT, 0);
+        {
+            let flow = table.get_mut(&key(CLIENT)).unwrap();
+            flow.initiator_from_syn = true;
+            flow.direction = Direction::Outbound;
+            flow.names.dst = Some("example.com".to_string());
+            flow.record(CLIENT, 100);
+            flow.record(SERVER, 1500);
+            let mut tcp = tracker(&[]);
+            tcp.forward.retransmissions = 3;
+            flow.tcp = Some(tcp);
+        }
+
+        let (next_id, opened, ended) = observe(&mut table, CLIENT, 59_999);
+        assert_eq!((next_id, opened), (id, false));
+        assert!(ended.is_empty());
+
+        // Karşı ucun paketi de bağlantının yönünü değiştirmez
+        let mut ended = Vec::new();
+        let (flow, opened) = table.observe(key(CLIENT), SERVER, ms(60_000), &mut ended);
+        assert!(opened && flow.id != id);
+        assert_eq!(flow.initiator, CLIENT);
+        assert!(flow.initiator_from_syn);
+        assert_eq!(flow.direction, Direction::Outbound);
+        assert_eq!(flow.names.dst.as_deref(), Some("example.com"));
+        assert_eq!((flow.forward.packets, flow.reverse.packets), (0, 0));
+        assert_eq!(flow.first_seen, ms(60_000));
+        let tcp = flow.tcp.as_ref().unwrap();
+        assert_eq!(tcp.state, TcpState::Established);
+        assert_eq!(tcp.forward.retransmissions, 0);
+
+        let (previous, reason) = &ended[0];
+        assert_eq!((previous.id, *reason), (id, EndReason::ActiveTimeout));
+        assert_eq!((previous.forward.bytes, previous.reverse.bytes), (100, 1500));
+        assert_eq!(previous.tcp.as_ref().unwrap().forward.retransmissions, 3);
+    }
+
+    #[test]
+    fn closed_flows_linger_before_leaving_the_table() {
+        let mut table = table(16);
+        let (id, _, _) = observe(&mut table, CLIENT, 1_000);
+        table.get_mut(&key(CLIENT)).unwrap().tcp = Some(tracker(&[(true, FIN_ACK), (false, FIN_ACK)]));
+        table.schedule_close(key(CLIENT), ms(1_000));
+
+        // Beklemede gelen son ACK aynı akışa düşer
+        let linger = ms(1_000) + CLOSE_LINGER;
+        let mut ended = Vec::new();
+        table.expire(linger - ms(1), &mut ended);
+        assert!(ended.is_empty());
+        let (same, opened, _) = observe(&mut table, CLIENT, 1_500);
+        assert_eq!((same, opened), (id, false));
+
+        table.expire(linger, &mut ended);
+        assert_eq!(ended_ids(&ended), [(id, EndReason::Closed)]);
+        assert!(table.get_mut(&key(CLIENT)).is_none());
+
+        // Süren akış erken sonlandırılmaz, kapanan akış beklemeden sonlanır
+        let (id, _, _) = observe(&mut table, CLIENT, 5_000);
+        table.get_mut(&key(CLIENT)).unwrap().tcp = Some(tracker(&[]));
+        ended.clear();
+        table.end_closed(&key(CLIENT), &mut ended);
+        assert!(ended.is_empty());
+        table.get_mut(&key(CLIENT)).unwrap().tcp = Some(tracker(&[(false, RST)]));
+        table.end_closed(&key(CLIENT), &mut ended);
+        assert_eq!(ended_ids(&ended), [(id, EndReason::Reset)]);
+
+        // Beklemesi dolan ama artık tabloda olmayan akış sorun çıkarmaz
+        table.schedule_close(key(CLIENT), ms(5_000));
+        table.expire(ms(5_000) + CLOSE_LINGER, &mut ended);
+        assert_eq!(ended.len(), 1);
+    }
+
+    #[test]
+    fn set_initiator_swaps_every_directional_field() {
+        let mut table = table(16);
+        let mut ended = Vec::new();
+        // İlk görülen paket sunucudan
+        let (flow, _) = table.observe(key(CLIENT), SERVER, ms(0), &mut ended);
+        assert_eq!(flow.responder(), CLIENT);
+        flow.record(SERVER, 1500);
+        flow.record(CLIENT, 60);
+        flow.record(CLIENT, 60);
+        flow.names = FlowNames {
+            src: Some("cdn.example.com".to_string()),
+            dst: None,
+        };
+        flow.geo = Some(FlowGeo {
+            src: US,
+            dst: DE,
+            src_country: Some("US".to_string()),
+            dst_country: Some("DE".to_string()),
+        });
+        let mut tcp = TcpTracker::default();
+        tcp.reverse.out_of_order = 2;
+        flow.tcp = Some(tcp);
+
+        flow.set_initiator(CLIENT);
+        assert_eq!((flow.initiator, flow.responder()), (CLIENT, SERVER));
+        assert_eq!((flow.forward.packets, flow.forward.bytes), (2, 120));
+        assert_eq!((flow.reverse.packets, flow.reverse.bytes), (1, 1500));
+        assert_eq!((flow.names.src.as_deref(), flow.names.dst.as_deref()), (None, Some("cdn.example.com")));
+        let geo = flow.geo.as_ref().unwrap();
+        assert_eq!((geo.src, geo.dst), (DE, US));
+        assert_eq!((geo.src_country.as_deref(), geo.dst_country.as_deref()), (Some("DE"), Some("US")));
+        let tcp = flow.tcp.as_ref().unwrap();
+        assert_eq!((tcp.forward.out_of_order, tcp.reverse.out_of_order), (2, 0));
+
+        // Aynı uç yeniden verilirse hiçbir şey değişmez
+        flow.set_initiator(CLIENT);
+        assert_eq!(flow.forward.packets, 2);
+        assert_eq!(flow.geo.as_ref().unwrap().src, DE);
+    }
+}
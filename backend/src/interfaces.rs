@@ -0,0 +1,436 @@
+use std::str::FromStr;
+
+use pnet::datalink::{self, NetworkInterface};
+
+use crate::source::LinkType;
+
+/// Linux'ta tüm arayüzleri birlikte dinleyen sözde arayüz. Çerçeveleri
+/// bağlantı türünden bağımsız "cooked" (SLL2) başlığıyla alınır.
+pub const ANY_INTERFACE: &str = "any";
+// Çekirdekte 0 sırası "tüm arayüzler" anlamına gelir
+const ANY_INDEX: u32 = 0;
+// libc'de tanımlı değil
+#[cfg(target_os = "linux")]
+const ARPHRD_RAWIP: u16 = 519;
+
+/// Arayüz başına yakalama seçenekleri
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CaptureOptions {
+    pub promiscuous: bool,
+    /// Çerçevenin en fazla bu kadar baytı alınır
+    pub snaplen: usize,
+    /// Çekirdek alıcı tamponu (bayt); halkada halka boyutu. `None` ise
+    /// sistem varsayılanı
+    pub buffer_size: Option<usize>,
+}
+
+impl Default for CaptureOptions {
+    fn default() -> Self {
+        Self {
+            promiscuous: true,
+            snaplen: 65_535,
+            buffer_size: None,
+        }
+    }
+}
+
+/// Arayüzün nasıl seçildiği
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum InterfaceSelector {
+    Name(String),
+    /// İşletim sisteminin arayüz sırası (`#3` ya da `3`)
+    Index(u32),
+    /// `*` ve `?` içeren ad kalıbı, ör. `enp*`
+    Pattern(String),
+}
+
+impl InterfaceSelector {
+    fn matches(&self, interface: &NetworkInterface) -> bool {
+        match self {
+            InterfaceSelector::Name(name) => interface.name == *name,
+            InterfaceSelector::Index(index) => interface.index == *index,
+            InterfaceSelector::Pattern(pattern) => glob_match(pattern.as_bytes(), interface.name.as_bytes()),
+        }
+    }
+}
+
+impl FromStr for InterfaceSelector {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        if s.is_empty() {
+            return Err("boş arayüz seçicisi".to_string());
+        }
+        let digits = s.strip_prefix('#').unwrap_or(s);
+        if digits.bytes().all(|b| b.is_ascii_digit()) {
+            return digits
+                .parse()
+                .map(InterfaceSelector::Index)
+                .map_err(|_| format!("geçersiz arayüz sırası: {}", s));
+        }
+        if s.contains(['*', '?']) {
+            return Ok(InterfaceSelector::Pattern(s.to_string()));
+        }
+        Ok(InterfaceSelector::Name(s.to_string()))
+    }
+}
+
+/// Seçici ve ona uyan arayüzlere uygulanacak seçenekler
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InterfaceSelection {
+    pub selector: InterfaceSelector,
+    /// `!` ile başlayan girdi; uyan arayüzler diğer seçimlerden çıkarılır
+    pub exclude: bool,
+    pub options: CaptureOptions,
+}
+
+impl InterfaceSelection {
+    /// `eth0`, `#2 snaplen=128`, `enp* promisc=off buffer=8388608` ya da
+    /// `!docker*` biçimindeki tek bir girdiyi `defaults` üzerine ayrıştırır.
+    pub fn parse(entry: &str, defaults: CaptureOptions) -> Result<Self, String> {
+        let mut parts = entry.split_whitespace();
+        let selector = parts.next().unwrap_or_default();
+        let (exclude, selector) = match selector.strip_prefix('!') {
+            Some(selector) => (true, selector),
+            None => (false, selector),
+        };
+        let selector = selector.parse()?;
+        let mut options = defaults;
+        if exclude && parts.clone().next().is_some() {
+            return Err(format!("dışlanan arayüze seçenek verilemez: {}", entry.trim()));
+        }
+
+        for part in parts {
+            let (key, value) = part
+                .split_once('=')
+                .ok_or_else(|| format!("seçenek anahtar=değer biçiminde olmalı: {}", part))?;
+            let invalid = || format!("{} için geçersiz değer: {}", key, value);
+            match key {
+                "promisc" => options.promiscuous = parse_flag(value).ok_or_else(invalid)?,
+                "snaplen" => options.snaplen = value.parse().ok().filter(|snaplen| *snaplen > 0).ok_or_else(invalid)?,
+                "buffer" => options.buffer_size = Some(value.parse().map_err(|_| invalid())?),
+                _ => return Err(format!("bilinmeyen arayüz seçeneği: {}", key)),
+            }
+        }
+
+        Ok(Self { selector, exclude, options })
+    }
+}
+
+pub fn parse_flag(value: &str) -> Option<bool> {
+    match value.to_ascii_lowercase().as_str() {
+        "1" | "true" | "yes" | "on" => Some(true),
+        "0" | "false" | "no" | "off" => Some(false),
+        _ => None,
+    }
+}
+
+/// Seçimlere uyan arayüzleri seçenekleriyle döndürür. Bir arayüz birden çok
+/// seçime uyuyorsa ilkinin seçenekleri geçerlidir; dışlanan arayüzler hiç
+/// seçilmez. Dışlama dışında seçim verilmemişse ayakta olan, geri döngü
+/// olmayan ve adresi bulunan ilk arayüz seçilir.
+pub fn select(
+    selections: &[InterfaceSelection],
+    defaults: CaptureOptions,
+) -> Result<Vec<(NetworkInterface, CaptureOptions)>, String> {
+    let interfaces = datalink::interfaces();
+    let any = any_interface(&interfaces);
+    select_from(interfaces, any, selections, defaults)
+}
+
+fn select_from(
+    interfaces: Vec<NetworkInterface>,
+    any: Option<NetworkInterface>,
+    selections: &[InterfaceSelection],
+    defaults: CaptureOptions,
+) -> Result<Vec<(NetworkInterface, CaptureOptions)>, String> {
+    let (exclusions, selections): (Vec<&InterfaceSelection>, Vec<&InterfaceSelection>) =
+        selections.iter().partition(|selection| selection.exclude);
+    let excluded = |interface: &NetworkInterface| {
+        exclusions.iter().any(|selection| selection.selector.matches(interface))
+    };
+
+    if selections.is_empty() {
+        return interfaces
+            .into_iter()
+            .find(|iface| iface.is_up() && !iface.is_loopback() && !iface.ips.is_empty() && !excluded(iface))
+            .map(|interface| vec![(interface, defaults)])
+            .ok_or_else(|| "Aktif ağ arayüzü bulunamadı".to_string());
+    }
+
+    let mut selected: Vec<(NetworkInterface, CaptureOptions)> = Vec::new();
+    for selection in selections {
+        let mut matches: Vec<&NetworkInterface> = interfaces
+            .iter()
+            .filter(|interface| selection.selector.matches(interface))
+            .collect();
+        // Sözde arayüz yalnızca tam adıyla seçilir; kalıplar ve varsayılan
+        // seçim ona uymaz
+        if matches.is_empty() && selection.selector == InterfaceSelector::Name(ANY_INTERFACE.to_string()) {
+            if let Some(any) = &any {
+                matches.push(any);
+            }
+        }
+        if matches.is_empty() {
+            match &selection.selector {
+                InterfaceSelector::Pattern(pattern) => eprintln!("'{}' kalıbına uyan arayüz yok", pattern),
+                selector => return Err(format!("Arayüz bulunamadı: {}", describe(selector))),
+            }
+        }
+        for interface in matches {
+            if !excluded(interface) && !selected.iter().any(|(existing, _)| existing.index == interface.index) {
+                selected.push((interface.clone(), selection.options));
+            }
+        }
+    }
+
+    if selected.is_empty() {
+        return Err("Seçimlere uyan ağ arayüzü bulunamadı".to_string());
+    }
+    Ok(selected)
+}
+
+pub fn is_any(interface: &NetworkInterface) -> bool {
+    interface.index == ANY_INDEX && interface.name == ANY_INTERFACE
+}
+
+/// Tüm arayüzlerin adreslerini taşıyan, her zaman ayakta görünen `any`
+/// sözde arayüzü; yalnızca Linux'ta vardır.
+#[cfg(target_os = "linux")]
+fn any_interface(interfaces: &[NetworkInterface]) -> Option<NetworkInterface> {
+    Some(NetworkInterface {
+        name: ANY_INTERFACE.to_string(),
+        description: "Tüm arayüzler".to_string(),
+        index: ANY_INDEX,
+        mac: None,
+        ips: interfaces.iter().flat_map(|interface| interface.ips.iter().copied()).collect(),
+        flags: (libc::IFF_UP | libc::IFF_RUNNING) as u32,
+    })
+}
+
+#[cfg(not(target_os = "linux"))]
+fn any_interface(_interfaces: &[NetworkInterface]) -> Option<NetworkInterface> {
+    None
+}
+
+/// Arayüzden gelen çerçevelerin bağlantı türü; Linux'ta çekirdeğin
+/// bildirdiği donanım türünden (ARPHRD) belirlenir.
+#[cfg(target_os = "linux")]
+pub fn link_type(interface: &NetworkInterface) -> LinkType {
+    if is_any(interface) {
+        return LinkType::LinuxSll2;
+    }
+    let path = format!("/sys/class/net/{}/type", interface.name);
+    let hardware_type = match std::fs::read_to_string(&path) {
+        Ok(hardware_type) => hardware_type.trim().parse::<u16>().ok(),
+        Err(_) => None,
+    };
+    match hardware_type {
+        Some(libc::ARPHRD_ETHER | libc::ARPHRD_LOOPBACK) | None => LinkType::Ethernet,
+        // tun, WireGuard, IP-in-IP, SIT ve PPP arayüzleri başlıksız IP verir
+        Some(
+            libc::ARPHRD_NONE | libc::ARPHRD_TUNNEL | libc::ARPHRD_TUNNEL6 | libc::ARPHRD_SIT | libc::ARPHRD_PPP
+            | ARPHRD_RAWIP,
+        ) => LinkType::Raw,
+        Some(libc::ARPHRD_IEEE80211_RADIOTAP) => LinkType::Radiotap,
+        Some(hardware_type) => {
+            eprintln!(
+                "{} arayüzünün donanım türü ({}) tanınmıyor, çerçeveler Ethernet olarak çözülecek",
+                interface.name, hardware_type
+            );
+            LinkType::Ethernet
+        }
+    }
+}
+
+/// pnet diğer sistemlerde çerçeveleri Ethernet başlığıyla verir
+#[cfg(not(target_os = "linux"))]
+pub fn link_type(_interface: &NetworkInterface) -> LinkType {
+    LinkType::Ethernet
+}
+
+/// Arayüzleri `--list-interfaces` çıktısı olarak yazar.
+pub fn print_list() {
+    let mut interfaces = datalink::interfaces();
+    if let Some(any) = any_interface(&interfaces) {
+        interfaces.push(any);
+    }
+    for interface in interfaces {
+        let mut flags = Vec::new();
+        if interface.is_up() {
+            flags.push("up");
+        }
+        if interface.is_loopback() {
+            flags.push("loopback");
+        }
+        let mac = interface.mac.map(|mac| mac.to_string()).unwrap_or_else(|| "-".to_string());
+        let ips: Vec<String> = interface.ips.iter().map(|network| network.to_string()).collect();
+        println!(
+            "#{:<3} {:<16} {:<18} [{}] {}",
+            interface.index,
+            interface.name,
+            mac,
+            flags.join(","),
+            ips.join(" ")
+        );
+        if !interface.description.is_empty() {
+            println!("     {}", interface.description);
+        }
+    }
+}
+
+fn describe(selector: &InterfaceSelector) -> String {
+    match selector {
+        InterfaceSelector::Name(name) => name.clone(),
+        InterfaceSelector::Index(index) => format!("#{}", index),
+        InterfaceSelector::Pattern(pattern) => pattern.clone(),
+    }
+}
+
+/// `*` herhangi bir dizi, `?` tek bir karakter
+fn glob_match(pattern: &[u8], text: &[u8]) -> bool {
+    match pattern.split_first() {
+        None => text.is_empty(),
+        Some((b'*', rest)) => (0..=text.len()).any(|skip| glob_match(rest, &text[skip..])),
+        Some((b'?', rest)) => !text.is_empty() && glob_match(rest, &text[1..]),
+        Some((c, rest)) => text.first() == Some(c) && glob_match(rest, &text[1..]),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Linux ve BSD'lerde aynı değerler
+    const UP: u32 = 0x1;
+    const LOOPBACK: u32 = 0x8;
+
+    fn interface(index: u32, name: &str, flags: u32, address: Option<&str>) -> NetworkInterface {
+        NetworkInterface {
+            name: name.to_string(),
+            description: String::new(),
+            index,
+            mac: None,
+            ips: address.map(|address| address.parse().unwrap()).into_iter().collect(),
+            flags,
+        }
+    }
+
+    /// Docker köprüsü gerçek arayüzden önce listelenen bir makine
+    fn host() -> Vec<NetworkInterface> {
+        vec![
+            interface(1, "lo", UP | LOOPBACK, Some("127.0.0.1/8")),
+            interface(2, "docker0", UP, Some("172.17.0.1/16")),
+            interface(3, "eth0", UP, Some("192.168.1.10/24")),
+            interface(4, "eth1", 0, None),
+            interface(5, "wlan0", UP, None),
+        ]
+    }
+
+    fn selections(entries: &[&str]) -> Vec<InterfaceSelection> {
+        entries
+            .iter()
+            .map(|entry| InterfaceSelection::parse(entry, CaptureOptions::default()).unwrap())
+            .collect()
+    }
+
+    /// Seçilen arayüzlerin adları ve yakalama boyları
+    fn selected(entries: &[&str], any: Option<NetworkInterface>) -> Result<Vec<(String, usize)>, String> {
+        let selected = select_from(host(), any, &selections(entries), CaptureOptions::default())?;
+        Ok(selected.into_iter().map(|(interface, options)| (interface.name, options.snaplen)).collect())
+    }
+
+    fn names(entries: &[&str]) -> Vec<String> {
+        selected(entries, None).unwrap().into_iter().map(|(name, _)| name).collect()
+    }
+
+    #[test]
+    fn entries_parse_into_selectors_and_options() {
+        let defaults = CaptureOptions::default();
+        let parse = |entry: &str| InterfaceSelection::parse(entry, defaults);
+        let selector = |entry: &str| parse(entry).map(|selection| (selection.selector, selection.exclude));
+
+        assert_eq!(selector(" eth0 "), Ok((InterfaceSelector::Name("eth0".to_string()), false)));
+        assert_eq!(selector("any"), Ok((InterfaceSelector::Name(ANY_INTERFACE.to_string()), false)));
+        assert_eq!(selector("#3"), Ok((InterfaceSelector::Index(3), false)));
+        assert_eq!(selector("7"), Ok((InterfaceSelector::Index(7), false)));
+        assert_eq!(selector("enp*"), Ok((InterfaceSelector::Pattern("enp*".to_string()), false)));
+        assert_eq!(selector("wl?0"), Ok((InterfaceSelector::Pattern("wl?0".to_string()), false)));
+        assert_eq!(selector("!docker*"), Ok((InterfaceSelector::Pattern("docker*".to_string()), true)));
+        assert_eq!(selector("!#2"), Ok((InterfaceSelector::Index(2), true)));
+
+        let selection = parse("enp* promisc=off snaplen=128 buffer=8388608").unwrap();
+        assert_eq!(
+            selection.options,
+            CaptureOptions {
+                promiscuous: false,
+                snaplen: 128,
+                buffer_size: Some(8_388_608),
+            }
+        );
+        assert_eq!(parse("eth0").unwrap().options, defaults);
+
+        for entry in [
+            "",
+            "!",
+            "#",
+            "#99999999999",
+            "eth0 snaplen",
+            "eth0 snaplen=0",
+            "eth0 promisc=maybe",
+            "eth0 buffer=-1",
+            "eth0 mtu=1500",
+            "!eth0 snaplen=128",
+        ] {
+            assert!(parse(entry).is_err(), "{entry}");
+        }
+    }
+
+    #[test]
+    fn patterns_match_whole_names() {
+        let matches = |pattern: &str, name: &str| glob_match(pattern.as_bytes(), name.as_bytes());
+        assert!(matches("enp*", "enp3s0"));
+        assert!(matches("enp*", "enp"));
+        assert!(matches("*0", "docker0"));
+        assert!(matches("wl?0", "wlx0"));
+        assert!(matches("e*h*", "eth0"));
+        assert!(!matches("enp*", "eth0"));
+        assert!(!matches("wl?0", "wl0"));
+        assert!(!matches("eth", "eth0"));
+    }
+
+    #[test]
+    fn selections_pick_interfaces_in_order_without_excluded_ones() {
+        // Varsayılan seçim köprüye düşer; dışlanınca gerçek arayüz seçilir
+        assert_eq!(names(&[]), ["docker0"]);
+        assert_eq!(names(&["!docker*"]), ["eth0"]);
+        assert!(selected(&["!docker0", "!eth0"], None).is_err());
+
+        // Bir arayüz birden çok seçime uyarsa ilk seçimin seçenekleri geçerli
+        assert_eq!(
+            selected(&["#3 snaplen=128", "eth*"], None),
+            Ok(vec![("eth0".to_string(), 128), ("eth1".to_string(), 65_535)])
+        );
+        assert_eq!(names(&["eth*", "lo", "!#4"]), ["eth0", "lo"]);
+        assert_eq!(names(&["*", "!e*", "!lo"]), ["docker0", "wlan0"]);
+
+        // Uyan arayüzü olmayan kalıp yalnızca uyarılır; ad ve sıra hatadır
+        assert_eq!(names(&["tun*", "wlan0"]), ["wlan0"]);
+        assert!(selected(&["tun*"], None).unwrap_err().contains("Seçimlere uyan"));
+        assert!(selected(&["eth9"], None).unwrap_err().contains("eth9"));
+        assert!(selected(&["#9"], None).unwrap_err().contains("#9"));
+        assert!(selected(&["eth0", "!eth0"], None).is_err());
+    }
+
+    #[test]
+    fn any_is_selected_only_by_its_exact_name() {
+        let any = interface(ANY_INDEX, ANY_INTERFACE, UP, None);
+        assert!(is_any(&any));
+        assert_eq!(selected(&["any"], Some(any.clone())), Ok(vec![(ANY_INTERFACE.to_string(), 65_535)]));
+        assert!(!selected(&["*"], Some(any.clone())).unwrap().iter().any(|(name, _)| name == ANY_INTERFACE));
+        assert!(selected(&["any", "!any"], Some(any)).is_err());
+        // Sözde arayüzün olmadığı sistemlerde
+        assert!(selected(&["any"], None).is_err());
+    }
+}
@@ -1,34 +1,123 @@
-use tokio;
+#[cfg(target_os = "linux")]
+mod afpacket;
+#[cfg_attr(not(target_os = "linux"), allow(dead_code))]
+mod bpf;
 mod capture;
+mod config;
+mod decode;
+mod dns;
+mod filter;
+mod flow;
+mod interfaces;
+mod metrics;
+#[cfg(target_os = "linux")]
+mod netlink;
+mod pcap;
+mod pipeline;
+mod reassembly;
+mod recorder;
+#[cfg(target_os = "linux")]
+mod ring;
+mod source;
+mod stats;
+mod stream;
+mod supervisor;
+mod synthetic;
+mod tcp;
+#[cfg(test)]
+mod testutil;
+mod tls;
+mod tunnel;
 mod websocket;
 
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+
+use config::{CaptureMode, Config};
+use recorder::Recorder;
+use stats::Stats;
+use supervisor::{CaptureStatus, StatusReporter};
+
 #[tokio::main]
 async fn main() {
+    if std::env::args().any(|arg| arg == "--list-interfaces") {
+        interfaces::print_list();
+        return;
+    }
+
     println!("Uygulama başlatılıyor...");
-    
+
+    let config = match Config::from_env() {
+        Ok(config) => config,
+        Err(e) => {
+            eprintln!("Yapılandırma hatası: {}", e);
+            return;
+        }
+    };
+
     // Broadcast kanalı oluştur
     let (tx, _) = tokio::sync::broadcast::channel(100);
     let tx_ws = tx.clone();
+    let (status, status_rx) = StatusReporter::new(tx.clone());
+    let stats = Arc::new(Stats::default());
+
+    if let Some(interval) = config.stats_interval {
+        tokio::spawn(stats::publish(tx.clone(), stats.clone(), interval));
+    }
+
+    // Yakalama thread'leri bu bayrağı görünce kendiliğinden sonlanır
+    let shutdown = Arc::new(AtomicBool::new(false));
+    let stop = shutdown.clone();
 
     // WebSocket sunucusunu başlat
+    let websocket_stats = stats.clone();
     let websocket_task = tokio::spawn(async move {
         println!("WebSocket sunucusu başlatılıyor...");
-        if let Err(e) = websocket::start_websocket_server(tx_ws).await {
+        if let Err(e) = websocket::start_websocket_server(tx_ws, status_rx, websocket_stats).await {
             eprintln!("WebSocket sunucusu hatası: {}", e);
         }
     });
 
+    // Metrik sunucusu çalışmasa da yakalama sürer
+    let metrics_addr = config.metrics_addr.clone();
+    let metrics_stats = stats.clone();
+    tokio::spawn(async move {
+        if let Err(e) = metrics::start_metrics_server(metrics_addr, metrics_stats).await {
+            eprintln!("Metrik sunucusu hatası: {}", e);
+        }
+    });
+
+    // Kayıt yalnızca canlı yakalamada anlamlı
+    let recorder = match (&config.mode, config.record.clone()) {
+        (CaptureMode::Live, Some(record)) => match Recorder::start(record, stats.clone()) {
+            Ok(recorder) => Some(recorder),
+            Err(e) => {
+                eprintln!("Kayıt başlatılamadı: {}", e);
+                None
+            }
+        },
+        _ => None,
+    };
+    let capture_recorder = recorder.clone();
+
     // Paket yakalamayı başlat
-    let capture_task = tokio::spawn(async move {
-        println!("Paket yakalama başlatılıyor...");
-        loop {
-            match capture::start_packet_capture(tx.clone()).await {
-                Ok(_) => println!("Paket yakalama normal şekilde sonlandı"),
-                Err(e) => {
-                    eprintln!("Paket yakalama hatası: {}", e);
-                    println!("5 saniye sonra yeniden başlatılacak...");
-                    tokio::time::sleep(tokio::time::Duration::from_secs(5)).await;
-                }
+    let mut capture_task = tokio::spawn(async move {
+        match config.mode.clone() {
+            CaptureMode::Replay { path, speed } => {
+                println!("Kayıt dosyası oynatılıyor...");
+                status.report(CaptureStatus::Running { interfaces: vec![path.display().to_string()] });
+                let result = capture::start_replay(tx.clone(), &path, speed, &config, stop.clone(), stats).await;
+                finish("Kayıt oynatma", result, &status, &stop).await;
+            }
+            CaptureMode::Synthetic(synthetic) => {
+                println!("Sentetik trafik üretiliyor...");
+                status.report(CaptureStatus::Running { interfaces: vec![synthetic::SYNTHETIC_SOURCE_NAME.to_string()] });
+                let result = capture::start_synthetic(tx.clone(), &synthetic, &config, stop.clone(), stats).await;
+                finish("Sentetik trafik", result, &status, &stop).await;
+            }
+            CaptureMode::Live => {
+                println!("Paket yakalama başlatılıyor...");
+                supervisor::supervise(tx.clone(), &config, stop.clone(), status, stats, capture_recorder).await;
             }
         }
     });
@@ -36,6 +125,37 @@ async fn main() {
     // Her iki task'ı da bekle
     tokio::select! {
         _ = websocket_task => println!("WebSocket sunucusu durdu"),
-        _ = capture_task => println!("Paket yakalama durdu"),
+        _ = &mut capture_task => println!("Paket yakalama durdu"),
+        _ = tokio::signal::ctrl_c() => {
+            println!("Kapatma isteği alındı, paket yakalama durduruluyor...");
+            shutdown.store(true, Ordering::Relaxed);
+            if tokio::time::timeout(tokio::time::Duration::from_secs(10), capture_task).await.is_err() {
+                eprintln!("Paket yakalama zamanında durmadı");
+            }
+            if let Some(recorder) = recorder {
+                recorder.close();
+            }
+        }
+    }
+}
+
+/// Kayıt oynatma ya da sentetik trafik bitince sonucu bildirir ve kapatma
+/// istenene kadar bekler.
+async fn finish(
+    name: &str,
+    result: Result<(), Box<dyn std::error::Error + Send + Sync>>,
+    status: &StatusReporter,
+    stop: &AtomicBool,
+) {
+    match result {
+        Ok(()) => status.report(CaptureStatus::Finished),
+        Err(e) => {
+            eprintln!("{} hatası: {}", name, e);
+            status.report(CaptureStatus::Failed { reason: e.to_string(), retry_in: None });
+        }
+    }
+    // Kaynak bitse de istemciler haritayı incelemeye devam edebilsin
+    while !stop.load(Ordering::Relaxed) {
+        tokio::time::sleep(tokio::time::Duration::from_millis(250)).await;
     }
-} 
\ No newline at end of file
+}
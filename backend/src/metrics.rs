@@ -0,0 +1,261 @@
+use std::fmt::Write as _;
+use std::sync::Arc;
+use std::time::Duration;
+
+use pnet::packet::ip::IpNextHeaderProtocol;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+
+use crate::stats::{Counter, Stats, TrafficCounters};
+
+// İstek satırı ve başlıklar için yeterli; daha uzun istekler reddedilir
+const MAX_REQUEST_LEN: usize = 8192;
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Prometheus'un okuyacağı `/metrics` uç noktasını sunar.
+pub async fn start_metrics_server(addr: String, stats: Arc<Stats>) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    let listener = TcpListener::bind(&addr).await?;
+    println!("Metrik sunucusu başlatıldı: http://{}/metrics", addr);
+
+    while let Ok((stream, addr)) = listener.accept().await {
+        let stats = stats.clone();
+        tokio::spawn(async move {
+            match tokio::time::timeout(REQUEST_TIMEOUT, handle_request(stream, &stats)).await {
+                Ok(Ok(())) => {}
+                Ok(Err(e)) => eprintln!("Metrik isteği hatası {}: {}", addr, e),
+                Err(_) => eprintln!("Metrik isteği zaman aşımına uğradı: {}", addr),
+            }
+        });
+    }
+
+    Ok(())
+}
+
+async fn handle_request(mut stream: TcpStream, stats: &Stats) -> std::io::Result<()> {
+    let mut buffer = vec![0u8; MAX_REQUEST_LEN];
+    let mut len = 0;
+    while !buffer[..len].windows(4).any(|window| window == b"\r\n\r\n") {
+        if len == buffer.len() {
+            return respond(&mut stream, "431 Request Header Fields Too Large", "").await;
+        }
+        let n = stream.read(&mut buffer[len..]).await?;
+        if n == 0 {
+            return Ok(());
+        }
+        len += n;
+    }
+
+    let request = String::from_utf8_lossy(&buffer[..len]);
+    let mut request_line = request.lines().next().unwrap_or_default().split_whitespace();
+    let method = request_line.next().unwrap_or_default();
+    let target = request_line.next().unwrap_or_default();
+    let path = target.split('?').next().unwrap_or_default();
+
+    match (method, path) {
+        ("GET", "/metrics") => respond(&mut stream, "200 OK", &render(stats)).await,
+        (_, "/metrics") => respond(&mut stream, "405 Method Not Allowed", "").await,
+        _ => respond(&mut stream, "404 Not Found", "").await,
+    }
+}
+
+async fn respond(stream: &mut TcpStream, status: &str, body: &str) -> std::io::Result<()> {
+    let response = format!(
+        "HTTP/1.1 {}\r\nContent-Type: text/plain; version=0.0.4; charset=utf-8\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+        status,
+        body.len(),
+        body
+    );
+    stream.write_all(response.as_bytes()).await?;
+    stream.shutdown().await
+}
+
+/// Prometheus metin biçimi (0.0.4)
+fn render(stats: &Stats) -> String {
+    let mut out = String::new();
+    let counter = |out: &mut String, name: &str, help: &str, counter: &Counter| {
+        metric(out, name, "counter", help, counter.get());
+    };
+
+    let capture = &stats.capture;
+    counter(&mut out, "ntv_capture_frames_total", "Yakalanan çerçeveler", &capture.frames);
+    counter(&mut out, "ntv_capture_queue_full_total", "Çözümleme kuyruğu dolu olduğu için bekleyen çerçeveler", &capture.queue_full);
+    counter(&mut out, "ntv_capture_read_errors_total", "Yakalama okuma hataları", &capture.read_errors);
+    counter(&mut out, "ntv_capture_restarts_total", "Yakalamanın yeniden kurulma sayısı", &capture.restarts);
+    counter(&mut out, "ntv_kernel_received_total", "Çekirdekte sokete ulaşan paketler", &capture.kernel_received);
+    counter(&mut out, "ntv_kernel_dropped_total", "Çekirdekte düşen paketler", &capture.kernel_dropped);
+    counter(&mut out, "ntv_kernel_freeze_queue_total", "Halka kuyruğunun dondurulma sayısı", &capture.kernel_freeze_queue);
+    counter(&mut out, "ntv_record_packets_total", "pcapng kaydına yazılan çerçeveler", &capture.recorded);
+    counter(&mut out, "ntv_record_dropped_total", "Kayda alınamayan çerçeveler", &capture.record_dropped);
+
+    let decode = &stats.decode;
+    counter(&mut out, "ntv_decode_frames_total", "Çözümlenen çerçeveler", &decode.frames);
+    counter(&mut out, "ntv_decode_undecodable_total", "Çözülemeyen çerçeveler", &decode.undecodable);
+    counter(&mut out, "ntv_decode_fragments_total", "IP parçaları", &decode.fragments);
+    counter(&mut out, "ntv_decode_reassembled_total", "Birleştirilen datagramlar", &decode.reassembled);
+    counter(&mut out, "ntv_decode_fragments_timed_out_total", "Zaman aşımıyla atılan yarım datagramlar", &decode.fragments_timed_out);
+    counter(&mut out, "ntv_decode_fragments_evicted_total", "Sınır aşımıyla atılan yarım datagramlar", &decode.fragments_evicted);
+    counter(&mut out, "ntv_decode_fragments_overlapping_total", "Çakışan parça içeren datagramlar", &decode.fragments_overlapping);
+    counter(&mut out, "ntv_decode_fragments_malformed_total", "Bozuk parçalar", &decode.fragments_malformed);
+    counter(&mut out, "ntv_decode_tunneled_total", "Tünelden çıkarılan paketler", &decode.tunneled);
+    counter(&mut out, "ntv_decode_tunnel_undecodable_total", "İç paketi çözülemeyen tünel paketleri", &decode.tunnel_undecodable);
+    counter(&mut out, "ntv_decode_tunnels_too_deep_total", "İç içe tünel sınırını aşan paketler", &decode.tunnels_too_deep);
+    counter(&mut out, "ntv_decode_icmp_errors_total", "ICMP hata mesajları", &decode.icmp_errors);
+    counter(&mut out, "ntv_decode_untracked_total", "İki ucu da izlenmeyen paketler", &decode.untracked);
+    counter(&mut out, "ntv_decode_no_transport_total", "Akış açmayan paketler", &decode.no_transport);
+
+    let flows = &stats.flows;
+    counter(&mut out, "ntv_flow_packets_total", "Akış tablosuna giren paketler", &flows.packets);
+    counter(&mut out, "ntv_flows_started_total", "Açılan akışlar", &flows.started);
+    counter(&mut out, "ntv_flow_merged_packets_total", "Var olan akışa eklenen paketler", &flows.merged);
+    counter(&mut out, "ntv_flows_ended_total", "Sonlanan akışlar", &flows.ended);
+    counter(&mut out, "ntv_flows_reported_total", "İstemcilere bildirilen akışlar", &flows.reported);
+    counter(&mut out, "ntv_flows_filtered_total", "Filtreye uymayan akışlar", &flows.filtered);
+    counter(&mut out, "ntv_flows_unlocated_total", "Konumlanamadığı için bildirilmeyen akışlar", &flows.unlocated);
+    counter(&mut out, "ntv_flows_failed_handshakes_total", "Başarısız TCP el sıkışmaları", &flows.failed_handshakes);
+    metric(
+        &mut out,
+        "ntv_flows_active",
+        "gauge",
+        "Akış tablosundaki akışlar",
+        flows.started.get().saturating_sub(flows.ended.get()),
+    );
+
+    let geoip = &stats.geoip;
+    counter(&mut out, "ntv_geoip_lookups_total", "GeoIP sorguları", &geoip.lookups);
+    counter(&mut out, "ntv_geoip_hits_total", "Konum bulunan GeoIP sorguları", &geoip.hits);
+    counter(&mut out, "ntv_geoip_misses_total", "Konum bulunamayan GeoIP sorguları", &geoip.misses);
+
+    let dns = &stats.dns;
+    counter(&mut out, "ntv_dns_responses_total", "Okunan DNS yanıtları", &dns.responses);
+    counter(&mut out, "ntv_dns_addresses_total", "DNS önbelleğine eklenen adresler", &dns.addresses);
+    counter(&mut out, "ntv_dns_malformed_total", "Çözülemeyen DNS mesajları", &dns.malformed);
+
+    let tls = &stats.tls;
+    counter(&mut out, "ntv_tls_client_hellos_total", "Akış başında okunan TLS ClientHello mesajları", &tls.client_hellos);
+    counter(&mut out, "ntv_tls_malformed_total", "Çözülemeyen TLS ClientHello mesajları", &tls.malformed);
+
+    let websocket = &stats.websocket;
+    metric(&mut out, "ntv_websocket_clients", "gauge", "Bağlı WebSocket istemcileri", websocket.clients.get());
+    counter(&mut out, "ntv_websocket_connections_total", "Kabul edilen WebSocket bağlantıları", &websocket.connections);
+    counter(&mut out, "ntv_broadcast_events_total", "Broadcast kanalına aktarılan olaylar", &websocket.events);
+    counter(&mut out, "ntv_broadcast_no_receivers_total", "Bağlı istemci olmadığı için ulaşmayan olaylar", &websocket.no_receivers);
+    counter(&mut out, "ntv_broadcast_lagged_total", "Yavaş istemcilerin kaçırdığı mesajlar", &websocket.lagged);
+    counter(&mut out, "ntv_websocket_sent_total", "İstemcilere gönderilen mesajlar", &websocket.sent);
+    counter(&mut out, "ntv_websocket_filtered_total", "İstemci filtresine uymayan mesajlar", &websocket.filtered);
+
+    // Etiket sırası kararlı olsun
+    let mut traffic: Vec<_> = stats.traffic().into_iter().collect();
+    traffic.sort_by(|(a, _), (b, _)| (a.protocol, &a.country, a.direction.as_str()).cmp(&(b.protocol, &b.country, b.direction.as_str())));
+    let labelled: Vec<_> = traffic
+        .iter()
+        .map(|(key, counters)| {
+            let labels = format!(
+                "protocol=\"{}\",country=\"{}\",direction=\"{}\"",
+                IpNextHeaderProtocol::new(key.protocol).to_string().to_lowercase(),
+                escape(key.country.as_deref().unwrap_or("unknown")),
+                key.direction.as_str()
+            );
+            (labels, *counters)
+        })
+        .collect();
+    traffic_family(&mut out, "ntv_traffic_flows_total", "Protokol, ülke ve yöne göre akışlar", &labelled, |counters| counters.flows);
+    traffic_family(&mut out, "ntv_traffic_packets_total", "Protokol, ülke ve yöne göre paketler", &labelled, |counters| counters.packets);
+    traffic_family(&mut out, "ntv_traffic_bytes_total", "Protokol, ülke ve yöne göre IP baytları", &labelled, |counters| counters.bytes);
+
+    metric(&mut out, "ntv_uptime_seconds", "gauge", "Uygulamanın çalışma süresi", stats.uptime().as_secs());
+    out
+}
+
+fn metric(out: &mut String, name: &str, kind: &str, help: &str, value: u64) {
+    let _ = writeln!(out, "# HELP {} {}\n# TYPE {} {}\n{} {}", name, help, name, kind, name, value);
+}
+
+fn traffic_family(
+    out: &mut String,
+    name: &str,
+    help: &str,
+    labelled: &[(String, TrafficCounters)],
+    value: impl Fn(&TrafficCounters) -> u64,
+) {
+    let _ = writeln!(out, "# HELP {} {}\n# TYPE {} counter", name, help, name);
+    for (labels, counters) in labelled {
+        let _ = writeln!(out, "{}{{{}}} {}", name, labels, value(counters));
+    }
+}
+
+fn escape(value: &str) -> String {
+    value.replace('\\', "\\\\").replace('"', "\\\"").replace('\n', "\\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::flow::Direction;
+    use crate::stats::{Traffic, TrafficKey};
+    use std::collections::HashSet;
+
+    fn traffic(protocol: u8, country: Option<&str>, direction: Direction, bytes: u64) -> (TrafficKey, TrafficCounters) {
+        let key = TrafficKey {
+            protocol,
+            country: country.map(str::to_string),
+            direction,
+        };
+        (key, TrafficCounters { flows: 1, packets: 2, bytes })
+    }
+
+    #[test]
+    fn render_types_values_and_escaped_labels() {
+        let stats = Stats::default();
+        stats.capture.frames.add(5);
+        stats.capture.restarts.inc();
+        stats.flows.started.add(3);
+        stats.flows.ended.inc();
+        stats.websocket.clients.inc();
+        stats.websocket.clients.inc();
+        let mut local: Traffic = [
+            traffic(6, Some("T\"R\\x\ny"), Direction::Outbound, 1500),
+            traffic(17, None, Direction::Inbound, 80),
+        ]
+        .into_iter()
+        .collect();
+        stats.merge_traffic(&mut local);
+
+        let out = render(&stats);
+        let lines: Vec<&str> = out.lines().collect();
+        for expected in [
+            "# TYPE ntv_capture_frames_total counter",
+            "ntv_capture_frames_total 5",
+            "ntv_capture_restarts_total 1",
+            "# TYPE ntv_flows_active gauge",
+            "ntv_flows_active 2",
+            "# TYPE ntv_websocket_clients gauge",
+            "ntv_websocket_clients 2",
+            "# TYPE ntv_traffic_bytes_total counter",
+            r#"ntv_traffic_bytes_total{protocol="tcp",country="T\"R\\x\ny",direction="outbound"} 1500"#,
+            r#"ntv_traffic_bytes_total{protocol="udp",country="unknown",direction="inbound"} 80"#,
+            r#"ntv_traffic_packets_total{protocol="udp",country="unknown",direction="inbound"} 2"#,
+            "# TYPE ntv_uptime_seconds gauge",
+        ] {
+            assert!(lines.contains(&expected), "{expected}\n{out}");
+        }
+
+        // Her ailenin tek TYPE satırı var ve örnekler ondan sonra geliyor;
+        // kaçışsız bir satır sonu örneği bölerdi
+        let mut typed = HashSet::new();
+        for line in lines {
+            if let Some(family) = line.strip_prefix("# TYPE ") {
+                let (name, kind) = family.split_once(' ').unwrap();
+                assert!(kind == "counter" || kind == "gauge", "{line}");
+                assert!(typed.insert(name), "{line}");
+            } else if !line.starts_with("# HELP ") {
+                let (series, value) = line.rsplit_once(' ').unwrap();
+                let name = series.split('{').next().unwrap();
+                assert!(typed.contains(name), "{line}");
+                assert!(value.parse::<u64>().is_ok(), "{line}");
+                if name.ends_with("_total") {
+                    assert!(out.contains(&format!("# TYPE {} counter", name)), "{line}");
+                }
+            }
+        }
+    }
+}
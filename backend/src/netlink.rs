@@ -0,0 +1,162 @@
+use std::io;
+use std::mem;
+use std::os::fd::{AsRawFd, FromRawFd, OwnedFd};
+use std::thread;
+
+use tokio::sync::mpsc;
+
+use crate::afpacket::check;
+
+// Bir okumada birden çok netlink mesajı gelebilir
+const RECV_BUFFER_LEN: usize = 8192;
+const NLMSG_HEADER_LEN: usize = mem::size_of::<libc::nlmsghdr>();
+
+/// Arayüzün bağlantı durumu ya da adreslerindeki değişiklik
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LinkEvent {
+    /// Arayüz eklendi ya da durumu (up/down, carrier) değişti
+    Link { index: u32 },
+    /// Arayüz kaldırıldı
+    Removed { index: u32 },
+    /// Adres eklendi ya da kaldırıldı
+    Address { index: u32 },
+}
+
+/// Bağlantı ve adres değişikliklerini dinleyen thread'i başlatır. Alıcı
+/// bırakıldığında thread bir sonraki olayda kendiliğinden sonlanır.
+pub fn watch() -> io::Result<mpsc::UnboundedReceiver<LinkEvent>> {
+    let socket = check(unsafe { libc::socket(libc::AF_NETLINK, libc::SOCK_RAW | libc::SOCK_CLOEXEC, libc::NETLINK_ROUTE) })?;
+    let socket = unsafe { OwnedFd::from_raw_fd(socket) };
+
+    let mut address: libc::sockaddr_nl = unsafe { mem::zeroed() };
+    address.nl_family = libc::AF_NETLINK as u16;
+    address.nl_groups = (libc::RTMGRP_LINK | libc::RTMGRP_IPV4_IFADDR | libc::RTMGRP_IPV6_IFADDR) as u32;
+    check(unsafe {
+        libc::bind(
+            socket.as_raw_fd(),
+            &address as *const libc::sockaddr_nl as *const libc::sockaddr,
+            mem::size_of::<libc::sockaddr_nl>() as libc::socklen_t,
+        )
+    })?;
+
+    let (tx, rx) = mpsc::unbounded_channel();
+    thread::Builder::new()
+        .name("netlink".to_string())
+        .spawn(move || run(socket, tx))?;
+    Ok(rx)
+}
+
+fn run(socket: OwnedFd, tx: mpsc::UnboundedSender<LinkEvent>) {
+    let mut buffer = vec![0u8; RECV_BUFFER_LEN];
+    loop {
+        let len = unsafe {
+            libc::recv(socket.as_raw_fd(), buffer.as_mut_ptr() as *mut libc::c_void, buffer.len(), 0)
+        };
+        if len < 0 {
+            let error = io::Error::last_os_error();
+            // Tampon taştıysa olay kaçmıştır; yine de bir değişiklik bildir
+            if error.raw_os_error() == Some(libc::ENOBUFS) {
+                if tx.send(LinkEvent::Link { index: 0 }).is_err() {
+                    return;
+                }
+                continue;
+            }
+            if error.kind() == io::ErrorKind::Interrupted {
+                continue;
+            }
+            eprintln!("Netlink okuma hatası: {}", error);
+            return;
+        }
+
+        for event in parse(&buffer[..len as usize]) {
+            if tx.send(event).is_err() {
+                return;
+            }
+        }
+    }
+}
+
+/// Mesajlardan arayüz olaylarını çıkarır. `ifinfomsg` ve `ifaddrmsg`
+/// başlıklarında arayüz sırası aynı konumdadır (4. bayt).
+fn parse(mut data: &[u8]) -> Vec<LinkEvent> {
+    let mut events = Vec::new();
+    while data.len() >= NLMSG_HEADER_LEN {
+        let len = u32::from_ne_bytes([data[0], data[1], data[2], data[3]]) as usize;
+        let kind = u16::from_ne_bytes([data[4], data[5]]);
+        if len < NLMSG_HEADER_LEN || len > data.len() {
+            break;
+        }
+
+        let body = &data[NLMSG_HEADER_LEN..len];
+        if let Some(bytes) = body.get(4..8) {
+            let index = u32::from_ne_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]);
+            match kind {
+                libc::RTM_NEWLINK => events.push(LinkEvent::Link { index }),
+                libc::RTM_DELLINK => events.push(LinkEvent::Removed { index }),
+                libc::RTM_NEWADDR | libc::RTM_DELADDR => events.push(LinkEvent::Address { index }),
+                _ => {}
+            }
+        }
+
+        // Mesajlar 4 bayta hizalı
+        let aligned = (len + 3) & !3;
+        data = data.get(aligned..).unwrap_or_default();
+    }
+    events
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn message(kind: u16, index: u32, body_len: usize) -> Vec<u8> {
+        let len = NLMSG_HEADER_LEN + body_len;
+        let mut data = (len as u32).to_ne_bytes().to_vec();
+        data.extend_from_slice(&kind.to_ne_bytes());
+        data.resize(NLMSG_HEADER_LEN, 0);
+        let mut body = vec![0u8; body_len];
+        body[4..8].copy_from_slice(&index.to_ne_bytes());
+        data.extend(body);
+        // Sonraki mesaj 4 bayta hizalı başlar
+        data.resize((len + 3) & !3, 0);
+        data
+    }
+
+    #[test]
+    fn link_and_address_messages_become_events() {
+        let data = [
+            message(libc::RTM_NEWLINK, 2, 16),
+            message(libc::RTM_NEWADDR, 2, 9),
+            message(libc::RTM_NEWROUTE, 2, 12),
+            message(libc::RTM_DELADDR, 3, 8),
+            message(libc::RTM_DELLINK, 3, 16),
+        ]
+        .concat();
+        assert_eq!(
+            parse(&data),
+            [
+                LinkEvent::Link { index: 2 },
+                LinkEvent::Address { index: 2 },
+                LinkEvent::Address { index: 3 },
+                LinkEvent::Removed { index: 3 },
+            ]
+        );
+    }
+
+    #[test]
+    fn truncated_messages_stop_parsing() {
+        let mut data = message(libc::RTM_NEWLINK, 2, 16);
+        let second = message(libc::RTM_DELLINK, 3, 16);
+        data.extend_from_slice(&second[..second.len() - 4]);
+        assert_eq!(parse(&data), [LinkEvent::Link { index: 2 }]);
+
+        // Gövdesi arayüz sırasını taşımayan ve uzunluğu başlıktan kısa mesajlar
+        let mut headless = message(libc::RTM_NEWLINK, 2, 8);
+        headless[..4].copy_from_slice(&(NLMSG_HEADER_LEN as u32 + 4).to_ne_bytes());
+        headless.truncate(NLMSG_HEADER_LEN + 4);
+        assert!(parse(&headless).is_empty());
+        let mut short = message(libc::RTM_NEWLINK, 2, 16);
+        short[..4].copy_from_slice(&4u32.to_ne_bytes());
+        assert!(parse(&short).is_empty());
+    }
+}
@@ -0,0 +1,659 @@
+use std::fs::File;
+use std::io::{self, BufReader, Read, Write};
+use std::path::Path;
+use std::time::Duration;
+
+// Bir kayıt bloğu veya paket için kabul edilen en büyük boyut. Bozuk dosyalarda
+// devasa bellek ayırmayı engeller.
+const MAX_PACKET_LEN: usize = 256 * 1024;
+const MAX_BLOCK_LEN: usize = 16 * 1024 * 1024;
+
+const PCAPNG_SHB: u32 = 0x0A0D_0D0A;
+const PCAPNG_IDB: u32 = 0x0000_0001;
+const PCAPNG_PB: u32 = 0x0000_0002;
+const PCAPNG_SPB: u32 = 0x0000_0003;
+const PCAPNG_EPB: u32 = 0x0000_0006;
+const PCAPNG_BYTE_ORDER_MAGIC: u32 = 0x1A2B_3C4D;
+
+const PCAPNG_OPT_END: u16 = 0;
+const PCAPNG_OPT_COMMENT: u16 = 1;
+const PCAPNG_OPT_IF_NAME: u16 = 2;
+const PCAPNG_OPT_IF_TSRESOL: u16 = 9;
+const PCAPNG_OPT_IF_TSOFFSET: u16 = 14;
+
+pub struct PcapPacket {
+    /// UNIX epoch'tan itibaren yakalama zamanı
+    pub timestamp: Duration,
+    pub link_type: u32,
+    pub data: Vec<u8>,
+}
+
+struct NgInterface {
+    link_type: u32,
+    ticks_per_second: u64,
+    offset_secs: i64,
+}
+
+enum Format {
+    Classic { link_type: u32, nanos: bool },
+    Ng { interfaces: Vec<NgInterface> },
+}
+
+/// Klasik pcap ve pcapng dosyalarını paket paket okur.
+pub struct PcapReader<R> {
+    inner: R,
+    big_endian: bool,
+    format: Format,
+    last_timestamp: Duration,
+}
+
+impl PcapReader<BufReader<File>> {
+    pub fn open(path: &Path) -> io::Result<Self> {
+        Self::new(BufReader::new(File::open(path)?))
+    }
+}
+
+impl<R: Read> PcapReader<R> {
+    pub fn new(mut inner: R) -> io::Result<Self> {
+        let mut magic = [0u8; 4];
+        inner.read_exact(&mut magic)?;
+
+        let (big_endian, nanos) = match magic {
+            [0xd4, 0xc3, 0xb2, 0xa1] => (false, false),
+            [0xa1, 0xb2, 0xc3, 0xd4] => (true, false),
+            [0x4d, 0x3c, 0xb2, 0xa1] => (false, true),
+            [0xa1, 0xb2, 0x3c, 0x4d] => (true, true),
+            [0x0a, 0x0d, 0x0d, 0x0a] => {
+                let mut reader = Self {
+                    inner,
+                    big_endian: false,
+                    format: Format::Ng { interfaces: Vec::new() },
+                    last_timestamp: Duration::ZERO,
+                };
+                reader.read_section_header()?;
+                return Ok(reader);
+            }
+            _ => return Err(invalid_data("pcap/pcapng imzası tanınmadı")),
+        };
+
+        let mut header = [0u8; 20];
+        inner.read_exact(&mut header)?;
+        let link_type = read_u32(&header[16..20], big_endian) & 0xFFFF;
+
+        Ok(Self {
+            inner,
+            big_endian,
+            format: Format::Classic { link_type, nanos },
+            last_timestamp: Duration::ZERO,
+        })
+    }
+
+    /// Sıradaki paketi döndürür, dosya sonunda `None` verir.
+    pub fn next_packet(&mut self) -> io::Result<Option<PcapPacket>> {
+        match self.format {
+            Format::Classic { link_type, nanos } => self.next_classic(link_type, nanos),
+            Format::Ng { .. } => self.next_ng(),
+        }
+    }
+
+    fn next_classic(&mut self, link_type: u32, nanos: bool) -> io::Result<Option<PcapPacket>> {
+        let mut header = [0u8; 16];
+        if !read_exact_or_eof(&mut self.inner, &mut header)? {
+            return Ok(None);
+        }
+
+        let secs = read_u32(&header[0..4], self.big_endian) as u64;
+        let frac = read_u32(&header[4..8], self.big_endian);
+        let incl_len = read_u32(&header[8..12], self.big_endian) as usize;
+
+        if incl_len > MAX_PACKET_LEN {
+            return Err(invalid_data("pcap kaydı çok büyük"));
+        }
+
+        let mut data = vec![0u8; incl_len];
+        self.inner.read_exact(&mut data)?;
+
+        let nanos = if nanos { frac } else { frac.saturating_mul(1_000) };
+        let timestamp = Duration::new(secs, nanos.min(999_999_999));
+        self.last_timestamp = timestamp;
+
+        Ok(Some(PcapPacket { timestamp, link_type, data }))
+    }
+
+    fn next_ng(&mut self) -> io::Result<Option<PcapPacket>> {
+        loop {
+            let mut block_type = [0u8; 4];
+            if !read_exact_or_eof(&mut self.inner, &mut block_type)? {
+                return Ok(None);
+            }
+
+            // SHB simetrik olduğu için bayt sırasından bağımsız tanınır
+            if read_u32(&block_type, true) == PCAPNG_SHB {
+                self.read_section_header()?;
+                continue;
+            }
+
+            let block_type = read_u32(&block_type, self.big_endian);
+            let body = self.read_block_body()?;
+
+            match block_type {
+                PCAPNG_IDB => self.parse_interface(&body)?,
+                PCAPNG_EPB => return self.parse_enhanced_packet(&body).map(Some),
+                PCAPNG_SPB => return self.parse_simple_packet(&body).map(Some),
+                PCAPNG_PB => return self.parse_obsolete_packet(&body).map(Some),
+                _ => {}
+            }
+        }
+    }
+
+    fn read_section_header(&mut self) -> io::Result<()> {
+        let mut header = [0u8; 8];
+        self.inner.read_exact(&mut header)?;
+
+        self.big_endian = match read_u32(&header[4..8], true) {
+            PCAPNG_BYTE_ORDER_MAGIC => true,
+            m if m.swap_bytes() == PCAPNG_BYTE_ORDER_MAGIC => false,
+            _ => return Err(invalid_data("pcapng bayt sırası imzası geçersiz")),
+        };
+
+        let total_len = read_u32(&header[0..4], self.big_endian) as usize;
+        if !(16..=MAX_BLOCK_LEN).contains(&total_len) || !total_len.is_multiple_of(4) {
+            return Err(invalid_data("pcapng bölüm başlığı uzunluğu geçersiz"));
+        }
+
+        // Sürüm, bölüm uzunluğu ve seçenekler kullanılmıyor
+        let mut rest = vec![0u8; total_len - 12];
+        self.inner.read_exact(&mut rest)?;
+
+        self.format = Format::Ng { interfaces: Vec::new() };
+        Ok(())
+    }
+
+    fn read_block_body(&mut self) -> io::Result<Vec<u8>> {
+        let mut len = [0u8; 4];
+        self.inner.read_exact(&mut len)?;
+        let total_len = read_u32(&len, self.big_endian) as usize;
+        if !(12..=MAX_BLOCK_LEN).contains(&total_len) || !total_len.is_multiple_of(4) {
+            return Err(invalid_data("pcapng blok uzunluğu geçersiz"));
+        }
+
+        let mut body = vec![0u8; total_len - 8];
+        self.inner.read_exact(&mut body)?;
+        // Sondaki tekrar uzunluk alanını at
+        body.truncate(total_len - 12);
+        Ok(body)
+    }
+
+    fn parse_interface(&mut self, body: &[u8]) -> io::Result<()> {
+        if body.len() < 8 {
+            return Err(invalid_data("pcapng arayüz bloğu kısa"));
+        }
+
+        let mut interface = NgInterface {
+            link_type: read_u16(&body[0..2], self.big_endian) as u32,
+            ticks_per_second: 1_000_000,
+            offset_secs: 0,
+        };
+
+        let mut options = &body[8..];
+        while options.len() >= 4 {
+            let code = read_u16(&options[0..2], self.big_endian);
+            let len = read_u16(&options[2..4], self.big_endian) as usize;
+            if code == PCAPNG_OPT_END || options.len() < 4 + len {
+                break;
+            }
+            let value = &options[4..4 + len];
+
+            match code {
+                PCAPNG_OPT_IF_TSRESOL if len >= 1 => {
+                    let resol = value[0];
+                    let exponent = (resol & 0x7f) as u32;
+                    interface.ticks_per_second = if resol & 0x80 != 0 {
+                        1u64.checked_shl(exponent).unwrap_or(1)
+                    } else {
+                        10u64.checked_pow(exponent).unwrap_or(1)
+                    };
+                }
+                PCAPNG_OPT_IF_TSOFFSET if len >= 8 => {
+                    interface.offset_secs = read_u64(&value[0..8], self.big_endian) as i64;
+                }
+                _ => {}
+            }
+
+            options = &options[((4 + len + 3) & !3).min(options.len())..];
+        }
+
+        if let Format::Ng { interfaces } = &mut self.format {
+            interfaces.push(interface);
+        }
+        Ok(())
+    }
+
+    fn parse_enhanced_packet(&mut self, body: &[u8]) -> io::Result<PcapPacket> {
+        if body.len() < 20 {
+            return Err(invalid_data("pcapng paket bloğu kısa"));
+        }
+
+        let interface_id = read_u32(&body[0..4], self.big_endian) as usize;
+        let ts_high = read_u32(&body[4..8], self.big_endian) as u64;
+        let ts_low = read_u32(&body[8..12], self.big_endian) as u64;
+        let cap_len = read_u32(&body[12..16], self.big_endian) as usize;
+
+        self.build_packet(interface_id, (ts_high << 32) | ts_low, &body[20..], cap_len)
+    }
+
+    fn parse_obsolete_packet(&mut self, body: &[u8]) -> io::Result<PcapPacket> {
+        if body.len() < 20 {
+            return Err(invalid_data("pcapng paket bloğu kısa"));
+        }
+
+        let interface_id = read_u16(&body[0..2], self.big_endian) as usize;
+        let ts_high = read_u32(&body[4..8], self.big_endian) as u64;
+        let ts_low = read_u32(&body[8..12], self.big_endian) as u64;
+        let cap_len = read_u32(&body[12..16], self.big_endian) as usize;
+
+        self.build_packet(interface_id, (ts_high << 32) | ts_low, &body[20..], cap_len)
+    }
+
+    fn parse_simple_packet(&mut self, body: &[u8]) -> io::Result<PcapPacket> {
+        if body.len() < 4 {
+            return Err(invalid_data("pcapng basit paket bloğu kısa"));
+        }
+
+        let orig_len = read_u32(&body[0..4], self.big_endian);
+        let data = &body[4..];
+        let cap_len = (orig_len as usize).min(data.len());
+        let link_type = self.interface(0)?.link_type;
+
+        // Basit paket bloğunda zaman damgası yok, bir öncekini kullan
+        Ok(PcapPacket {
+            timestamp: self.last_timestamp,
+            link_type,
+            data: data[..cap_len].to_vec(),
+        })
+    }
+
+    fn build_packet(
+        &mut self,
+        interface_id: usize,
+        ticks: u64,
+        data: &[u8],
+        cap_len: usize,
+    ) -> io::Result<PcapPacket> {
+        if cap_len > data.len() || cap_len > MAX_PACKET_LEN {
+            return Err(invalid_data("pcapng paket uzunluğu geçersiz"));
+        }
+
+        let interface = self.interface(interface_id)?;
+        let units = interface.ticks_per_second.max(1);
+        let secs = (ticks / units) as i64 + interface.offset_secs;
+        let nanos = ((ticks % units) as u128 * 1_000_000_000 / units as u128) as u32;
+        let timestamp = Duration::new(secs.max(0) as u64, nanos);
+        let link_type = interface.link_type;
+        self.last_timestamp = timestamp;
+
+        Ok(PcapPacket {
+            timestamp,
+            link_type,
+            data: data[..cap_len].to_vec(),
+        })
+    }
+
+    fn interface(&self, id: usize) -> io::Result<&NgInterface> {
+        match &self.format {
+            Format::Ng { interfaces } => interfaces
+                .get(id)
+                .ok_or_else(|| invalid_data("pcapng paketi tanımsız arayüze ait")),
+            Format::Classic { .. } => Err(invalid_data("pcapng arayüzü bekleniyordu")),
+        }
+    }
+}
+
+/// pcapng yazıcısı. Zaman damgaları nanosaniye çözünürlükle, yerel bayt
+/// sırasında yazılır.
+pub struct PcapngWriter<W: Write> {
+    inner: W,
+    interfaces: u32,
+    written: u64,
+}
+
+impl<W: Write> PcapngWriter<W> {
+    /// Bölüm başlığını yazar.
+    pub fn new(inner: W) -> io::Result<Self> {
+        let mut writer = Self { inner, interfaces: 0, written: 0 };
+        let mut body = Vec::with_capacity(16);
+        body.extend_from_slice(&PCAPNG_BYTE_ORDER_MAGIC.to_ne_bytes());
+        // Sürüm 1.0, bölüm uzunluğu bilinmiyor (-1)
+        body.extend_from_slice(&1u16.to_ne_bytes());
+        body.extend_from_slice(&0u16.to_ne_bytes());
+        body.extend_from_slice(&(-1i64).to_ne_bytes());
+        writer.write_block(PCAPNG_SHB, &body)?;
+        Ok(writer)
+    }
+
+    /// Arayüz tanım bloğunu yazar ve paketlerde kullanılacak arayüz
+    /// sırasını döndürür. `snaplen` 0 ise sınır yoktur.
+    pub fn add_interface(&mut self, link_type: u32, name: &str, snaplen: u32) -> io::Result<u32> {
+        let mut body = Vec::new();
+        body.extend_from_slice(&(link_type as u16).to_ne_bytes());
+        body.extend_from_slice(&0u16.to_ne_bytes());
+        body.extend_from_slice(&snaplen.to_ne_bytes());
+        push_option(&mut body, PCAPNG_OPT_IF_NAME, name.as_bytes());
+        push_option(&mut body, PCAPNG_OPT_IF_TSRESOL, &[9]);
+        push_option(&mut body, PCAPNG_OPT_END, &[]);
+        self.write_block(PCAPNG_IDB, &body)?;
+
+        self.interfaces += 1;
+        Ok(self.interfaces - 1)
+    }
+
+    /// Gelişmiş paket bloğu yazar; `comment` pakete `opt_comment` olarak eklenir.
+    pub fn write_packet(
+        &mut self,
+        interface_id: u32,
+        timestamp: Duration,
+        data: &[u8],
+        comment: Option<&str>,
+    ) -> io::Result<()> {
+        let ticks = timestamp.as_nanos() as u64;
+        let mut body = Vec::with_capacity(20 + data.len() + 4);
+        body.extend_from_slice(&interface_id.to_ne_bytes());
+        body.extend_from_slice(&((ticks >> 32) as u32).to_ne_bytes());
+        body.extend_from_slice(&(ticks as u32).to_ne_bytes());
+        body.extend_from_slice(&(data.len() as u32).to_ne_bytes());
+        body.extend_from_slice(&(data.len() as u32).to_ne_bytes());
+        body.extend_from_slice(data);
+        pad(&mut body);
+        if let Some(comment) = comment {
+            push_option(&mut body, PCAPNG_OPT_COMMENT, comment.as_bytes());
+            push_option(&mut body, PCAPNG_OPT_END, &[]);
+        }
+        self.write_block(PCAPNG_EPB, &body)
+    }
+
+    /// Dosyaya yazılan toplam bayt
+    pub fn written(&self) -> u64 {
+        self.written
+    }
+
+    pub fn flush(&mut self) -> io::Result<()> {
+        self.inner.flush()
+    }
+
+    fn write_block(&mut self, block_type: u32, body: &[u8]) -> io::Result<()> {
+        let total_len = (12 + body.len()) as u32;
+        self.inner.write_all(&block_type.to_ne_bytes())?;
+        self.inner.write_all(&total_len.to_ne_bytes())?;
+        self.inner.write_all(body)?;
+        self.inner.write_all(&total_len.to_ne_bytes())?;
+        self.written += total_len as u64;
+        Ok(())
+    }
+}
+
+fn push_option(body: &mut Vec<u8>, code: u16, value: &[u8]) {
+    body.extend_from_slice(&code.to_ne_bytes());
+    body.extend_from_slice(&(value.len() as u16).to_ne_bytes());
+    body.extend_from_slice(value);
+    pad(body);
+}
+
+/// Bloklar ve seçenekler 4 bayta hizalı
+fn pad(body: &mut Vec<u8>) {
+    body.resize((body.len() + 3) & !3, 0);
+}
+
+/// Kayıt sınırında temiz dosya sonu için `false` döndürür.
+fn read_exact_or_eof<R: Read>(reader: &mut R, buf: &mut [u8]) -> io::Result<bool> {
+    let mut filled = 0;
+    while filled < buf.len() {
+        match reader.read(&mut buf[filled..]) {
+            Ok(0) if filled == 0 => return Ok(false),
+            Ok(0) => return Err(io::ErrorKind::UnexpectedEof.into()),
+            Ok(n) => filled += n,
+            Err(e) if e.kind() == io::ErrorKind::Interrupted => {}
+            Err(e) => return Err(e),
+        }
+    }
+    Ok(true)
+}
+
+fn read_u16(bytes: &[u8], big_endian: bool) -> u16 {
+    let bytes = [bytes[0], bytes[1]];
+    if big_endian { u16::from_be_bytes(bytes) } else { u16::from_le_bytes(bytes) }
+}
+
+fn read_u32(bytes: &[u8], big_endian: bool) -> u32 {
+    let bytes = [bytes[0], bytes[1], bytes[2], bytes[3]];
+    if big_endian { u32::from_be_bytes(bytes) } else { u32::from_le_bytes(bytes) }
+}
+
+fn read_u64(bytes: &[u8], big_endian: bool) -> u64 {
+    let mut buf = [0u8; 8];
+    buf.copy_from_slice(&bytes[..8]);
+    if big_endian { u64::from_be_bytes(buf) } else { u64::from_le_bytes(buf) }
+}
+
+fn invalid_data(msg: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, msg.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Seçilen bayt sırasında dosya oluşturur.
+    struct Bytes {
+        big_endian: bool,
+        buf: Vec<u8>,
+    }
+
+    impl Bytes {
+        fn new(big_endian: bool) -> Self {
+            Self { big_endian, buf: Vec::new() }
+        }
+
+        fn u16(&mut self, value: u16) -> &mut Self {
+            let bytes = if self.big_endian { value.to_be_bytes() } else { value.to_le_bytes() };
+            self.buf.extend_from_slice(&bytes);
+            self
+        }
+
+        fn u32(&mut self, value: u32) -> &mut Self {
+            let bytes = if self.big_endian { value.to_be_bytes() } else { value.to_le_bytes() };
+            self.buf.extend_from_slice(&bytes);
+            self
+        }
+
+        fn u64(&mut self, value: u64) -> &mut Self {
+            let bytes = if self.big_endian { value.to_be_bytes() } else { value.to_le_bytes() };
+            self.buf.extend_from_slice(&bytes);
+            self
+        }
+
+        fn raw(&mut self, bytes: &[u8]) -> &mut Self {
+            self.buf.extend_from_slice(bytes);
+            self
+        }
+
+        fn block(&mut self, block_type: u32, body: &[u8]) -> &mut Self {
+            let total_len = 12 + body.len() as u32;
+            self.u32(block_type).u32(total_len).raw(body).u32(total_len)
+        }
+
+        fn section(&mut self) -> &mut Self {
+            let mut body = Bytes::new(self.big_endian);
+            body.u32(PCAPNG_BYTE_ORDER_MAGIC).u16(1).u16(0).u64(u64::MAX);
+            self.block(PCAPNG_SHB, &body.buf)
+        }
+
+        fn interface(&mut self, link_type: u16, options: &[(u16, &[u8])]) -> &mut Self {
+            let mut body = Bytes::new(self.big_endian);
+            body.u16(link_type).u16(0).u32(0);
+            for (code, value) in options {
+                body.u16(*code).u16(value.len() as u16).raw(value);
+                pad(&mut body.buf);
+            }
+            self.block(PCAPNG_IDB, &body.buf)
+        }
+
+        fn enhanced(&mut self, interface: u32, ticks: u64, data: &[u8]) -> &mut Self {
+            let mut body = Bytes::new(self.big_endian);
+            body.u32(interface).u32((ticks >> 32) as u32).u32(ticks as u32);
+            body.u32(data.len() as u32).u32(data.len() as u32).raw(data);
+            pad(&mut body.buf);
+            self.block(PCAPNG_EPB, &body.buf)
+        }
+
+        fn reader(&self) -> io::Result<PcapReader<&[u8]>> {
+            PcapReader::new(&self.buf[..])
+        }
+    }
+
+    fn classic(big_endian: bool, nanos: bool, link_type: u32) -> Bytes {
+        let mut file = Bytes::new(big_endian);
+        file.u32(if nanos { 0xa1b2_3c4d } else { 0xa1b2_c3d4 });
+        file.u16(2).u16(4).u32(0).u32(0).u32(65535).u32(link_type);
+        file
+    }
+
+    fn read_all<R: Read>(reader: &mut PcapReader<R>) -> Vec<PcapPacket> {
+        let mut packets = Vec::new();
+        while let Some(packet) = reader.next_packet().unwrap() {
+            packets.push(packet);
+        }
+        packets
+    }
+
+    #[test]
+    fn reads_classic_pcap_in_both_byte_orders() {
+        for big_endian in [false, true] {
+            let mut file = classic(big_endian, false, 1);
+            file.u32(1_700_000_000).u32(250_000).u32(3).u32(60).raw(&[1, 2, 3]);
+            file.u32(1_700_000_001).u32(0).u32(2).u32(2).raw(&[4, 5]);
+
+            let packets = read_all(&mut file.reader().unwrap());
+            assert_eq!(packets.len(), 2);
+            assert_eq!(packets[0].timestamp, Duration::new(1_700_000_000, 250_000_000));
+            assert_eq!(packets[0].link_type, 1);
+            assert_eq!(packets[0].data, [1, 2, 3]);
+            assert_eq!(packets[1].timestamp, Duration::from_secs(1_700_000_001));
+            assert_eq!(packets[1].data, [4, 5]);
+        }
+    }
+
+    #[test]
+    fn reads_nanosecond_pcap_and_masks_link_type_flags() {
+        // Üst 16 bit FCS bilgisi taşıyabilir
+        let mut file = classic(false, true, 0x1000_0000 | 101);
+        file.u32(10).u32(123_456_789).u32(1).u32(1).raw(&[0x45]);
+
+        let packets = read_all(&mut file.reader().unwrap());
+        assert_eq!(packets[0].timestamp, Duration::new(10, 123_456_789));
+        assert_eq!(packets[0].link_type, 101);
+    }
+
+    #[test]
+    fn rejects_bad_magic_and_oversized_or_truncated_records() {
+        assert!(PcapReader::new(&b"not a capture file"[..]).is_err());
+
+        let mut file = classic(false, false, 1);
+        file.u32(0).u32(0).u32(MAX_PACKET_LEN as u32 + 1).u32(0);
+        assert!(file.reader().unwrap().next_packet().is_err());
+
+        let mut file = classic(false, false, 1);
+        file.u32(0).u32(0).u32(10).u32(10).raw(&[0; 4]);
+        assert!(file.reader().unwrap().next_packet().is_err());
+
+        // Kayıt başlığının ortasında biten dosya
+        let mut file = classic(false, false, 1);
+        file.u32(0).u32(0);
+        assert!(file.reader().unwrap().next_packet().is_err());
+    }
+
+    #[test]
+    fn reads_pcapng_with_interface_resolution_and_offset() {
+        for big_endian in [false, true] {
+            let mut file = Bytes::new(big_endian);
+            let offset = if big_endian { 100u64.to_be_bytes() } else { 100u64.to_le_bytes() };
+            file.section();
+            file.interface(1, &[(PCAPNG_OPT_IF_NAME, b"eth0"), (PCAPNG_OPT_IF_TSRESOL, &[3])]);
+            file.interface(101, &[(PCAPNG_OPT_IF_TSRESOL, &[0x80 | 10]), (PCAPNG_OPT_IF_TSOFFSET, &offset)]);
+            // Bilinmeyen bloklar atlanır
+            file.block(0x0000_0bad, &[0; 8]);
+            file.enhanced(0, 1_500, &[1, 2, 3]);
+            file.enhanced(1, 3 * 1024 + 512, &[4]);
+            // Basit paket bloğu zaman damgasını bir öncekinden alır
+            let mut simple = Bytes::new(big_endian);
+            simple.u32(2).raw(&[5, 6, 0, 0]);
+            file.block(PCAPNG_SPB, &simple.buf);
+
+            let packets = read_all(&mut file.reader().unwrap());
+            assert_eq!(packets.len(), 3);
+            assert_eq!(packets[0].timestamp, Duration::from_millis(1_500));
+            assert_eq!(packets[0].link_type, 1);
+            assert_eq!(packets[0].data, [1, 2, 3]);
+            assert_eq!(packets[1].timestamp, Duration::from_millis(103_500));
+            assert_eq!(packets[1].link_type, 101);
+            assert_eq!(packets[2].timestamp, packets[1].timestamp);
+            assert_eq!(packets[2].link_type, 1);
+            assert_eq!(packets[2].data, [5, 6]);
+        }
+    }
+
+    #[test]
+    fn new_pcapng_section_resets_interfaces() {
+        let mut file = Bytes::new(false);
+        file.section().interface(1, &[]).enhanced(0, 1, &[1]);
+        file.section().interface(113, &[]).enhanced(0, 2_000_000, &[2]);
+        let mut reader = file.reader().unwrap();
+        assert_eq!(read_all(&mut reader).iter().map(|packet| packet.link_type).collect::<Vec<_>>(), [1, 113]);
+
+        // Yeni bölümde tanımlanmamış arayüze ait paket
+        let mut file = Bytes::new(false);
+        file.section().interface(1, &[]).interface(1, &[]);
+        file.section().interface(1, &[]).enhanced(1, 0, &[1]);
+        assert!(file.reader().unwrap().next_packet().is_err());
+    }
+
+    #[test]
+    fn rejects_malformed_pcapng_blocks() {
+        let mut file = Bytes::new(false);
+        file.section().interface(1, &[]);
+        file.u32(PCAPNG_EPB).u32(13);
+        assert!(file.reader().unwrap().next_packet().is_err());
+
+        // Yakalanan uzunluk blok gövdesini aşıyor
+        let mut file = Bytes::new(false);
+        file.section().interface(1, &[]);
+        let mut body = Bytes::new(false);
+        body.u32(0).u32(0).u32(0).u32(100).u32(100).raw(&[0; 4]);
+        file.block(PCAPNG_EPB, &body.buf);
+        assert!(file.reader().unwrap().next_packet().is_err());
+
+        let mut file = Bytes::new(false);
+        file.u32(PCAPNG_SHB).u32(28).u32(0xdead_beef).raw(&[0; 16]);
+        assert!(file.reader().is_err());
+    }
+
+    #[test]
+    fn writer_output_reads_back() {
+        let mut buf = Vec::new();
+        let mut writer = PcapngWriter::new(&mut buf).unwrap();
+        let eth = writer.add_interface(1, "eth0", 0).unwrap();
+        let tun = writer.add_interface(101, "tun0", 0).unwrap();
+        writer.write_packet(eth, Duration::new(1_700_000_000, 123_456_789), &[1, 2, 3, 4, 5], Some("US -> DE")).unwrap();
+        writer.write_packet(tun, Duration::new(1_700_000_001, 1), &[0x45], None).unwrap();
+        let written = writer.written();
+        assert_eq!(written, buf.len() as u64);
+
+        let packets = read_all(&mut PcapReader::new(&buf[..]).unwrap());
+        assert_eq!(packets.len(), 2);
+        assert_eq!(packets[0].timestamp, Duration::new(1_700_000_000, 123_456_789));
+        assert_eq!(packets[0].link_type, 1);
+        assert_eq!(packets[0].data, [1, 2, 3, 4, 5]);
+        assert_eq!(packets[1].timestamp, Duration::new(1_700_000_001, 1));
+        assert_eq!(packets[1].link_type, 101);
+        assert_eq!(packets[1].data, [0x45]);
+    }
+}
@@ -0,0 +1,227 @@
+use std::io;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::mpsc::{self, Receiver, SyncSender, TrySendError};
+use std::sync::{Arc, Mutex};
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, Instant};
+
+use crate::capture::PacketProcessor;
+use crate::source::{self, KernelStats, OwnedFrame, PacketSource};
+use crate::stats::Stats;
+
+// Yakalama ile çözümleme arasındaki kuyruk; çözümleme anlık yavaşladığında
+// çerçeveler çekirdekte düşmek yerine burada birikir
+const FRAME_QUEUE_LEN: usize = 8192;
+const REPORT_INTERVAL: Duration = Duration::from_secs(10);
+// Çekirdek sayaçlarının okunma aralığı; rapordan önce güncel olsun diye kısa
+const STATS_INTERVAL: Duration = Duration::from_secs(1);
+// Paket gelmese de akış zaman aşımlarının işlemesi için
+const TICK_INTERVAL: Duration = Duration::from_secs(1);
+// Arka arkaya bu kadar okuma hatası veren kaynak kalıcı olarak bozulmuş
+// sayılır (arayüz kaldırıldı, kapandı ...)
+const MAX_CONSECUTIVE_ERRORS: u32 = 10;
+
+#[derive(Default)]
+struct Counters {
+    captured: AtomicU64,
+    processed: AtomicU64,
+    queue_full: AtomicU64,
+    // Kaynak destekliyorsa çekirdeğin son bildirdiği sayaçlar
+    kernel: Mutex<Option<KernelStats>>,
+}
+
+/// Yakalama ve çözümleme thread'lerini yönetir. Kaynak tükendiğinde ya da
+/// durdurma isteğinde iki thread de kendiliğinden sonlanır. Kaynak kalıcı
+/// hata verirse hata, thread bitmeden `failures` kanalına da bildirilir.
+pub struct Pipeline {
+    capture: JoinHandle<Result<u64, String>>,
+    process: JoinHandle<()>,
+}
+
+impl Pipeline {
+    pub fn spawn(
+        mut source: Box<dyn PacketSource + Send>,
+        mut processor: PacketProcessor,
+        stop: Arc<AtomicBool>,
+        stats: Arc<Stats>,
+        failures: tokio::sync::mpsc::UnboundedSender<String>,
+    ) -> io::Result<Self> {
+        let (frame_tx, frame_rx) = mpsc::sync_channel(FRAME_QUEUE_LEN);
+        let counters = Arc::new(Counters::default());
+
+        let name = source.name().to_string();
+        let capture_counters = counters.clone();
+        let capture = thread::Builder::new()
+            .name(format!("yakalama-{}", name))
+            .spawn(move || {
+                let result = run_source(source.as_mut(), &stop, frame_tx, &capture_counters, &stats);
+                if let Err(reason) = &result {
+                    // Diğer kaynaklar sürerken de hatanın fark edilmesi için
+                    let _ = failures.send(reason.clone());
+                }
+                result
+            })?;
+
+        let process = thread::Builder::new()
+            .name("cozumleme".to_string())
+            .spawn(move || run_processor(&mut processor, &name, frame_rx, &counters))?;
+
+        Ok(Self { capture, process })
+    }
+
+    /// İki thread'in bitmesini bekler ve yakalanan çerçeve sayısını döndürür.
+    pub fn join(self) -> Result<u64, String> {
+        let captured = self.capture.join().map_err(|_| "Yakalama thread'i çöktü".to_string())?;
+        self.process.join().map_err(|_| "Çözümleme thread'i çöktü".to_string())?;
+        captured
+    }
+}
+
+fn run_source(
+    source: &mut dyn PacketSource,
+    stop: &AtomicBool,
+    frames: SyncSender<OwnedFrame>,
+    counters: &Counters,
+    stats: &Stats,
+) -> Result<u64, String> {
+    let mut count: u64 = 0;
+    // Kayda yalnızca son okumadan bu yana artan çekirdek sayaçları eklenir
+    let mut kernel = KernelStats::default();
+    let mut errors: u32 = 0;
+    let mut link_type = source.link_type();
+    let mut last_stats = Instant::now();
+    println!("Kaynak: {} (bağlantı katmanı: {:?})", source.name(), link_type);
+
+    while !stop.load(Ordering::Relaxed) {
+        if last_stats.elapsed() >= STATS_INTERVAL {
+            let current = source.kernel_stats();
+            if let Some(current) = current {
+                stats.add_kernel(current, kernel);
+                kernel = current;
+            }
+            *counters.kernel.lock().unwrap() = current;
+            last_stats = Instant::now();
+        }
+
+        let frame = match source.next_frame() {
+            Ok(Some(frame)) => OwnedFrame::from_frame(&frame),
+            Ok(None) => break,
+            Err(e) if source::is_timeout(&e) => continue,
+            Err(e) => {
+                eprintln!("Paket yakalama hatası ({}): {}", source.name(), e);
+                stats.capture.read_errors.inc();
+                errors += 1;
+                if errors >= MAX_CONSECUTIVE_ERRORS {
+                    return Err(format!("{} okunamıyor: {}", source.name(), e));
+                }
+                continue;
+            }
+        };
+        errors = 0;
+
+        // pcapng dosyaları farklı türde arayüzler içerebilir
+        if frame.link_type != link_type {
+            link_type = frame.link_type;
+            println!("Bağlantı katmanı türü değişti: {:?}", link_type);
+        }
+
+        count += 1;
+        counters.captured.fetch_add(1, Ordering::Relaxed);
+        stats.capture.frames.inc();
+
+        let sent = match frames.try_send(frame) {
+            Ok(()) => Ok(()),
+            Err(TrySendError::Full(frame)) => {
+                counters.queue_full.fetch_add(1, Ordering::Relaxed);
+                stats.capture.queue_full.inc();
+                frames.send(frame).map_err(|_| ())
+            }
+            Err(TrySendError::Disconnected(_)) => Err(()),
+        };
+        if sent.is_err() {
+            break;
+        }
+    }
+
+    if let Some(current) = source.kernel_stats() {
+        stats.add_kernel(current, kernel);
+        println!(
+            "Çekirdek sayaçları ({}): alınan {}, düşen {}, kuyruk dondurma {}",
+            source.name(), current.received, current.dropped, current.freeze_queue
+        );
+    }
+
+    Ok(count)
+}
+
+fn run_processor(processor: &mut PacketProcessor, name: &str, frames: Receiver<OwnedFrame>, counters: &Counters) {
+    let mut last_report = Instant::now();
+    let mut last_processed: u64 = 0;
+
+    loop {
+        match frames.recv_timeout(TICK_INTERVAL) {
+            Ok(frame) => {
+                processor.process(&frame.as_frame());
+                counters.processed.fetch_add(1, Ordering::Relaxed);
+            }
+            Err(mpsc::RecvTimeoutError::Timeout) => processor.tick(),
+            Err(mpsc::RecvTimeoutError::Disconnected) => break,
+        }
+
+        // WebSocket tarafı kapandıysa çalışmaya devam etmenin anlamı yok
+        if processor.is_closed() {
+            break;
+        }
+
+        let elapsed = last_report.elapsed();
+        if elapsed >= REPORT_INTERVAL {
+            let processed = counters.processed.load(Ordering::Relaxed);
+            println!(
+                "Verim: {:.0} paket/sn (yakalanan: {}, işlenen: {}, kuyruk dolu: {})",
+                (processed - last_processed) as f64 / elapsed.as_secs_f64(),
+                counters.captured.load(Ordering::Relaxed),
+                processed,
+                counters.queue_full.load(Ordering::Relaxed),
+            );
+            let kernel = *counters.kernel.lock().unwrap();
+            if let Some(stats) = kernel {
+                println!("Çekirdekte düşen: {} / {} ({})", stats.dropped, stats.received, name);
+                processor.report_kernel_stats(name, stats);
+            }
+            last_processed = processed;
+            last_report = Instant::now();
+        }
+    }
+
+    // Hangi yoldan çıkılırsa çıkılsın biten akışlar ve trafik sayaçları
+    // istatistiklere eklenir
+    processor.finish();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::source::LinkType;
+    use crate::testutil::*;
+
+    #[test]
+    fn closed_event_queue_still_merges_stats() {
+        let (event_tx, event_rx) = tokio::sync::mpsc::channel(16);
+        let stats = Arc::new(Stats::default());
+        let mut processor = PacketProcessor::new(event_tx, geoip_reader(), &config(), stats.clone());
+        drop(event_rx);
+
+        let packet = ipv4("10.0.0.5".parse().unwrap(), "151.101.1.69".parse().unwrap(), 17, &udp(40000, 4433, &[0; 8]));
+        let data = ethernet(CLIENT_MAC, SERVER_MAC, &packet);
+        let (frame_tx, frame_rx) = mpsc::sync_channel(1);
+        frame_tx.send(OwnedFrame { timestamp: ms(0), link_type: LinkType::Ethernet, data }).unwrap();
+        // Kaynak açık kalır; döngü kapanan olay kuyruğu yüzünden biter
+        run_processor(&mut processor, "test", frame_rx, &Counters::default());
+        drop(frame_tx);
+
+        assert_eq!(stats.flows.started.get(), 1);
+        assert_eq!(stats.flows.ended.get(), 1);
+        let traffic = stats.traffic();
+        assert_eq!(traffic.values().map(|traffic| traffic.packets).sum::<u64>(), 1);
+    }
+}
@@ -0,0 +1,358 @@
+use std::collections::{BTreeMap, HashMap};
+use std::net::IpAddr;
+use std::sync::Arc;
+use std::time::Duration;
+
+use crate::decode::{Fragment, IpPacket};
+use crate::stats::Stats;
+
+// IP datagramının alabileceği en büyük yük
+const MAX_DATAGRAM_LEN: usize = 65_535;
+
+#[derive(Debug, Clone, Copy)]
+pub struct ReassemblyConfig {
+    /// Bu süre içinde tamamlanmayan datagram atılır
+    pub timeout: Duration,
+    /// Aynı anda birleştirilen en fazla datagram
+    pub max_datagrams: usize,
+    /// Bekleyen parçaların toplam bellek sınırı (bayt)
+    pub max_bytes: usize,
+}
+
+impl Default for ReassemblyConfig {
+    fn default() -> Self {
+        Self {
+            timeout: Duration::from_secs(30),
+            max_datagrams: 1024,
+            max_bytes: 4 * 1024 * 1024,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+struct DatagramKey {
+    src: IpAddr,
+    dst: IpAddr,
+    protocol: u8,
+    id: u32,
+}
+
+struct PendingDatagram {
+    first_seen: Duration,
+    // Konuma göre sıralı parça verileri
+    fragments: BTreeMap<usize, Vec<u8>>,
+    // Son parça görüldüğünde belli olur
+    total_len: Option<usize>,
+    bytes: usize,
+}
+
+impl PendingDatagram {
+    /// Parçalar 0'dan toplam uzunluğa boşluksuz uzanıyorsa birleştirir.
+    fn assemble(&self) -> Option<Vec<u8>> {
+        let total_len = self.total_len?;
+        let mut expected = 0;
+        for (offset, data) in &self.fragments {
+            if *offset != expected {
+                return None;
+            }
+            expected += data.len();
+        }
+        if expected != total_len {
+            return None;
+        }
+
+        let mut datagram = Vec::with_capacity(total_len);
+        for data in self.fragments.values() {
+            datagram.extend_from_slice(data);
+        }
+        Some(datagram)
+    }
+}
+
+enum Insert {
+    Stored,
+    Duplicate,
+    Overlap,
+    Malformed,
+}
+
+/// IPv4 ve IPv6 parçalarını datagramlara birleştirir. Zaman olarak akış
+/// tablosu gibi paketlerin yakalama zamanı kullanılır. Sonuçlar `stats`
+/// içindeki çözümleme sayaçlarına işlenir.
+pub struct Reassembler {
+    config: ReassemblyConfig,
+    pending: HashMap<DatagramKey, PendingDatagram>,
+    bytes: usize,
+    stats: Arc<Stats>,
+}
+
+impl Reassembler {
+    pub fn new(config: ReassemblyConfig, stats: Arc<Stats>) -> Self {
+        Self {
+            config,
+            pending: HashMap::new(),
+            bytes: 0,
+            stats,
+        }
+    }
+
+    /// Parçayı saklar; datagram tamamlandıysa birleşmiş yükü döndürür.
+    pub fn insert(&mut self, now: Duration, packet: &IpPacket, fragment: Fragment) -> Option<Vec<u8>> {
+        self.stats.decode.fragments.inc();
+
+        let data = packet.payload;
+        let end = fragment.offset + data.len();
+        // Kesik yakalanmış, 8 bayta hizalı olmayan ya da datagram sınırını
+        // aşan parçalar birleştirilemez
+        let malformed = data.len() < packet.payload_len
+            || data.is_empty()
+            || (fragment.more && !data.len().is_multiple_of(8))
+            || end > MAX_DATAGRAM_LEN;
+        if malformed {
+            self.stats.decode.fragments_malformed.inc();
+            return None;
+        }
+
+        let key = DatagramKey {
+            src: packet.src,
+            dst: packet.dst,
+            protocol: packet.protocol.0,
+            id: fragment.id,
+        };
+
+        if !self.pending.contains_key(&key) {
+            self.make_room(data.len());
+            self.pending.insert(key, PendingDatagram {
+                first_seen: now,
+                fragments: BTreeMap::new(),
+                total_len: None,
+                bytes: 0,
+            });
+        } else if self.bytes + data.len() > self.config.max_bytes {
+            self.make_room(data.len());
+        }
+
+        let datagram = self.pending.get_mut(&key)?;
+        match store(datagram, fragment, data) {
+            Insert::Stored => self.bytes += data.len(),
+            Insert::Duplicate => return None,
+            Insert::Overlap => {
+                // Çakışan parçalar güvenlik cihazlarını atlatmak için
+                // kullanılabilir; datagram tümüyle atılır (RFC 5722)
+                self.stats.decode.fragments_overlapping.inc();
+                self.remove(&key);
+                return None;
+            }
+            Insert::Malformed => {
+                self.stats.decode.fragments_malformed.inc();
+                self.remove(&key);
+                return None;
+            }
+        }
+
+        let assembled = datagram.assemble()?;
+        self.remove(&key);
+        self.stats.decode.reassembled.inc();
+        Some(assembled)
+    }
+
+    /// Zaman aşımına uğrayan yarım datagramları atar.
+    pub fn expire(&mut self, now: Duration) {
+        let timeout = self.config.timeout;
+        let expired: Vec<DatagramKey> = self.pending
+            .iter()
+            .filter(|(_, datagram)| now.saturating_sub(datagram.first_seen) >= timeout)
+            .map(|(key, _)| *key)
+            .collect();
+
+        for key in expired {
+            self.remove(&key);
+            self.stats.decode.fragments_timed_out.inc();
+        }
+    }
+
+    /// Yeni veri için gerekirse en eski datagramları atar.
+    fn make_room(&mut self, len: usize) {
+        while !self.pending.is_empty()
+            && (self.pending.len() >= self.config.max_datagrams.max(1) || self.bytes + len > self.config.max_bytes)
+        {
+            let oldest = self.pending
+                .iter()
+                .min_by_key(|(_, datagram)| datagram.first_seen)
+                .map(|(key, _)| *key);
+            if let Some(key) = oldest {
+                self.remove(&key);
+                self.stats.decode.fragments_evicted.inc();
+            }
+        }
+    }
+
+    fn remove(&mut self, key: &DatagramKey) {
+        if let Some(datagram) = self.pending.remove(key) {
+            self.bytes -= datagram.bytes;
+        }
+    }
+}
+
+fn store(datagram: &mut PendingDatagram, fragment: Fragment, data: &[u8]) -> Insert {
+    let start = fragment.offset;
+    let end = start + data.len();
+
+    if !fragment.more {
+        match datagram.total_len {
+            Some(total_len) if total_len != end => return Insert::Malformed,
+            _ => datagram.total_len = Some(end),
+        }
+    }
+    if datagram.total_len.is_some_and(|total_len| end > total_len)
+        || datagram.fragments.last_key_value().is_some_and(|(offset, last)| {
+            !fragment.more && offset + last.len() > end
+        })
+    {
+        return Insert::Malformed;
+    }
+
+    // Aynı parçanın tekrarı sorun değil
+    if datagram.fragments.get(&start).is_some_and(|existing| existing.as_slice() == data) {
+        return Insert::Duplicate;
+    }
+
+    let before = datagram.fragments.range(..end).next_back();
+    if before.is_some_and(|(offset, existing)| offset + existing.len() > start) {
+        return Insert::Overlap;
+    }
+
+    datagram.fragments.insert(start, data.to_vec());
+    datagram.bytes += data.len();
+    Insert::Stored
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testutil::ms;
+    use pnet::packet::ip::IpNextHeaderProtocols;
+
+    fn reassembler(config: ReassemblyConfig) -> (Reassembler, Arc<Stats>) {
+        let stats = Arc::new(Stats::default());
+        (Reassembler::new(config, stats.clone()), stats)
+    }
+
+    fn insert(reassembler: &mut Reassembler, at: u64, id: u32, offset: usize, more: bool, data: &[u8]) -> Option<Vec<u8>> {
+        let packet = IpPacket {
+            src: "198.51.100.1".parse().unwrap(),
+            dst: "151.101.1.69".parse().unwrap(),
+            protocol: IpNextHeaderProtocols::Udp,
+            len: 20 + data.len(),
+            payload: data,
+            payload_len: data.len(),
+            fragment: None,
+        };
+        reassembler.insert(ms(at), &packet, Fragment { id, offset, more })
+    }
+
+    fn bytes(range: std::ops::Range<usize>) -> Vec<u8> {
+        range.map(|byte| byte as u8).collect()
+    }
+
+    #[test]
+    fn reassembles_fragments_in_any_order() {
+        let (mut reassembler, stats) = reassembler(ReassemblyConfig::default());
+        assert_eq!(insert(&mut reassembler, 0, 1, 16, false, &bytes(16..20)), None);
+        assert_eq!(insert(&mut reassembler, 1, 1, 0, true, &bytes(0..8)), None);
+        // Aynı parçanın tekrarı datagramı bozmaz
+        assert_eq!(insert(&mut reassembler, 2, 1, 0, true, &bytes(0..8)), None);
+        assert_eq!(insert(&mut reassembler, 3, 1, 8, true, &bytes(8..16)), Some(bytes(0..20)));
+
+        assert_eq!(stats.decode.fragments.get(), 4);
+        assert_eq!(stats.decode.reassembled.get(), 1);
+        assert_eq!(reassembler.bytes, 0);
+        assert!(reassembler.pending.is_empty());
+    }
+
+    #[test]
+    fn overlapping_fragment_drops_the_datagram() {
+        let (mut reassembler, stats) = reassembler(ReassemblyConfig::default());
+        insert(&mut reassembler, 0, 7, 0, true, &[0; 16]);
+        assert_eq!(insert(&mut reassembler, 1, 7, 8, true, &[1; 16]), None);
+        assert_eq!(stats.decode.fragments_overlapping.get(), 1);
+        assert!(reassembler.pending.is_empty());
+
+        // Atılan datagramın geri kalanı yeniden başlar, tamamlanamaz
+        assert_eq!(insert(&mut reassembler, 2, 7, 16, false, &[2; 4]), None);
+        assert_eq!(stats.decode.reassembled.get(), 0);
+    }
+
+    #[test]
+    fn malformed_fragments_are_counted() {
+        let (mut reassembler, stats) = reassembler(ReassemblyConfig::default());
+        // 8 bayta hizasız ara parça
+        insert(&mut reassembler, 0, 1, 0, true, &[0; 10]);
+        // Datagram sınırını aşan parça
+        insert(&mut reassembler, 1, 2, MAX_DATAGRAM_LEN - 4, false, &[0; 8]);
+        // Boş parça
+        insert(&mut reassembler, 2, 3, 8, false, &[]);
+        // İki farklı son parça
+        insert(&mut reassembler, 3, 4, 8, false, &[0; 8]);
+        insert(&mut reassembler, 4, 4, 16, false, &[0; 8]);
+        // Son parçanın ötesine uzanan parça
+        insert(&mut reassembler, 5, 5, 16, false, &[0; 8]);
+        insert(&mut reassembler, 6, 5, 24, true, &[0; 8]);
+
+        assert_eq!(stats.decode.fragments_malformed.get(), 5);
+        assert_eq!(stats.decode.fragments.get(), 7);
+        assert!(reassembler.pending.is_empty());
+        assert_eq!(reassembler.bytes, 0);
+    }
+
+    #[test]
+    fn truncated_capture_is_malformed() {
+        let (mut reassembler, stats) = reassembler(ReassemblyConfig::default());
+        let data = [0; 8];
+        let packet = IpPacket {
+            src: "198.51.100.1".parse().unwrap(),
+            dst: "151.101.1.69".parse().unwrap(),
+            protocol: IpNextHeaderProtocols::Udp,
+            len: 1500,
+            payload: &data,
+            payload_len: 1480,
+            fragment: None,
+        };
+        assert_eq!(reassembler.insert(ms(0), &packet, Fragment { id: 1, offset: 0, more: true }), None);
+        assert_eq!(stats.decode.fragments_malformed.get(), 1);
+    }
+
+    #[test]
+    fn incomplete_datagrams_time_out() {
+        let config = ReassemblyConfig { timeout: Duration::from_secs(30), ..ReassemblyConfig::default() };
+        let (mut reassembler, stats) = reassembler(config);
+        insert(&mut reassembler, 0, 1, 0, true, &[0; 8]);
+        insert(&mut reassembler, 10_000, 2, 0, true, &[0; 8]);
+
+        reassembler.expire(ms(29_999));
+        assert_eq!(stats.decode.fragments_timed_out.get(), 0);
+        reassembler.expire(ms(30_000));
+        assert_eq!(stats.decode.fragments_timed_out.get(), 1);
+        assert_eq!(reassembler.pending.len(), 1);
+        assert_eq!(reassembler.bytes, 8);
+
+        // Zamanı dolan datagramın geç gelen parçası tek başına kalır
+        assert_eq!(insert(&mut reassembler, 30_001, 1, 8, false, &[0; 8]), None);
+    }
+
+    #[test]
+    fn limits_evict_the_oldest_datagram() {
+        let config = ReassemblyConfig { max_datagrams: 2, max_bytes: 64, ..ReassemblyConfig::default() };
+        let (mut reassembler, stats) = reassembler(config);
+        insert(&mut reassembler, 0, 1, 0, true, &[0; 8]);
+        insert(&mut reassembler, 1, 2, 0, true, &[0; 8]);
+        insert(&mut reassembler, 2, 3, 0, true, &[0; 8]);
+        assert_eq!(stats.decode.fragments_evicted.get(), 1);
+        assert!(!reassembler.pending.keys().any(|key| key.id == 1));
+
+        // Bellek sınırı: 3 numaralı datagrama eklenen büyük parça yer açar
+        insert(&mut reassembler, 3, 3, 8, true, &[0; 56]);
+        assert_eq!(stats.decode.fragments_evicted.get(), 2);
+        assert!(reassembler.bytes <= 64);
+    }
+}
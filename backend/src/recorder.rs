@@ -0,0 +1,442 @@
+use std::fs::{self, File};
+use std::io::{self, BufWriter};
+use std::path::PathBuf;
+use std::sync::mpsc::{self, Receiver, SyncSender, TrySendError};
+use std::sync::Arc;
+use std::thread;
+use std::time::{Duration, Instant};
+
+use crate::pcap::PcapngWriter;
+use crate::source::Frame;
+use crate::stats::Stats;
+
+// Çözümleme thread'leri diske yazmayı beklemez; kuyruk dolarsa paket
+// kayda alınmaz
+const RECORD_QUEUE_LEN: usize = 8192;
+// Trafik az olsa da dosya bu aralıkla diske aktarılır
+const FLUSH_INTERVAL: Duration = Duration::from_secs(1);
+// Yazma hatasından sonra bu süre boyunca yeni dosya denenmez
+const RETRY_INTERVAL: Duration = Duration::from_secs(5);
+const CLOSE_TIMEOUT: Duration = Duration::from_secs(2);
+const FILE_PREFIX: &str = "capture-";
+const FILE_SUFFIX: &str = ".pcapng";
+
+/// Kayıt dosyalarının nereye ve ne zaman döndürülerek yazılacağı
+#[derive(Debug, Clone)]
+pub struct RecorderConfig {
+    pub dir: PathBuf,
+    /// Dosya bu boyuta ulaşınca yenisine geçilir; 0 ise sınır yok
+    pub max_file_size: u64,
+    /// Dosya bu süre sonunda yenisine geçilir
+    pub rotate_interval: Option<Duration>,
+    /// Dizinde tutulacak en fazla dosya; eskiler silinir. 0 ise sınır yok
+    pub max_files: usize,
+}
+
+impl Default for RecorderConfig {
+    fn default() -> Self {
+        Self {
+            dir: PathBuf::from("recordings"),
+            max_file_size: 100 * 1024 * 1024,
+            rotate_interval: Some(Duration::from_secs(3600)),
+            max_files: 24,
+        }
+    }
+}
+
+struct Record {
+    interface: String,
+    link_type: u32,
+    timestamp: Duration,
+    data: Vec<u8>,
+    comment: Option<String>,
+}
+
+enum Command {
+    Packet(Record),
+    /// Dosyayı diske aktarıp kaydı bitirir; bitince onay gönderilir
+    Close(SyncSender<()>),
+}
+
+/// Canlı yakalanan çerçeveleri ayrı bir thread'de döndürülen pcapng
+/// dosyalarına yazar. Kopyaları tüm çözümleme thread'lerince paylaşılır.
+#[derive(Clone)]
+pub struct Recorder {
+    tx: SyncSender<Command>,
+    stats: Arc<Stats>,
+}
+
+impl Recorder {
+    pub fn start(config: RecorderConfig, stats: Arc<Stats>) -> io::Result<Self> {
+        fs::create_dir_all(&config.dir)?;
+        let (tx, rx) = mpsc::sync_channel(RECORD_QUEUE_LEN);
+        let writer = RotatingWriter {
+            config,
+            stats: stats.clone(),
+            file: None,
+            opened: Instant::now(),
+            failed_at: None,
+            interfaces: Vec::new(),
+            sequence: 0,
+        };
+        thread::Builder::new()
+            .name("kayit".to_string())
+            .spawn(move || writer.run(rx))?;
+        Ok(Self { tx, stats })
+    }
+
+    /// Çerçeveyi geldiği arayüz ve varsa paket yorumuyla kayda ekler.
+    pub fn record(&self, interface: &str, frame: &Frame, comment: Option<String>) {
+        let record = Record {
+            interface: interface.to_string(),
+            link_type: frame.link_type.to_pcap(),
+            timestamp: frame.timestamp,
+            data: frame.data.to_vec(),
+            comment,
+        };
+        match self.tx.try_send(Command::Packet(record)) {
+            Ok(()) | Err(TrySendError::Disconnected(_)) => {}
+            Err(TrySendError::Full(_)) => self.stats.capture.record_dropped.inc(),
+        }
+    }
+
+    /// Kuyruktaki paketler yazılıp dosya kapanana kadar (en fazla
+    /// `CLOSE_TIMEOUT`) bekler.
+    pub fn close(&self) {
+        let (ack_tx, ack_rx) = mpsc::sync_channel(1);
+        if self.tx.send(Command::Close(ack_tx)).is_ok() && ack_rx.recv_timeout(CLOSE_TIMEOUT).is_err() {
+            eprintln!("Kayıt dosyası zamanında kapatılamadı");
+        }
+    }
+}
+
+struct RotatingWriter {
+    config: RecorderConfig,
+    stats: Arc<Stats>,
+    file: Option<PcapngWriter<BufWriter<File>>>,
+    opened: Instant,
+    failed_at: Option<Instant>,
+    // Görülen arayüzler (ad, bağlantı türü); sırası pcapng arayüz sırasıdır
+    // ve her yeni dosyanın başına yeniden yazılır
+    interfaces: Vec<(String, u32)>,
+    sequence: u32,
+}
+
+impl RotatingWriter {
+    fn run(mut self, commands: Receiver<Command>) {
+        loop {
+            match commands.recv_timeout(FLUSH_INTERVAL) {
+                Ok(Command::Packet(record)) => self.write(&record),
+                Ok(Command::Close(ack)) => {
+                    self.flush();
+                    let _ = ack.send(());
+                    return;
+                }
+                Err(mpsc::RecvTimeoutError::Timeout) => self.flush(),
+                Err(mpsc::RecvTimeoutError::Disconnected) => {
+                    self.flush();
+                    return;
+                }
+            }
+        }
+    }
+
+    fn write(&mut self, record: &Record) {
+        if self.failed_at.is_some_and(|failed_at| failed_at.elapsed() < RETRY_INTERVAL) {
+            self.stats.capture.record_dropped.inc();
+            return;
+        }
+
+        match self.try_write(record) {
+            Ok(()) => {
+                self.failed_at = None;
+                self.stats.capture.recorded.inc();
+            }
+            Err(e) => {
+                eprintln!("Kayıt dosyasına yazılamadı: {}", e);
+                self.stats.capture.record_dropped.inc();
+                self.failed_at = Some(Instant::now());
+                self.file = None;
+            }
+        }
+    }
+
+    fn try_write(&mut self, record: &Record) -> io::Result<()> {
+        if self.file.is_none() || self.should_rotate() {
+            self.rotate()?;
+        }
+
+        let key = (record.interface.clone(), record.link_type);
+        let interface_id = match self.interfaces.iter().position(|interface| *interface == key) {
+            Some(index) => index as u32,
+            None => {
+                self.interfaces.push(key);
+                let file = self.file.as_mut().expect("dosya yukarıda açıldı");
+                file.add_interface(record.link_type, &record.interface, 0)?
+            }
+        };
+
+        let file = self.file.as_mut().expect("dosya yukarıda açıldı");
+        file.write_packet(interface_id, record.timestamp, &record.data, record.comment.as_deref())
+    }
+
+    fn should_rotate(&self) -> bool {
+        let size = self.file.as_ref().map_or(0, |file| file.written());
+        (self.config.max_file_size > 0 && size >= self.config.max_file_size)
+            || self.config.rotate_interval.is_some_and(|interval| self.opened.elapsed() >= interval)
+    }
+
+    /// Yeni dosya açar, bilinen arayüzleri başına yazar ve eski dosyaları siler.
+    fn rotate(&mut self) -> io::Result<()> {
+        self.flush();
+        self.file = None;
+
+        self.sequence += 1;
+        let name = format!(
+            "{}{}-{:04}{}",
+            FILE_PREFIX,
+            chrono::Utc::now().format("%Y%m%d-%H%M%S"),
+            self.sequence,
+            FILE_SUFFIX
+        );
+        let path = self.config.dir.join(name);
+        let mut file = PcapngWriter::new(BufWriter::new(File::create(&path)?))?;
+        for (name, link_type) in &self.interfaces {
+            file.add_interface(*link_type, name, 0)?;
+        }
+        println!("Kayıt dosyası: {}", path.display());
+
+        self.file = Some(file);
+        self.opened = Instant::now();
+        self.remove_old_files();
+        Ok(())
+    }
+
+    fn remove_old_files(&self) {
+        if self.config.max_files == 0 {
+            return;
+        }
+        let entries = match fs::read_dir(&self.config.dir) {
+            Ok(entries) => entries,
+            Err(e) => {
+                eprintln!("Kayıt dizini okunamadı: {}", e);
+                return;
+            }
+        };
+
+        // Adlar zaman damgasıyla başladığından ada göre sıralama yaşa göredir
+        let mut files: Vec<PathBuf> = entries
+            .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+            .filter(|path| {
+                path.file_name()
+                    .and_then(|name| name.to_str())
+                    .is_some_and(|name| name.starts_with(FILE_PREFIX) && name.ends_with(FILE_SUFFIX))
+            })
+            .collect();
+        files.sort();
+
+        let excess = files.len().saturating_sub(self.config.max_files);
+        for path in &files[..excess] {
+            match fs::remove_file(path) {
+                Ok(()) => println!("Eski kayıt dosyası silindi: {}", path.display()),
+                Err(e) => eprintln!("Eski kayıt dosyası silinemedi ({}): {}", path.display(), e),
+            }
+        }
+    }
+
+    fn flush(&mut self) {
+        if let Some(file) = self.file.as_mut() {
+            if let Err(e) = file.flush() {
+                eprintln!("Kayıt dosyası diske aktarılamadı: {}", e);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::Path;
+
+    const IDB: u32 = 1;
+    const EPB: u32 = 6;
+    const OPT_COMMENT: u16 = 1;
+    const OPT_IF_NAME: u16 = 2;
+    const LINKTYPE_ETHERNET: u32 = 1;
+    const LINKTYPE_IEEE802_11_RADIOTAP: u32 = 127;
+
+    /// Her test kendi boş dizininde çalışır.
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("ntv-recorder-{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    fn writer(dir: &Path, max_file_size: u64, rotate_interval: Option<Duration>, max_files: usize) -> RotatingWriter {
+        RotatingWriter {
+            config: RecorderConfig {
+                dir: dir.to_path_buf(),
+                max_file_size,
+                rotate_interval,
+                max_files,
+            },
+            stats: Arc::new(Stats::default()),
+            file: None,
+            opened: Instant::now(),
+            failed_at: None,
+            interfaces: Vec::new(),
+            sequence: 0,
+        }
+    }
+
+    fn record(interface: &str, link_type: u32, len: usize, comment: Option<&str>) -> Record {
+        Record {
+            interface: interface.to_string(),
+            link_type,
+            timestamp: Duration::new(1_700_000_000, 0),
+            data: vec![0xab; len],
+            comment: comment.map(str::to_string),
+        }
+    }
+
+    fn files(dir: &Path) -> Vec<PathBuf> {
+        let mut files: Vec<PathBuf> = fs::read_dir(dir)
+            .unwrap()
+            .map(|entry| entry.unwrap().path())
+            .filter(|path| path.extension().is_some_and(|extension| extension == "pcapng"))
+            .collect();
+        files.sort();
+        files
+    }
+
+    /// Dosyadaki blokları (tür, gövde) olarak döndürür.
+    fn blocks(path: &Path) -> Vec<(u32, Vec<u8>)> {
+        let data = fs::read(path).unwrap();
+        let u32_at = |offset: usize| u32::from_ne_bytes(data[offset..offset + 4].try_into().unwrap());
+        let mut blocks = Vec::new();
+        let mut offset = 0;
+        while offset < data.len() {
+            let len = u32_at(offset + 4) as usize;
+            assert_eq!(u32_at(offset + len - 4) as usize, len);
+            blocks.push((u32_at(offset), data[offset + 8..offset + len - 4].to_vec()));
+            offset += len;
+        }
+        blocks
+    }
+
+    /// Seçeneklerden `code` kodlu ilk değeri bulur.
+    fn option(mut options: &[u8], code: u16) -> Option<Vec<u8>> {
+        while options.len() >= 4 {
+            let option_code = u16::from_ne_bytes([options[0], options[1]]);
+            let len = u16::from_ne_bytes([options[2], options[3]]) as usize;
+            if option_code == code {
+                return Some(options[4..4 + len].to_vec());
+            }
+            options = &options[4 + ((len + 3) & !3)..];
+        }
+        None
+    }
+
+    #[test]
+    fn size_limit_rotates_and_every_file_describes_its_interfaces() {
+        let dir = temp_dir("size");
+        let mut writer = writer(&dir, 1000, None, 0);
+        writer.write(&record("eth0", LINKTYPE_ETHERNET, 200, None));
+        for index in 0..15 {
+            let comment = (index % 3 == 0).then_some("US -> DE");
+            writer.write(&record("wlan0", LINKTYPE_IEEE802_11_RADIOTAP, 200, comment));
+            writer.write(&record("eth0", LINKTYPE_ETHERNET, 200, None));
+        }
+        writer.flush();
+        assert_eq!(writer.stats.capture.recorded.get(), 31);
+
+        let files = files(&dir);
+        assert!(files.len() > 2, "{:?}", files);
+        let mut packets = 0;
+        let mut comments = 0;
+        for (index, path) in files.iter().enumerate() {
+            let size = fs::metadata(path).unwrap().len();
+            let blocks = blocks(path);
+            // Sınır yalnızca yazmadan önce denetlenir; son paket sınırı aşabilir
+            if index + 1 < files.len() {
+                let (_, last) = blocks.last().unwrap();
+                assert!(size >= 1000 && size - (last.len() as u64 + 12) < 1000, "{}", size);
+            }
+
+            let names: Vec<Vec<u8>> = blocks
+                .iter()
+                .filter(|(kind, _)| *kind == IDB)
+                .map(|(_, body)| option(&body[8..], OPT_IF_NAME).unwrap())
+                .collect();
+            assert_eq!(names, [b"eth0".to_vec(), b"wlan0".to_vec()], "{}", path.display());
+
+            // Paketler yalnızca kendinden önce tanımlanmış arayüzlere başvurur
+            let mut interfaces = 0;
+            for (kind, body) in &blocks {
+                match *kind {
+                    IDB => interfaces += 1,
+                    EPB => {
+                        packets += 1;
+                        let interface_id = u32::from_ne_bytes(body[..4].try_into().unwrap());
+                        assert!(interface_id < interfaces);
+                        let captured = u32::from_ne_bytes(body[12..16].try_into().unwrap()) as usize;
+                        assert_eq!(captured, 200);
+                        if let Some(comment) = option(&body[20 + captured..], OPT_COMMENT) {
+                            assert_eq!(interface_id, 1);
+                            assert_eq!(comment, b"US -> DE");
+                            comments += 1;
+                        }
+                    }
+                    _ => {}
+                }
+            }
+        }
+        assert_eq!((packets, comments), (31, 5));
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn interval_rotates_the_file() {
+        let dir = temp_dir("interval");
+        let interval = Duration::from_secs(60);
+        let mut writer = writer(&dir, 0, Some(interval), 0);
+        writer.write(&record("eth0", LINKTYPE_ETHERNET, 60, None));
+        writer.write(&record("eth0", LINKTYPE_ETHERNET, 60, None));
+        assert_eq!(files(&dir).len(), 1);
+
+        writer.opened = Instant::now().checked_sub(interval).unwrap();
+        writer.write(&record("eth0", LINKTYPE_ETHERNET, 60, None));
+        writer.write(&record("eth0", LINKTYPE_ETHERNET, 60, None));
+        writer.flush();
+
+        let files = files(&dir);
+        let epbs = |path: &PathBuf| blocks(path).iter().filter(|(kind, _)| *kind == EPB).count();
+        assert_eq!(files.iter().map(epbs).collect::<Vec<_>>(), [2, 2]);
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn oldest_recordings_are_removed_beyond_max_files() {
+        let dir = temp_dir("max-files");
+        // Başka dosyalara dokunulmaz
+        fs::write(dir.join("notes.txt"), "").unwrap();
+        // Her paket yeni dosya açar
+        let mut writer = writer(&dir, 1, None, 3);
+        for _ in 0..6 {
+            writer.write(&record("eth0", LINKTYPE_ETHERNET, 60, None));
+        }
+        writer.flush();
+
+        let names: Vec<String> = files(&dir)
+            .iter()
+            .map(|path| path.file_name().unwrap().to_string_lossy().into_owned())
+            .collect();
+        assert_eq!(names.len(), 3);
+        for (name, sequence) in names.iter().zip(["-0004", "-0005", "-0006"]) {
+            assert!(name.starts_with(FILE_PREFIX) && name.ends_with(&format!("{}{}", sequence, FILE_SUFFIX)), "{name}");
+        }
+        assert!(dir.join("notes.txt").exists());
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,222 @@
+use std::io;
+use std::net::IpAddr;
+use std::os::fd::{AsRawFd, OwnedFd};
+use std::ptr;
+use std::sync::atomic::{fence, Ordering};
+use std::time::Duration;
+
+use pnet::datalink::NetworkInterface;
+
+use crate::afpacket::{attach_filter, bind, check, open_socket, set_option, statistics};
+use crate::bpf::Instruction;
+use crate::config::RingConfig;
+use crate::interfaces::{self, CaptureOptions};
+use crate::source::{Frame, KernelStats, LinkType, PacketSource, POLL_INTERVAL};
+
+// TPACKET_V3 çerçeve boyutunu yalnızca doğrulamada kullanır; paketler
+// bloklara değişken boyutlu yerleşir
+const RING_FRAME_SIZE: usize = 2048;
+
+/// Linux TPACKET_V3 halkası üzerinden canlı arayüz. Çekirdek paketleri
+/// paylaşılan belleğe blok blok yazar; bir blok dolduğunda ya da süresi
+/// geçtiğinde bloktaki tüm paketler kopyalanmadan okunur.
+pub struct RingSource {
+    name: String,
+    addrs: Vec<IpAddr>,
+    socket: OwnedFd,
+    link_type: LinkType,
+    ring: *mut u8,
+    ring_len: usize,
+    block_size: usize,
+    block_count: usize,
+    snaplen: usize,
+    // Okunmakta olan blok ve içindeki sıradaki paket
+    block: usize,
+    held: bool,
+    remaining: u32,
+    next_packet: usize,
+    stats: KernelStats,
+}
+
+// Halka belleği yalnızca bu kaynağa ait; kaynak tek bir thread'de okunur
+unsafe impl Send for RingSource {}
+
+impl RingSource {
+    /// Halkayı kurar, varsa filtre programını ekler ve `fanout` verilmişse
+    /// soketi o fanout grubuna katar.
+    pub fn open(
+        interface: &NetworkInterface,
+        config: &RingConfig,
+        options: &CaptureOptions,
+        program: Option<&[Instruction]>,
+        fanout: Option<u16>,
+    ) -> io::Result<Self> {
+        let socket = open_socket(libc::SOCK_RAW)?;
+        let fd = socket.as_raw_fd();
+
+        if let Some(program) = program {
+            attach_filter(fd, program)?;
+        }
+
+        let version = libc::tpacket_versions::TPACKET_V3 as libc::c_int;
+        set_option(fd, libc::SOL_PACKET, libc::PACKET_VERSION, &version)?;
+
+        let block_timeout = config.block_timeout.as_millis().clamp(1, u32::MAX as u128) as libc::c_uint;
+        let request = libc::tpacket_req3 {
+            tp_block_size: config.block_size as libc::c_uint,
+            tp_block_nr: config.block_count as libc::c_uint,
+            tp_frame_size: RING_FRAME_SIZE as libc::c_uint,
+            tp_frame_nr: (config.block_size / RING_FRAME_SIZE * config.block_count) as libc::c_uint,
+            tp_retire_blk_tov: block_timeout,
+            tp_sizeof_priv: 0,
+            tp_feature_req_word: 0,
+        };
+        set_option(fd, libc::SOL_PACKET, libc::PACKET_RX_RING, &request)?;
+
+        let ring_len = config.block_size * config.block_count;
+        let ring = unsafe {
+            libc::mmap(
+                ptr::null_mut(),
+                ring_len,
+                libc::PROT_READ | libc::PROT_WRITE,
+                libc::MAP_SHARED,
+                fd,
+                0,
+            )
+        };
+        if ring == libc::MAP_FAILED {
+            return Err(io::Error::last_os_error());
+        }
+
+        // Bu noktadan sonra hata olursa halka `Drop` ile serbest bırakılır
+        let source = Self {
+            name: interface.name.clone(),
+            addrs: interface.ips.iter().map(|network| network.ip()).collect(),
+            socket,
+            link_type: interfaces::link_type(interface),
+            ring: ring as *mut u8,
+            ring_len,
+            block_size: config.block_size,
+            block_count: config.block_count,
+            snaplen: options.snaplen,
+            block: 0,
+            held: false,
+            remaining: 0,
+            next_packet: 0,
+            stats: KernelStats::default(),
+        };
+
+        bind(fd, interface, options.promiscuous)?;
+
+        if let Some(group) = fanout {
+            // Parçalar çekirdekte birleştirilip hash'lenir; aksi halde aynı
+            // datagramın parçaları farklı thread'lere düşebilir
+            let mode = libc::PACKET_FANOUT_HASH | libc::PACKET_FANOUT_FLAG_DEFRAG;
+            let argument = (group as u32 | (mode << 16)) as libc::c_int;
+            set_option(fd, libc::SOL_PACKET, libc::PACKET_FANOUT, &argument)?;
+        }
+
+        Ok(source)
+    }
+
+    fn block_header(&self, block: usize) -> *mut libc::tpacket_hdr_v1 {
+        unsafe {
+            let desc = self.ring.add(block * self.block_size) as *mut libc::tpacket_block_desc;
+            ptr::addr_of_mut!((*desc).hdr.bh1)
+        }
+    }
+
+    fn block_ready(&self) -> bool {
+        let header = self.block_header(self.block);
+        let status = unsafe { ptr::read_volatile(ptr::addr_of!((*header).block_status)) };
+        fence(Ordering::Acquire);
+        status & libc::TP_STATUS_USER != 0
+    }
+
+    /// Okunan bloğu çekirdeğe geri verir ve sıradakine geçer.
+    fn release_block(&mut self) {
+        let header = self.block_header(self.block);
+        fence(Ordering::Release);
+        unsafe { ptr::write_volatile(ptr::addr_of_mut!((*header).block_status), libc::TP_STATUS_KERNEL) };
+        self.held = false;
+        self.block = (self.block + 1) % self.block_count;
+    }
+
+    fn wait_for_block(&self) -> io::Result<()> {
+        let mut poll = libc::pollfd {
+            fd: self.socket.as_raw_fd(),
+            events: libc::POLLIN | libc::POLLERR,
+            revents: 0,
+        };
+        check(unsafe { libc::poll(&mut poll, 1, POLL_INTERVAL.as_millis() as libc::c_int) })?;
+        Ok(())
+    }
+}
+
+impl PacketSource for RingSource {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn link_type(&self) -> LinkType {
+        self.link_type
+    }
+
+    fn local_addrs(&self) -> Vec<IpAddr> {
+        self.addrs.clone()
+    }
+
+    fn next_frame(&mut self) -> io::Result<Option<Frame<'_>>> {
+        // Önceki çağrıda verilen çerçeve artık kullanılmıyor
+        if self.held && self.remaining == 0 {
+            self.release_block();
+        }
+
+        if !self.held {
+            if !self.block_ready() {
+                self.wait_for_block()?;
+                if !self.block_ready() {
+                    return Err(io::ErrorKind::TimedOut.into());
+                }
+            }
+            let header = unsafe { &*self.block_header(self.block) };
+            self.held = true;
+            self.remaining = header.num_pkts;
+            self.next_packet = header.offset_to_first_pkt as usize;
+            if self.remaining == 0 {
+                return Err(io::ErrorKind::Interrupted.into());
+            }
+        }
+
+        let block = unsafe { self.ring.add(self.block * self.block_size) };
+        let packet = unsafe { &*(block.add(self.next_packet) as *const libc::tpacket3_hdr) };
+        let start = self.next_packet + packet.tp_mac as usize;
+        let len = (packet.tp_snaplen as usize)
+            .min(self.snaplen)
+            .min(self.block_size.saturating_sub(start));
+        let data = unsafe { std::slice::from_raw_parts(block.add(start), len) };
+
+        self.remaining -= 1;
+        self.next_packet += packet.tp_next_offset as usize;
+
+        Ok(Some(Frame {
+            timestamp: Duration::new(packet.tp_sec as u64, packet.tp_nsec),
+            link_type: self.link_type,
+            data,
+        }))
+    }
+
+    fn kernel_stats(&mut self) -> Option<KernelStats> {
+        let stats: libc::tpacket_stats_v3 = statistics(self.socket.as_raw_fd()).ok()?;
+        self.stats.add(stats.tp_packets, stats.tp_drops, stats.tp_freeze_q_cnt);
+        Some(self.stats)
+    }
+}
+
+impl Drop for RingSource {
+    fn drop(&mut self) {
+        unsafe {
+            libc::munmap(self.ring as *mut libc::c_void, self.ring_len);
+        }
+    }
+}
@@ -0,0 +1,380 @@
+use pnet::datalink::{self, DataLinkReceiver, NetworkInterface};
+use std::io;
+use std::net::IpAddr;
+use std::path::Path;
+use std::str::FromStr;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+use crate::interfaces::{self, CaptureOptions};
+use crate::pcap::{PcapPacket, PcapReader};
+
+// tcpdump.org/linktypes.html
+pub const LINKTYPE_NULL: u32 = 0;
+pub const LINKTYPE_ETHERNET: u32 = 1;
+pub const LINKTYPE_RAW: u32 = 101;
+pub const LINKTYPE_LOOP: u32 = 108;
+pub const LINKTYPE_LINUX_SLL: u32 = 113;
+pub const LINKTYPE_IEEE802_11_RADIOTAP: u32 = 127;
+pub const LINKTYPE_IPV4: u32 = 228;
+pub const LINKTYPE_IPV6: u32 = 229;
+pub const LINKTYPE_LINUX_SLL2: u32 = 276;
+// Bazı sistemlerin DLT_RAW değerleri
+const DLT_RAW_BSD: u32 = 12;
+const DLT_RAW_OPENBSD: u32 = 14;
+
+/// Çerçevenin hangi bağlantı katmanı başlığıyla başladığı
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LinkType {
+    Ethernet,
+    /// Linux "cooked" başlığı (`any` arayüzü)
+    LinuxSll,
+    LinuxSll2,
+    /// Başlıksız IP (tun, WireGuard ...)
+    Raw,
+    /// BSD geri döngü; adres ailesi yakalayan makinenin bayt sırasında
+    Null,
+    /// OpenBSD geri döngü; adres ailesi ağ bayt sırasında
+    Loop,
+    /// 802.11 çerçevesi önünde radiotap başlığı
+    Radiotap,
+    Other(u32),
+}
+
+impl LinkType {
+    pub fn from_pcap(link_type: u32) -> Self {
+        match link_type {
+            LINKTYPE_ETHERNET => LinkType::Ethernet,
+            LINKTYPE_LINUX_SLL => LinkType::LinuxSll,
+            LINKTYPE_LINUX_SLL2 => LinkType::LinuxSll2,
+            LINKTYPE_RAW | LINKTYPE_IPV4 | LINKTYPE_IPV6 | DLT_RAW_BSD | DLT_RAW_OPENBSD => LinkType::Raw,
+            LINKTYPE_NULL => LinkType::Null,
+            LINKTYPE_LOOP => LinkType::Loop,
+            LINKTYPE_IEEE802_11_RADIOTAP => LinkType::Radiotap,
+            other => LinkType::Other(other),
+        }
+    }
+
+    pub fn to_pcap(self) -> u32 {
+        match self {
+            LinkType::Ethernet => LINKTYPE_ETHERNET,
+            LinkType::LinuxSll => LINKTYPE_LINUX_SLL,
+            LinkType::LinuxSll2 => LINKTYPE_LINUX_SLL2,
+            LinkType::Raw => LINKTYPE_RAW,
+            LinkType::Null => LINKTYPE_NULL,
+            LinkType::Loop => LINKTYPE_LOOP,
+            LinkType::Radiotap => LINKTYPE_IEEE802_11_RADIOTAP,
+            LinkType::Other(link_type) => link_type,
+        }
+    }
+}
+
+/// Kaynaktan okunan tek bir çerçeve
+pub struct Frame<'a> {
+    /// UNIX epoch'tan itibaren yakalama zamanı
+    pub timestamp: Duration,
+    pub link_type: LinkType,
+    pub data: &'a [u8],
+}
+
+/// Çerçevelerin nereden geldiğini (canlı arayüz, dosya, bellek ...)
+/// çözümleme ve zenginleştirme adımlarından soyutlar.
+pub trait PacketSource {
+    /// Loglarda ve olaylarda kullanılacak kaynak adı
+    fn name(&self) -> &str;
+
+    fn link_type(&self) -> LinkType;
+
+    /// Yakalama yapılan arayüzün kendi adresleri; akışların yönünü
+    /// (gelen/giden/geçen) belirlemek için kullanılır.
+    fn local_addrs(&self) -> Vec<IpAddr> {
+        Vec::new()
+    }
+
+    /// Sıradaki çerçeveyi döndürür; kaynak tükendiğinde `None` verir.
+    fn next_frame(&mut self) -> io::Result<Option<Frame<'_>>>;
+
+    /// Çekirdeğin bu kaynak için tuttuğu sayaçlar; desteklenmiyorsa `None`.
+    fn kernel_stats(&mut self) -> Option<KernelStats> {
+        None
+    }
+}
+
+/// Yakalama soketi açıldığından beri çekirdeğin saydığı paketler
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct KernelStats {
+    /// Sokete ulaşan paketler (düşenler dahil)
+    pub received: u64,
+    /// Alıcı tampon ya da halka dolu olduğu için düşenler
+    pub dropped: u64,
+    /// Halka dolduğu için kuyruğun dondurulma sayısı (yalnızca TPACKET_V3)
+    pub freeze_queue: u64,
+}
+
+impl KernelStats {
+    #[cfg_attr(not(target_os = "linux"), allow(dead_code))]
+    pub fn add(&mut self, received: u32, dropped: u32, freeze_queue: u32) {
+        self.received += received as u64;
+        self.dropped += dropped as u64;
+        self.freeze_queue += freeze_queue as u64;
+    }
+}
+
+/// Thread'ler arasında taşınabilen, veriye sahip çerçeve
+pub struct OwnedFrame {
+    pub timestamp: Duration,
+    pub link_type: LinkType,
+    pub data: Vec<u8>,
+}
+
+impl OwnedFrame {
+    pub fn from_frame(frame: &Frame) -> Self {
+        Self {
+            timestamp: frame.timestamp,
+            link_type: frame.link_type,
+            data: frame.data.to_vec(),
+        }
+    }
+
+    pub fn as_frame(&self) -> Frame<'_> {
+        Frame {
+            timestamp: self.timestamp,
+            link_type: self.link_type,
+            data: &self.data,
+        }
+    }
+}
+
+/// Bloklayan okumaların en fazla bu kadar sürmesi beklenir; kaynaklar bu
+/// süre dolduğunda `TimedOut` döndürerek durdurma isteğinin fark edilmesini
+/// sağlar.
+pub const POLL_INTERVAL: Duration = Duration::from_millis(250);
+
+pub fn is_timeout(e: &io::Error) -> bool {
+    matches!(e.kind(), io::ErrorKind::TimedOut | io::ErrorKind::WouldBlock | io::ErrorKind::Interrupted)
+}
+
+pub fn now_timestamp() -> Duration {
+    SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default()
+}
+
+/// pnet üzerinden canlı ağ arayüzü
+pub struct LiveSource {
+    name: String,
+    addrs: Vec<IpAddr>,
+    link_type: LinkType,
+    rx: Box<dyn DataLinkReceiver>,
+}
+
+impl LiveSource {
+    pub fn open(interface: &NetworkInterface, options: &CaptureOptions) -> io::Result<Self> {
+        // pnet okuma tamponundan uzun çerçeveleri keser; snaplen bu yolla uygulanır
+        let config = datalink::Config {
+            read_timeout: Some(POLL_INTERVAL),
+            read_buffer_size: options.snaplen,
+            promiscuous: options.promiscuous,
+            ..Default::default()
+        };
+        let rx = match datalink::channel(interface, config)? {
+            datalink::Channel::Ethernet(_, rx) => rx,
+            _ => return Err(io::Error::new(io::ErrorKind::Unsupported, "Desteklenmeyen kanal türü")),
+        };
+
+        Ok(Self {
+            name: interface.name.clone(),
+            addrs: interface.ips.iter().map(|network| network.ip()).collect(),
+            link_type: interfaces::link_type(interface),
+            rx,
+        })
+    }
+}
+
+impl PacketSource for LiveSource {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn link_type(&self) -> LinkType {
+        self.link_type
+    }
+
+    fn local_addrs(&self) -> Vec<IpAddr> {
+        self.addrs.clone()
+    }
+
+    fn next_frame(&mut self) -> io::Result<Option<Frame<'_>>> {
+        let data = self.rx.next()?;
+        Ok(Some(Frame {
+            timestamp: now_timestamp(),
+            link_type: self.link_type,
+            data,
+        }))
+    }
+}
+
+/// Kayıt oynatma hızı
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ReplaySpeed {
+    /// Orijinal paketler arası süreler bu katsayıya bölünür (1x, 10x ...)
+    Multiplier(f64),
+    /// Beklemeden, okunabildiği kadar hızlı
+    Unlimited,
+}
+
+impl FromStr for ReplaySpeed {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim().to_ascii_lowercase();
+        match s.as_str() {
+            "max" | "asap" | "0" => Ok(ReplaySpeed::Unlimited),
+            _ => {
+                let factor: f64 = s
+                    .trim_end_matches('x')
+                    .parse()
+                    .map_err(|_| format!("Geçersiz oynatma hızı: {}", s))?;
+                if factor.is_finite() && factor > 0.0 {
+                    Ok(ReplaySpeed::Multiplier(factor))
+                } else {
+                    Err(format!("Geçersiz oynatma hızı: {}", s))
+                }
+            }
+        }
+    }
+}
+
+/// pcap/pcapng dosyasını orijinal zamanlamaya `speed` katsayısıyla uyarak
+/// oynatır.
+pub struct FileSource {
+    name: String,
+    reader: PcapReader<io::BufReader<std::fs::File>>,
+    speed: ReplaySpeed,
+    started: Instant,
+    first_timestamp: Option<Duration>,
+    link_type: LinkType,
+    pending: Option<PcapPacket>,
+    current: Vec<u8>,
+    finished: bool,
+}
+
+impl FileSource {
+    pub fn open(path: &Path, speed: ReplaySpeed) -> io::Result<Self> {
+        Ok(Self {
+            name: path.display().to_string(),
+            reader: PcapReader::open(path)?,
+            speed,
+            started: Instant::now(),
+            first_timestamp: None,
+            link_type: LinkType::Ethernet,
+            pending: None,
+            current: Vec::new(),
+            finished: false,
+        })
+    }
+}
+
+impl PacketSource for FileSource {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn link_type(&self) -> LinkType {
+        self.link_type
+    }
+
+    fn next_frame(&mut self) -> io::Result<Option<Frame<'_>>> {
+        if self.finished {
+            return Ok(None);
+        }
+
+        // Bozuk dosyada hata bir kez bildirilir, ardından oynatma biter
+        let packet = match self.pending.take() {
+            Some(packet) => packet,
+            None => match self.reader.next_packet() {
+                Ok(Some(packet)) => packet,
+                Ok(None) => {
+                    self.finished = true;
+                    return Ok(None);
+                }
+                Err(e) => {
+                    self.finished = true;
+                    return Err(e);
+                }
+            },
+        };
+
+        if let ReplaySpeed::Multiplier(factor) = self.speed {
+            let first = *self.first_timestamp.get_or_insert_with(|| {
+                self.started = Instant::now();
+                packet.timestamp
+            });
+            let offset = packet.timestamp.saturating_sub(first).div_f64(factor);
+            let due = self.started + offset;
+            let now = Instant::now();
+            if due > now + POLL_INTERVAL {
+                // Uzun boşluklarda durdurma isteği kaçmasın
+                std::thread::sleep(POLL_INTERVAL);
+                self.pending = Some(packet);
+                return Err(io::ErrorKind::TimedOut.into());
+            }
+            if due > now {
+                std::thread::sleep(due - now);
+            }
+        }
+
+        self.link_type = LinkType::from_pcap(packet.link_type);
+        self.current = packet.data;
+
+        Ok(Some(Frame {
+            timestamp: packet.timestamp,
+            link_type: self.link_type,
+            data: &self.current,
+        }))
+    }
+}
+
+/// Bellekteki çerçeveleri sırayla veren kaynak; gerçek bir arayüz olmadan
+/// çözümleme ve zenginleştirme adımlarını çalıştırmak için.
+#[cfg(test)]
+pub struct MemorySource {
+    name: String,
+    link_type: LinkType,
+    frames: std::collections::VecDeque<(Duration, Vec<u8>)>,
+    current: Vec<u8>,
+}
+
+#[cfg(test)]
+impl MemorySource {
+    pub fn new(name: &str, link_type: LinkType, frames: Vec<(Duration, Vec<u8>)>) -> Self {
+        Self {
+            name: name.to_string(),
+            link_type,
+            frames: frames.into(),
+            current: Vec::new(),
+        }
+    }
+}
+
+#[cfg(test)]
+impl PacketSource for MemorySource {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn link_type(&self) -> LinkType {
+        self.link_type
+    }
+
+    fn next_frame(&mut self) -> io::Result<Option<Frame<'_>>> {
+        let (timestamp, data) = match self.frames.pop_front() {
+            Some(frame) => frame,
+            None => return Ok(None),
+        };
+        self.current = data;
+
+        Ok(Some(Frame {
+            timestamp,
+            link_type: self.link_type,
+            data: &self.current,
+        }))
+    }
+}
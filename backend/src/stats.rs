@@ -0,0 +1,317 @@
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use serde_json::json;
+use tokio::sync::broadcast;
+
+use crate::flow::Direction;
+use crate::source::KernelStats;
+
+/// Yalnızca artan sayaç
+#[derive(Default)]
+pub struct Counter(AtomicU64);
+
+impl Counter {
+    pub fn inc(&self) {
+        self.add(1);
+    }
+
+    pub fn add(&self, n: u64) {
+        self.0.fetch_add(n, Ordering::Relaxed);
+    }
+
+    pub fn get(&self) -> u64 {
+        self.0.load(Ordering::Relaxed)
+    }
+}
+
+/// Artıp azalabilen anlık değer
+#[derive(Default)]
+pub struct Gauge(AtomicU64);
+
+impl Gauge {
+    pub fn inc(&self) {
+        self.0.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub fn dec(&self) {
+        self.0.fetch_sub(1, Ordering::Relaxed);
+    }
+
+    pub fn get(&self) -> u64 {
+        self.0.load(Ordering::Relaxed)
+    }
+}
+
+/// Yakalama thread'leri
+#[derive(Default)]
+pub struct CaptureCounters {
+    pub frames: Counter,
+    /// Çözümleme kuyruğu dolu olduğu için yakalamanın beklediği çerçeveler
+    pub queue_full: Counter,
+    pub read_errors: Counter,
+    /// Denetleyicinin yakalamayı yeniden kurma sayısı
+    pub restarts: Counter,
+    pub kernel_received: Counter,
+    pub kernel_dropped: Counter,
+    pub kernel_freeze_queue: Counter,
+    /// pcapng kaydına yazılan çerçeveler
+    pub recorded: Counter,
+    /// Kayıt kuyruğu dolu olduğu ya da yazılamadığı için kayda alınmayanlar
+    pub record_dropped: Counter,
+}
+
+/// Çerçeve çözümleme
+#[derive(Default)]
+pub struct DecodeCounters {
+    pub frames: Counter,
+    /// Bağlantı katmanı ya da IP olarak çözülemeyen çerçeveler
+    pub undecodable: Counter,
+    pub fragments: Counter,
+    pub reassembled: Counter,
+    /// Süresi içinde tamamlanmadığı için atılan datagramlar
+    pub fragments_timed_out: Counter,
+    /// Bellek ya da datagram sınırı yüzünden atılanlar
+    pub fragments_evicted: Counter,
+    /// Çakışan parça içerdiği için atılan datagramlar
+    pub fragments_overlapping: Counter,
+    /// Hizasız, sınır dışı ya da kesik parçalar
+    pub fragments_malformed: Counter,
+    pub tunneled: Counter,
+    /// İç paketi çözülemediği için dış akışa sayılan tünel paketleri
+    pub tunnel_undecodable: Counter,
+    /// İç içe tünel sınırını aşan; sınırdaki iç paket akışa sayılır
+    pub tunnels_too_deep: Counter,
+    pub icmp_errors: Counter,
+    /// İki ucu da izlenmeyen (özel, loopback ...) adresli paketler
+    pub untracked: Counter,
+    /// Taşıma başlığı olmayan ya da akış açmayan paketler
+    pub no_transport: Counter,
+}
+
+/// Akış tablosu
+#[derive(Default)]
+pub struct FlowCounters {
+    /// Akış tablosuna giren paketler
+    pub packets: Counter,
+    pub started: Counter,
+    /// Var olan bir akışa eklenen paketler
+    pub merged: Counter,
+    pub ended: Counter,
+    /// `flow_start` olayı gönderilenler
+    pub reported: Counter,
+    /// Yapılandırmadaki filtreye uymayanlar
+    pub filtered: Counter,
+    /// Uçlarından biri konumlanamadığı için bildirilmeyenler
+    pub unlocated: Counter,
+    /// SYN'i yanıtsız kalan ya da RST ile reddedilen TCP bağlantıları
+    pub failed_handshakes: Counter,
+}
+
+#[derive(Default)]
+pub struct GeoIpCounters {
+    pub lookups: Counter,
+    pub hits: Counter,
+    pub misses: Counter,
+}
+
+/// Port 53 trafiğinden okunan DNS yanıtları
+#[derive(Default)]
+pub struct DnsCounters {
+    pub responses: Counter,
+    /// Önbelleğe eklenen adres→ad eşlemeleri
+    pub addresses: Counter,
+    pub malformed: Counter,
+}
+
+/// Akış başındaki TLS ClientHello mesajları
+#[derive(Default)]
+pub struct TlsCounters {
+    pub client_hellos: Counter,
+    pub malformed: Counter,
+}
+
+/// Broadcast kanalı ve WebSocket istemcileri
+#[derive(Default)]
+pub struct WebSocketCounters {
+    pub clients: Gauge,
+    pub connections: Counter,
+    /// Çözümlemeden broadcast kanalına aktarılan olaylar
+    pub events: Counter,
+    /// Bağlı istemci olmadığı için kimseye ulaşmayan olaylar
+    pub no_receivers: Counter,
+    /// Yavaş istemcilerin broadcast kanalında kaçırdığı mesajlar
+    pub lagged: Counter,
+    pub sent: Counter,
+    /// İstemci filtresine uymadığı için gönderilmeyenler
+    pub filtered: Counter,
+}
+
+/// Trafik sayaçlarının etiketleri
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct TrafficKey {
+    pub protocol: u8,
+    /// Uzak ucun ülkesi; bildirilmeyen ya da konumlanamayan akışlarda boş
+    pub country: Option<String>,
+    pub direction: Direction,
+}
+
+#[derive(Debug, Clone, Copy, Default)]
+pub struct TrafficCounters {
+    pub flows: u64,
+    pub packets: u64,
+    pub bytes: u64,
+}
+
+pub type Traffic = HashMap<TrafficKey, TrafficCounters>;
+
+/// Yakalamadan WebSocket'e kadar her aşamanın sayaçları. Tüm thread'ler
+/// aynı kaydı paylaşır; sayaçlar yakalama yeniden kurulsa da sıfırlanmaz.
+pub struct Stats {
+    started: Instant,
+    pub capture: CaptureCounters,
+    pub decode: DecodeCounters,
+    pub flows: FlowCounters,
+    pub geoip: GeoIpCounters,
+    pub dns: DnsCounters,
+    pub tls: TlsCounters,
+    pub websocket: WebSocketCounters,
+    // Çözümleme thread'leri kendi tablolarında biriktirip aralıklarla ekler
+    traffic: Mutex<Traffic>,
+}
+
+impl Default for Stats {
+    fn default() -> Self {
+        Self {
+            started: Instant::now(),
+            capture: CaptureCounters::default(),
+            decode: DecodeCounters::default(),
+            flows: FlowCounters::default(),
+            geoip: GeoIpCounters::default(),
+            dns: DnsCounters::default(),
+            tls: TlsCounters::default(),
+            websocket: WebSocketCounters::default(),
+            traffic: Mutex::new(Traffic::new()),
+        }
+    }
+}
+
+impl Stats {
+    /// Kaynağın son okumadan bu yana artan çekirdek sayaçlarını ekler.
+    pub fn add_kernel(&self, current: KernelStats, previous: KernelStats) {
+        self.capture.kernel_received.add(current.received.saturating_sub(previous.received));
+        self.capture.kernel_dropped.add(current.dropped.saturating_sub(previous.dropped));
+        self.capture.kernel_freeze_queue.add(current.freeze_queue.saturating_sub(previous.freeze_queue));
+    }
+
+    /// `local` tablosundaki sayaçları ekleyip tabloyu boşaltır.
+    pub fn merge_traffic(&self, local: &mut Traffic) {
+        if local.is_empty() {
+            return;
+        }
+        let mut traffic = self.traffic.lock().unwrap();
+        for (key, counters) in local.drain() {
+            let total = traffic.entry(key).or_default();
+            total.flows += counters.flows;
+            total.packets += counters.packets;
+            total.bytes += counters.bytes;
+        }
+    }
+
+    pub fn traffic(&self) -> Traffic {
+        self.traffic.lock().unwrap().clone()
+    }
+
+    pub fn uptime(&self) -> Duration {
+        self.started.elapsed()
+    }
+
+    pub fn to_json(&self) -> serde_json::Value {
+        let capture = &self.capture;
+        let decode = &self.decode;
+        let flows = &self.flows;
+        let geoip = &self.geoip;
+        let dns = &self.dns;
+        let tls = &self.tls;
+        let websocket = &self.websocket;
+        json!({
+            "type": "stats",
+            "uptime_secs": self.uptime().as_secs(),
+            "capture": {
+                "frames": capture.frames.get(),
+                "queue_full": capture.queue_full.get(),
+                "read_errors": capture.read_errors.get(),
+                "restarts": capture.restarts.get(),
+                "kernel_received": capture.kernel_received.get(),
+                "kernel_dropped": capture.kernel_dropped.get(),
+                "kernel_freeze_queue": capture.kernel_freeze_queue.get(),
+                "recorded": capture.recorded.get(),
+                "record_dropped": capture.record_dropped.get(),
+            },
+            "decode": {
+                "frames": decode.frames.get(),
+                "undecodable": decode.undecodable.get(),
+                "fragments": decode.fragments.get(),
+                "reassembled": decode.reassembled.get(),
+                "fragments_timed_out": decode.fragments_timed_out.get(),
+                "fragments_evicted": decode.fragments_evicted.get(),
+                "fragments_overlapping": decode.fragments_overlapping.get(),
+                "fragments_malformed": decode.fragments_malformed.get(),
+                "tunneled": decode.tunneled.get(),
+                "tunnel_undecodable": decode.tunnel_undecodable.get(),
+                "tunnels_too_deep": decode.tunnels_too_deep.get(),
+                "icmp_errors": decode.icmp_errors.get(),
+                "untracked": decode.untracked.get(),
+                "no_transport": decode.no_transport.get(),
+            },
+            "flows": {
+                "packets": flows.packets.get(),
+                "started": flows.started.get(),
+                "merged": flows.merged.get(),
+                "ended": flows.ended.get(),
+                "active": flows.started.get().saturating_sub(flows.ended.get()),
+                "reported": flows.reported.get(),
+                "filtered": flows.filtered.get(),
+                "unlocated": flows.unlocated.get(),
+                "failed_handshakes": flows.failed_handshakes.get(),
+            },
+            "geoip": {
+                "lookups": geoip.lookups.get(),
+                "hits": geoip.hits.get(),
+                "misses": geoip.misses.get(),
+            },
+            "dns": {
+                "responses": dns.responses.get(),
+                "addresses": dns.addresses.get(),
+                "malformed": dns.malformed.get(),
+            },
+            "tls": {
+                "client_hellos": tls.client_hellos.get(),
+                "malformed": tls.malformed.get(),
+            },
+            "websocket": {
+                "clients": websocket.clients.get(),
+                "connections": websocket.connections.get(),
+                "events": websocket.events.get(),
+                "no_receivers": websocket.no_receivers.get(),
+                "lagged": websocket.lagged.get(),
+                "sent": websocket.sent.get(),
+                "filtered": websocket.filtered.get(),
+            },
+        })
+    }
+}
+
+/// İstatistikleri `interval` aralıkla tüm istemcilere yayınlar.
+pub async fn publish(tx: broadcast::Sender<String>, stats: Arc<Stats>, interval: Duration) {
+    let mut ticker = tokio::time::interval(interval);
+    // İlk tik hemen gelir; henüz sayılacak bir şey yok
+    ticker.tick().await;
+    loop {
+        ticker.tick().await;
+        // Bağlı istemci yoksa gönderim hatası beklenen bir durum
+        let _ = tx.send(stats.to_json().to_string());
+    }
+}
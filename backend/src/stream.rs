@@ -0,0 +1,91 @@
+use std::collections::BTreeMap;
+
+/// TCP bağlantısının tek yönündeki veriyi sıra numarasına göre birleştirir.
+/// Yeniden iletimlerin tekrarlanan kısmı atılır, sırası gelmemiş segmentler
+/// aradaki boşluk dolana kadar bekletilir. Toplam veri `limit` bayta
+/// kadar tutulur.
+pub struct StreamBuffer {
+    // `data` dizisinin ilk baytının sıra numarası
+    start: u32,
+    data: Vec<u8>,
+    // Sırası gelmemiş segmentler; `start` değerine göre konumlarıyla
+    pending: BTreeMap<usize, Vec<u8>>,
+    pending_bytes: usize,
+    limit: usize,
+}
+
+impl StreamBuffer {
+    /// `start` akıştaki ilk veri baytının sıra numarasıdır; SYN
+    /// görüldüyse onun sıra numarasının bir fazlası.
+    pub fn new(start: u32, limit: usize) -> Self {
+        Self {
+            start,
+            data: Vec::new(),
+            pending: BTreeMap::new(),
+            pending_bytes: 0,
+            limit,
+        }
+    }
+
+    /// Segmenti ekler; sıralı veri uzadıysa `true` döner.
+    pub fn push(&mut self, sequence: u32, payload: &[u8]) -> bool {
+        // Başlangıçtan önceki kısım daha önce alınmış ya da tüketilmiş veridir
+        let offset = sequence.wrapping_sub(self.start) as i32;
+        let (offset, payload) = if offset < 0 {
+            let skip = offset.unsigned_abs() as usize;
+            if skip >= payload.len() {
+                return false;
+            }
+            (0, &payload[skip..])
+        } else {
+            (offset as usize, payload)
+        };
+        if payload.is_empty() || offset >= self.limit {
+            return false;
+        }
+        let payload = &payload[..payload.len().min(self.limit - offset)];
+
+        if offset > self.data.len() {
+            if self.data.len() + self.pending_bytes + payload.len() <= self.limit && !self.pending.contains_key(&offset) {
+                self.pending_bytes += payload.len();
+                self.pending.insert(offset, payload.to_vec());
+            }
+            return false;
+        }
+
+        let before = self.data.len();
+        self.append(offset, payload);
+        while let Some(entry) = self.pending.first_entry() {
+            if *entry.key() > self.data.len() {
+                break;
+            }
+            let (offset, segment) = entry.remove_entry();
+            self.pending_bytes -= segment.len();
+            self.append(offset, &segment);
+        }
+        self.data.len() > before
+    }
+
+    fn append(&mut self, offset: usize, segment: &[u8]) {
+        let end = offset + segment.len();
+        if end > self.data.len() {
+            self.data.extend_from_slice(&segment[self.data.len() - offset..]);
+        }
+    }
+
+    /// Sıralı olarak birleşmiş veri
+    pub fn data(&self) -> &[u8] {
+        &self.data
+    }
+
+    /// İşlenen ilk `len` baytı atar; sınır bu kadar genişler.
+    pub fn consume(&mut self, len: usize) {
+        let len = len.min(self.data.len());
+        self.data.drain(..len);
+        self.start = self.start.wrapping_add(len as u32);
+        self.pending = std::mem::take(&mut self.pending)
+            .into_iter()
+            .map(|(offset, segment)| (offset - len, segment))
+            .collect();
+    }
+}
@@ -0,0 +1,359 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use pnet::datalink::NetworkInterface;
+use pnet::ipnetwork::IpNetwork;
+use serde_json::json;
+use tokio::sync::{broadcast, mpsc, watch};
+
+use crate::capture;
+use crate::config::Config;
+use crate::interfaces::{self, CaptureOptions};
+use crate::recorder::Recorder;
+use crate::stats::Stats;
+
+// Başarısız denemeler arasındaki bekleme 1 sn'den başlayıp 60 sn'ye kadar
+// ikiye katlanır
+const MIN_BACKOFF: Duration = Duration::from_secs(1);
+const MAX_BACKOFF: Duration = Duration::from_secs(60);
+// Bu kadar kesintisiz çalışan yakalama sonrası bekleme sıfırlanır
+const BACKOFF_RESET: Duration = Duration::from_secs(30);
+// Netlink olmayan sistemlerde ya da kaçan olaylara karşı arayüzler bu
+// aralıkla yeniden kontrol edilir
+const RECHECK_INTERVAL: Duration = Duration::from_secs(5);
+const STOP_POLL_INTERVAL: Duration = Duration::from_millis(250);
+
+/// İstemcilere `capture_status` mesajıyla bildirilen yakalama durumu
+#[derive(Debug, Clone, PartialEq)]
+pub enum CaptureStatus {
+    Starting,
+    Running { interfaces: Vec<String> },
+    /// Seçilen arayüz yok ya da kapalı; gelmesi bekleniyor
+    WaitingForInterface { reason: String, retry_in: Duration },
+    /// Kayıt oynatmada tekrar denenmez (`retry_in` boş)
+    Failed { reason: String, retry_in: Option<Duration> },
+    /// Kayıt oynatma tamamlandı
+    Finished,
+    Stopped,
+}
+
+impl CaptureStatus {
+    pub fn to_json(&self) -> serde_json::Value {
+        match self {
+            CaptureStatus::Starting => json!({ "type": "capture_status", "state": "starting" }),
+            CaptureStatus::Running { interfaces } => json!({
+                "type": "capture_status",
+                "state": "running",
+                "interfaces": interfaces,
+            }),
+            CaptureStatus::WaitingForInterface { reason, retry_in } => json!({
+                "type": "capture_status",
+                "state": "waiting_for_interface",
+                "reason": reason,
+                "retry_in_ms": retry_in.as_millis() as u64,
+            }),
+            CaptureStatus::Failed { reason, retry_in } => json!({
+                "type": "capture_status",
+                "state": "failed",
+                "reason": reason,
+                "retry_in_ms": retry_in.map(|retry_in| retry_in.as_millis() as u64),
+            }),
+            CaptureStatus::Finished => json!({ "type": "capture_status", "state": "finished" }),
+            CaptureStatus::Stopped => json!({ "type": "capture_status", "state": "stopped" }),
+        }
+    }
+}
+
+/// Durum değişikliklerini bağlı istemcilere yayınlar ve sonradan
+/// bağlananlar için son durumu saklar.
+#[derive(Clone)]
+pub struct StatusReporter {
+    tx: broadcast::Sender<String>,
+    current: Arc<watch::Sender<String>>,
+}
+
+impl StatusReporter {
+    pub fn new(tx: broadcast::Sender<String>) -> (Self, watch::Receiver<String>) {
+        let (current, rx) = watch::channel(CaptureStatus::Starting.to_json().to_string());
+        (Self { tx, current: Arc::new(current) }, rx)
+    }
+
+    pub fn report(&self, status: CaptureStatus) {
+        match &status {
+            CaptureStatus::Running { interfaces } => println!("Yakalama durumu: çalışıyor ({})", interfaces.join(", ")),
+            CaptureStatus::WaitingForInterface { reason, retry_in } => {
+                println!("Yakalama durumu: arayüz bekleniyor ({}), {:?} sonra tekrar denenecek", reason, retry_in)
+            }
+            CaptureStatus::Failed { reason, retry_in: Some(retry_in) } => {
+                eprintln!("Yakalama durumu: başarısız ({}), {:?} sonra tekrar denenecek", reason, retry_in)
+            }
+            CaptureStatus::Failed { reason, retry_in: None } => eprintln!("Yakalama durumu: başarısız ({})", reason),
+            CaptureStatus::Starting | CaptureStatus::Finished | CaptureStatus::Stopped => {}
+        }
+
+        let message = status.to_json().to_string();
+        self.current.send_replace(message.clone());
+        // Bağlı istemci yoksa gönderim hatası beklenen bir durum
+        let _ = self.tx.send(message);
+    }
+}
+
+/// Başarısız denemeler arasındaki artan bekleme
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Backoff(Duration);
+
+impl Backoff {
+    fn new() -> Self {
+        Backoff(MIN_BACKOFF)
+    }
+
+    fn current(&self) -> Duration {
+        self.0
+    }
+
+    /// Bir sonraki başarısız denemenin beklemesini ikiye katlar.
+    fn failed(&mut self) {
+        self.0 = (self.0 * 2).min(MAX_BACKOFF);
+    }
+
+    /// Arayüz değişikliğiyle yeniden kurulan ya da yeterince uzun süren
+    /// yakalamadan sonra bekleme baştan başlar.
+    fn finished(&mut self, changed: bool, ran_for: Duration) {
+        if changed || ran_for >= BACKOFF_RESET {
+            self.0 = MIN_BACKOFF;
+        }
+    }
+}
+
+type Selected = Vec<(NetworkInterface, CaptureOptions)>;
+
+// Arayüzlerin yakalamayı etkileyen hali; değişirse yakalama yeniden kurulur
+type Snapshot = Vec<(u32, String, Vec<IpNetwork>)>;
+
+fn snapshot(selected: &[(NetworkInterface, CaptureOptions)]) -> Snapshot {
+    selected
+        .iter()
+        .map(|(interface, _)| (interface.index, interface.name.clone(), interface.ips.clone()))
+        .collect()
+}
+
+/// Seçimlere uyan arayüzlerden ayakta olanlar ve kapalı olanların adları
+fn usable_interfaces(config: &Config) -> Result<(Selected, Vec<String>), String> {
+    let selected = interfaces::select(&config.interfaces, config.capture)?;
+    let (up, down): (Vec<_>, Vec<_>) = selected.into_iter().partition(|(interface, _)| interface.is_up());
+    let down: Vec<String> = down.into_iter().map(|(interface, _)| interface.name).collect();
+    if up.is_empty() {
+        return Err(format!("Arayüz kapalı: {}", down.join(", ")));
+    }
+    Ok((up, down))
+}
+
+#[cfg(target_os = "linux")]
+fn watch_links() -> Option<mpsc::UnboundedReceiver<crate::netlink::LinkEvent>> {
+    match crate::netlink::watch() {
+        Ok(events) => Some(events),
+        Err(e) => {
+            eprintln!("Netlink dinlenemedi ({}), arayüzler {:?} aralıkla kontrol edilecek", e, RECHECK_INTERVAL);
+            None
+        }
+    }
+}
+
+#[cfg(not(target_os = "linux"))]
+fn watch_links() -> Option<mpsc::UnboundedReceiver<()>> {
+    None
+}
+
+/// Sıradaki arayüz olayını bekler; dinleyici yoksa hiç tamamlanmaz.
+async fn next_link_event<T: std::fmt::Debug>(events: &mut Option<mpsc::UnboundedReceiver<T>>) {
+    match events {
+        Some(rx) => match rx.recv().await {
+            Some(event) => println!("Ağ arayüzü değişti: {:?}", event),
+            None => {
+                *events = None;
+                std::future::pending::<()>().await
+            }
+        },
+        None => std::future::pending::<()>().await,
+    }
+}
+
+/// `duration` kadar, arayüz değişene ya da kapatma istenene kadar bekler.
+async fn wait<T: std::fmt::Debug>(duration: Duration, events: &mut Option<mpsc::UnboundedReceiver<T>>, stop: &AtomicBool) {
+    let deadline = Instant::now() + duration;
+    while !stop.load(Ordering::Relaxed) && Instant::now() < deadline {
+        tokio::select! {
+            _ = next_link_event(events) => return,
+            _ = tokio::time::sleep(STOP_POLL_INTERVAL) => {}
+        }
+    }
+}
+
+/// Canlı yakalamayı yönetir: seçilen arayüzler gelene kadar bekler,
+/// bağlantı durumu ya da adresler değişince yakalamayı yeniden kurar,
+/// hatalarda artan aralıklarla yeniden dener ve her durum değişikliğini
+/// istemcilere bildirir.
+pub async fn supervise(
+    tx: broadcast::Sender<String>,
+    config: &Config,
+    stop: Arc<AtomicBool>,
+    status: StatusReporter,
+    stats: Arc<Stats>,
+    recorder: Option<Recorder>,
+) {
+    let mut events = watch_links();
+    let mut backoff = Backoff::new();
+    let mut first = true;
+
+    while !stop.load(Ordering::Relaxed) {
+        let selected = match usable_interfaces(config) {
+            Ok((selected, down)) => {
+                if !down.is_empty() {
+                    println!("Kapalı arayüzler açıldığında yakalamaya eklenecek: {}", down.join(", "));
+                }
+                selected
+            }
+            Err(reason) => {
+                status.report(CaptureStatus::WaitingForInterface { reason, retry_in: backoff.current() });
+                wait(backoff.current(), &mut events, &stop).await;
+                backoff.failed();
+                continue;
+            }
+        };
+
+        let current = snapshot(&selected);
+        if !std::mem::take(&mut first) {
+            stats.capture.restarts.inc();
+        }
+        status.report(CaptureStatus::Running {
+            interfaces: selected.iter().map(|(interface, _)| interface.name.clone()).collect(),
+        });
+
+        // Yalnızca bu yakalamayı durdurur; kapatma isteği de buraya aktarılır
+        let run_stop = Arc::new(AtomicBool::new(false));
+        let started = Instant::now();
+        let mut last_check = Instant::now();
+        let mut changed = false;
+        let capture = capture::start_packet_capture(
+            tx.clone(),
+            config,
+            &selected,
+            run_stop.clone(),
+            stats.clone(),
+            recorder.clone(),
+        );
+        tokio::pin!(capture);
+
+        let result = loop {
+            let recheck = tokio::select! {
+                result = &mut capture => break result,
+                _ = next_link_event(&mut events) => true,
+                _ = tokio::time::sleep(STOP_POLL_INTERVAL) => last_check.elapsed() >= RECHECK_INTERVAL,
+            };
+
+            if stop.load(Ordering::Relaxed) {
+                run_stop.store(true, Ordering::Relaxed);
+            } else if recheck && !changed {
+                last_check = Instant::now();
+                if usable_interfaces(config).map(|(selected, _)| snapshot(&selected)).ok() != Some(current.clone()) {
+                    println!("Arayüzler değişti, yakalama yeniden kurulacak");
+                    changed = true;
+                    run_stop.store(true, Ordering::Relaxed);
+                }
+            }
+        };
+
+        // Arayüz değişikliğiyle yeniden kurulan yakalama bir hata sayılmaz
+        backoff.finished(changed, started.elapsed());
+
+        match result {
+            Ok(()) if changed || stop.load(Ordering::Relaxed) => {}
+            Ok(()) => {
+                println!("Paket yakalama normal şekilde sonlandı");
+                wait(MIN_BACKOFF, &mut events, &stop).await;
+            }
+            // Arayüzlerden birinin kalıcı hatası da buraya gelir; bekledikten
+            // sonra bozulan arayüz de yeniden açılır
+            Err(e) => {
+                status.report(CaptureStatus::Failed { reason: e.to_string(), retry_in: Some(backoff.current()) });
+                wait(backoff.current(), &mut events, &stop).await;
+                backoff.failed();
+            }
+        }
+    }
+
+    status.report(CaptureStatus::Stopped);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn backoff_doubles_up_to_the_limit_and_resets() {
+        let mut backoff = Backoff::new();
+        let mut waits = Vec::new();
+        for _ in 0..8 {
+            waits.push(backoff.current().as_secs());
+            backoff.failed();
+        }
+        assert_eq!(waits, [1, 2, 4, 8, 16, 32, 60, 60]);
+
+        // Kısa süren yakalamadan sonra bekleme sürer
+        backoff.finished(false, Duration::from_secs(5));
+        assert_eq!(backoff.current(), MAX_BACKOFF);
+        backoff.finished(false, BACKOFF_RESET);
+        assert_eq!(backoff.current(), MIN_BACKOFF);
+
+        backoff.failed();
+        backoff.finished(true, Duration::ZERO);
+        assert_eq!(backoff.current(), MIN_BACKOFF);
+    }
+
+    #[tokio::test]
+    async fn link_event_or_stop_cuts_the_wait_short() {
+        let (tx, rx) = mpsc::unbounded_channel();
+        let mut events = Some(rx);
+        let stop = AtomicBool::new(false);
+
+        // Arayüz geri geldiğinde yeniden bağlanmak için bekleme bitmeli
+        tx.send("eth1 up").unwrap();
+        let started = Instant::now();
+        wait(MAX_BACKOFF, &mut events, &stop).await;
+        assert!(started.elapsed() < Duration::from_secs(5));
+
+        stop.store(true, Ordering::Relaxed);
+        wait(MAX_BACKOFF, &mut events, &stop).await;
+        assert!(started.elapsed() < Duration::from_secs(5));
+
+        // Dinleyici kapanınca süre dolana kadar beklenir
+        drop(tx);
+        stop.store(false, Ordering::Relaxed);
+        let started = Instant::now();
+        wait(Duration::from_millis(300), &mut events, &stop).await;
+        assert!(started.elapsed() >= Duration::from_millis(300));
+        assert!(events.is_none());
+    }
+
+    #[test]
+    fn failures_are_published_and_kept_for_new_clients() {
+        let (tx, mut rx) = broadcast::channel(4);
+        let (status, current) = StatusReporter::new(tx);
+        status.report(CaptureStatus::Failed {
+            reason: "eth1 okunamıyor: No such device".to_string(),
+            retry_in: Some(Duration::from_secs(2)),
+        });
+
+        let message: serde_json::Value = serde_json::from_str(&rx.try_recv().unwrap()).unwrap();
+        assert_eq!(message["state"], "failed");
+        assert_eq!(message["reason"], "eth1 okunamıyor: No such device");
+        assert_eq!(message["retry_in_ms"], 2000);
+        assert_eq!(*current.borrow(), message.to_string());
+
+        status.report(CaptureStatus::Running { interfaces: vec!["eth0".to_string(), "eth1".to_string()] });
+        let message: serde_json::Value = serde_json::from_str(&current.borrow()).unwrap();
+        assert_eq!(message["state"], "running");
+        assert_eq!(message["interfaces"], json!(["eth0", "eth1"]));
+    }
+}
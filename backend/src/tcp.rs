@@ -0,0 +1,414 @@
+use std::time::Duration;
+
+use pnet::packet::tcp::TcpFlags;
+
+use crate::decode::TcpHeader;
+
+// El sıkışma RTT'si bilinmiyorsa, geride kalan segmentin yeniden iletim
+// yerine sıra dışı sayılacağı en uzun süre
+const OUT_OF_ORDER_WINDOW: Duration = Duration::from_millis(3);
+
+/// Pasif gözlemle çıkarılan TCP bağlantı durumu
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TcpState {
+    /// Henüz segment görülmedi
+    Unknown,
+    /// SYN görüldü, SYN-ACK bekleniyor (yarı açık)
+    SynSent,
+    /// SYN-ACK görüldü, son ACK bekleniyor (yarı açık)
+    SynReceived,
+    Established,
+    /// En az bir taraf FIN gönderdi
+    Closing,
+    Closed,
+    Reset,
+}
+
+impl TcpState {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            TcpState::Unknown => "unknown",
+            TcpState::SynSent => "syn_sent",
+            TcpState::SynReceived => "syn_received",
+            TcpState::Established => "established",
+            TcpState::Closing => "closing",
+            TcpState::Closed => "closed",
+            TcpState::Reset => "reset",
+        }
+    }
+
+    pub fn is_finished(&self) -> bool {
+        matches!(self, TcpState::Closed | TcpState::Reset)
+    }
+}
+
+/// Durum geçişlerinde istemcilere bildirilen olaylar
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TcpEvent {
+    /// Üçlü el sıkışma tamamlandı
+    Opened,
+    /// İki taraf da FIN gönderdi
+    Closed,
+    Reset,
+    /// SYN'e SYN-ACK yerine RST döndü
+    Refused,
+}
+
+impl TcpEvent {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            TcpEvent::Opened => "connection_opened",
+            TcpEvent::Closed => "connection_closed",
+            TcpEvent::Reset => "connection_reset",
+            TcpEvent::Refused => "connection_failed",
+        }
+    }
+}
+
+/// Tek yöndeki sıra numarası takibi ve ağ kalitesi sayaçları
+#[derive(Debug, Clone, Copy, Default)]
+pub struct TcpSide {
+    // Bu yönde görülen en ileri sıra numarası (segment sonu)
+    next_seq: Option<u32>,
+    // `next_seq` değerini en son ilerleten segmentin zamanı
+    last_advance: Duration,
+    zero_window: bool,
+    pub retransmissions: u64,
+    pub out_of_order: u64,
+    /// Sıfır pencere ilan edilme sayısı
+    pub zero_windows: u64,
+}
+
+/// Tek bir akışın TCP durum makinesi. Yön, akışın başlatanına göredir.
+#[derive(Debug, Clone)]
+pub struct TcpTracker {
+    pub state: TcpState,
+    /// SYN ile son ACK arasındaki süre (yakalama noktasından görüldüğü haliyle)
+    pub handshake_rtt: Option<Duration>,
+    /// Başlatanın gönderdiği segmentler
+    pub forward: TcpSide,
+    /// Karşı ucun gönderdiği segmentler
+    pub reverse: TcpSide,
+    syn_at: Option<Duration>,
+    fin_from_initiator: bool,
+    fin_from_responder: bool,
+}
+
+impl Default for TcpTracker {
+    fn default() -> Self {
+        Self {
+            state: TcpState::Unknown,
+            handshake_rtt: None,
+            forward: TcpSide::default(),
+            reverse: TcpSide::default(),
+            syn_at: None,
+            fin_from_initiator: false,
+            fin_from_responder: false,
+        }
+    }
+}
+
+/// Sıra numaralarının 2^32 sınırında dönmesini hesaba katarak `a < b`
+fn seq_before(a: u32, b: u32) -> bool {
+    (a.wrapping_sub(b) as i32) < 0
+}
+
+impl TcpTracker {
+    /// Akışın başlatanı değiştiğinde yöne bağlı alanları yer değiştirir.
+    pub fn swap_sides(&mut self) {
+        std::mem::swap(&mut self.forward, &mut self.reverse);
+        std::mem::swap(&mut self.fin_from_initiator, &mut self.fin_from_responder);
+    }
+
+    /// Aktif zaman aşımıyla bölünen bağlantının devamı için durumu ve sıra
+    /// takibini korur, sayaçları sıfırlar.
+    pub fn resume(&self) -> Self {
+        let mut tracker = self.clone();
+        for side in [&mut tracker.forward, &mut tracker.reverse] {
+            side.retransmissions = 0;
+            side.out_of_order = 0;
+            side.zero_windows = 0;
+        }
+        tracker
+    }
+
+    pub fn on_segment(&mut self, now: Duration, from_initiator: bool, flags: u8) -> Option<TcpEvent> {
+        let syn = flags & TcpFlags::SYN != 0;
+        let ack = flags & TcpFlags::ACK != 0;
+        let fin = flags & TcpFlags::FIN != 0;
+        let rst = flags & TcpFlags::RST != 0;
+
+        if self.state.is_finished() {
+            return None;
+        }
+
+        if rst {
+            let refused = self.state == TcpState::SynSent && !from_initiator;
+            self.state = TcpState::Reset;
+            return Some(if refused { TcpEvent::Refused } else { TcpEvent::Reset });
+        }
+
+        match self.state {
+            TcpState::Unknown => {
+                self.state = if syn && !ack {
+                    self.syn_at = Some(now);
+                    TcpState::SynSent
+                } else if syn && ack {
+                    TcpState::SynReceived
+                } else {
+                    // Akış ortasından yakalamaya başlandı
+                    TcpState::Established
+                };
+            }
+            TcpState::SynSent if syn && ack && !from_initiator => {
+                self.state = TcpState::SynReceived;
+            }
+            TcpState::SynReceived if ack && !syn && from_initiator => {
+                self.state = TcpState::Established;
+                self.handshake_rtt = self.syn_at.map(|syn_at| now.saturating_sub(syn_at));
+                if !fin {
+                    return Some(TcpEvent::Opened);
+                }
+            }
+            _ => {}
+        }
+
+        if fin {
+            if from_initiator {
+                self.fin_from_initiator = true;
+            } else {
+                self.fin_from_responder = true;
+            }
+
+            if self.fin_from_initiator && self.fin_from_responder {
+                self.state = TcpState::Closed;
+                return Some(TcpEvent::Closed);
+            }
+            self.state = TcpState::Closing;
+        }
+
+        None
+    }
+
+    /// Segmenti sıra numarasına göre yeniden iletim ya da sıra dışı olarak
+    /// sınıflandırır. Gönderen taraf sıfır pencere ilan etmeye yeni
+    /// başladıysa `true` döner.
+    pub fn on_sequence(&mut self, now: Duration, from_initiator: bool, tcp: &TcpHeader) -> bool {
+        let out_of_order_window = self.handshake_rtt.unwrap_or(OUT_OF_ORDER_WINDOW);
+        let side = if from_initiator { &mut self.forward } else { &mut self.reverse };
+
+        let syn = tcp.flags & TcpFlags::SYN != 0;
+        let fin = tcp.flags & TcpFlags::FIN != 0;
+        let rst = tcp.flags & TcpFlags::RST != 0;
+
+        // SYN ve FIN birer sıra numarası tüketir; yalın ACK'ler sayılmaz
+        let len = tcp.payload_len as u32 + syn as u32 + fin as u32;
+        if len > 0 && !rst {
+            let end = tcp.sequence.wrapping_add(len);
+            match side.next_seq {
+                Some(next) if seq_before(tcp.sequence, next) => {
+                    // Daha önce görülen aralığın gerisinde: az önce ilerleyen
+                    // akışın arkasından geldiyse sıra dışı, yoksa yeniden iletim
+                    if now.saturating_sub(side.last_advance) < out_of_order_window {
+                        side.out_of_order += 1;
+                    } else {
+                        side.retransmissions += 1;
+                    }
+                    if seq_before(next, end) {
+                        side.next_seq = Some(end);
+                        side.last_advance = now;
+                    }
+                }
+                _ => {
+                    side.next_seq = Some(end);
+                    side.last_advance = now;
+                }
+            }
+        }
+
+        if syn || fin || rst {
+            return false;
+        }
+        let zero_window = tcp.window == 0;
+        let started = zero_window && !side.zero_window;
+        side.zero_window = zero_window;
+        if started {
+            side.zero_windows += 1;
+        }
+        started
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testutil::{ms, ACK, FIN_ACK, PSH_ACK, RST, SYN, SYN_ACK};
+
+    // Başlatan (true) ya da karşı uç (false) ve bayraklar
+    fn run(tracker: &mut TcpTracker, segments: &[(u64, bool, u8)]) -> Vec<Option<TcpEvent>> {
+        segments
+            .iter()
+            .map(|&(at, from_initiator, flags)| tracker.on_segment(ms(at), from_initiator, flags))
+            .collect()
+    }
+
+    #[test]
+    fn handshake_opens_and_fins_close() {
+        let mut tracker = TcpTracker::default();
+        let events = run(&mut tracker, &[(0, true, SYN), (30, false, SYN_ACK), (42, true, ACK)]);
+        assert_eq!(events, [None, None, Some(TcpEvent::Opened)]);
+        assert_eq!(tracker.state, TcpState::Established);
+        assert_eq!(tracker.handshake_rtt, Some(ms(42)));
+
+        let events = run(&mut tracker, &[(50, true, PSH_ACK), (60, false, FIN_ACK)]);
+        assert_eq!(events, [None, None]);
+        assert_eq!(tracker.state, TcpState::Closing);
+        assert_eq!(run(&mut tracker, &[(70, true, FIN_ACK)]), [Some(TcpEvent::Closed)]);
+        assert_eq!(tracker.state, TcpState::Closed);
+
+        // Kapanmış bağlantıda başka olay üretilmez
+        assert_eq!(run(&mut tracker, &[(80, true, ACK), (90, false, RST)]), [None, None]);
+        assert_eq!(tracker.state, TcpState::Closed);
+    }
+
+    #[test]
+    fn rst_to_syn_is_a_failed_handshake() {
+        let mut tracker = TcpTracker::default();
+        assert_eq!(run(&mut tracker, &[(0, true, SYN), (1, false, RST | ACK)]), [None, Some(TcpEvent::Refused)]);
+        assert_eq!(tracker.state, TcpState::Reset);
+
+        // Başlatanın kendi RST'si ret sayılmaz
+        let mut tracker = TcpTracker::default();
+        assert_eq!(run(&mut tracker, &[(0, true, SYN), (1, true, RST)]), [None, Some(TcpEvent::Reset)]);
+
+        let mut tracker = TcpTracker::default();
+        let events = run(&mut tracker, &[(0, true, SYN), (1, false, SYN_ACK), (2, true, ACK), (3, false, RST)]);
+        assert_eq!(events[3], Some(TcpEvent::Reset));
+        assert_eq!(tracker.state, TcpState::Reset);
+    }
+
+    #[test]
+    fn half_open_states_are_tracked() {
+        let mut tracker = TcpTracker::default();
+        run(&mut tracker, &[(0, true, SYN)]);
+        assert_eq!(tracker.state, TcpState::SynSent);
+        // Aynı yönden gelen SYN-ACK el sıkışmayı ilerletmez
+        run(&mut tracker, &[(1, true, SYN_ACK)]);
+        assert_eq!(tracker.state, TcpState::SynSent);
+        run(&mut tracker, &[(2, false, SYN_ACK)]);
+        assert_eq!(tracker.state, TcpState::SynReceived);
+        assert_eq!(tracker.handshake_rtt, None);
+    }
+
+    #[test]
+    fn mid_stream_capture_starts_established() {
+        let mut tracker = TcpTracker::default();
+        assert_eq!(run(&mut tracker, &[(0, false, PSH_ACK)]), [None]);
+        assert_eq!(tracker.state, TcpState::Established);
+        assert_eq!(tracker.handshake_rtt, None);
+
+        // SYN-ACK ile başlayan akışın RTT'si bilinmez
+        let mut tracker = TcpTracker::default();
+        let events = run(&mut tracker, &[(0, false, SYN_ACK), (5, true, ACK)]);
+        assert_eq!(events, [None, Some(TcpEvent::Opened)]);
+        assert_eq!(tracker.handshake_rtt, None);
+    }
+
+    #[test]
+    fn swapped_sides_keep_fin_direction() {
+        let mut tracker = TcpTracker::default();
+        run(&mut tracker, &[(0, true, ACK), (1, true, FIN_ACK)]);
+        tracker.swap_sides();
+        // Aynı uç artık karşı uç; onun ikinci FIN'i bağlantıyı kapatmaz
+        assert_eq!(run(&mut tracker, &[(2, false, FIN_ACK)]), [None]);
+        assert_eq!(run(&mut tracker, &[(3, true, FIN_ACK)]), [Some(TcpEvent::Closed)]);
+    }
+
+    fn segment(flags: u8, sequence: u32, payload_len: usize, window: u16) -> TcpHeader {
+        TcpHeader { flags, sequence, window, payload_len }
+    }
+
+    fn data(sequence: u32, payload_len: usize) -> TcpHeader {
+        segment(PSH_ACK, sequence, payload_len, 65535)
+    }
+
+    #[test]
+    fn late_segment_is_retransmission_after_rtt() {
+        let mut tracker = TcpTracker { handshake_rtt: Some(ms(20)), ..TcpTracker::default() };
+        tracker.on_sequence(ms(0), true, &segment(SYN, 999, 0, 65535));
+        tracker.on_sequence(ms(100), true, &data(1000, 100));
+        tracker.on_sequence(ms(110), true, &data(1100, 100));
+        // Gönderilen son segment RTT'den sonra yeniden geliyor
+        tracker.on_sequence(ms(200), true, &data(1100, 100));
+        // Yalın ACK'ler sıra numarası tüketmez
+        tracker.on_sequence(ms(210), true, &segment(ACK, 1200, 0, 65535));
+        tracker.on_sequence(ms(220), true, &segment(ACK, 1200, 0, 65535));
+
+        assert_eq!(tracker.forward.retransmissions, 1);
+        assert_eq!(tracker.forward.out_of_order, 0);
+        assert_eq!(tracker.reverse.retransmissions, 0);
+    }
+
+    #[test]
+    fn late_segment_within_rtt_is_out_of_order() {
+        let mut tracker = TcpTracker { handshake_rtt: Some(ms(20)), ..TcpTracker::default() };
+        tracker.on_sequence(ms(0), false, &data(5000, 100));
+        // Arada bir segment atlandı, ardından geride kalan geliyor
+        tracker.on_sequence(ms(5), false, &data(5200, 100));
+        tracker.on_sequence(ms(10), false, &data(5100, 100));
+        assert_eq!(tracker.reverse.out_of_order, 1);
+        assert_eq!(tracker.reverse.retransmissions, 0);
+
+        // RTT bilinmiyorsa sabit pencere kullanılır
+        let mut tracker = TcpTracker::default();
+        tracker.on_sequence(ms(0), false, &data(5000, 100));
+        tracker.on_sequence(ms(1), false, &data(5200, 100));
+        tracker.on_sequence(ms(2), false, &data(5100, 100));
+        tracker.on_sequence(ms(100), false, &data(5100, 100));
+        assert_eq!(tracker.reverse.out_of_order, 1);
+        assert_eq!(tracker.reverse.retransmissions, 1);
+    }
+
+    #[test]
+    fn sequence_wraparound_is_not_a_retransmission() {
+        let mut tracker = TcpTracker::default();
+        tracker.on_sequence(ms(0), true, &data(u32::MAX - 49, 100));
+        tracker.on_sequence(ms(100), true, &data(50, 100));
+        assert_eq!(tracker.forward.retransmissions, 0);
+
+        // Yeniden iletilen FIN de sayılır
+        tracker.on_sequence(ms(200), true, &segment(FIN_ACK, 150, 0, 65535));
+        tracker.on_sequence(ms(300), true, &segment(FIN_ACK, 150, 0, 65535));
+        assert_eq!(tracker.forward.retransmissions, 1);
+    }
+
+    #[test]
+    fn zero_window_is_reported_once_per_episode() {
+        let mut tracker = TcpTracker::default();
+        assert!(!tracker.on_sequence(ms(0), false, &segment(ACK, 1, 0, 100)));
+        assert!(tracker.on_sequence(ms(1), false, &segment(ACK, 1, 0, 0)));
+        assert!(!tracker.on_sequence(ms(2), false, &segment(ACK, 1, 0, 0)));
+        assert!(!tracker.on_sequence(ms(3), false, &segment(ACK, 1, 0, 512)));
+        assert!(tracker.on_sequence(ms(4), false, &segment(ACK, 1, 0, 0)));
+        // SYN'deki pencere ölçeklenmemiştir, sayılmaz
+        assert!(!tracker.on_sequence(ms(5), true, &segment(SYN, 1, 0, 0)));
+
+        assert_eq!(tracker.reverse.zero_windows, 2);
+        assert_eq!(tracker.forward.zero_windows, 0);
+    }
+
+    #[test]
+    fn resume_keeps_sequence_tracking_and_clears_counters() {
+        let mut tracker = TcpTracker::default();
+        tracker.on_segment(ms(0), false, PSH_ACK);
+        tracker.on_sequence(ms(0), true, &data(1000, 100));
+        tracker.on_sequence(ms(100), true, &data(1000, 100));
+        assert_eq!(tracker.forward.retransmissions, 1);
+
+        let mut resumed = tracker.resume();
+        assert_eq!(resumed.state, TcpState::Established);
+        assert_eq!(resumed.forward.retransmissions, 0);
+        resumed.on_sequence(ms(200), true, &data(1000, 100));
+        assert_eq!(resumed.forward.retransmissions, 1);
+    }
+}
@@ -0,0 +1,251 @@
+//! Testlerde elle çerçeve ve küçük bir GeoIP veritabanı oluşturmak için
+//! yardımcılar.
+
+use std::net::{Ipv4Addr, Ipv6Addr};
+use std::sync::Arc;
+use std::time::Duration;
+
+use pnet::packet::tcp::TcpFlags;
+
+use crate::config::{CaptureBackend, CaptureMode, Config};
+use crate::flow::FlowConfig;
+use crate::interfaces::CaptureOptions;
+use crate::reassembly::ReassemblyConfig;
+
+pub const SYN: u8 = TcpFlags::SYN;
+pub const SYN_ACK: u8 = TcpFlags::SYN | TcpFlags::ACK;
+pub const ACK: u8 = TcpFlags::ACK;
+pub const PSH_ACK: u8 = TcpFlags::PSH | TcpFlags::ACK;
+pub const FIN_ACK: u8 = TcpFlags::FIN | TcpFlags::ACK;
+pub const RST: u8 = TcpFlags::RST;
+
+pub const CLIENT_MAC: [u8; 6] = [0x02, 0, 0, 0, 0, 0x01];
+pub const SERVER_MAC: [u8; 6] = [0x02, 0, 0, 0, 0, 0x02];
+
+/// Ortam değişkenlerinden bağımsız varsayılan yapılandırma
+pub fn config() -> Config {
+    Config {
+        mode: CaptureMode::Live,
+        backend: CaptureBackend::Pnet,
+        interfaces: Vec::new(),
+        capture: CaptureOptions::default(),
+        flows: FlowConfig::default(),
+        fragments: ReassemblyConfig::default(),
+        local_addrs: Vec::new(),
+        filter: None,
+        bpf_dump: false,
+        stats_interval: None,
+        metrics_addr: String::new(),
+        record: None,
+        dns: None,
+    }
+}
+
+pub fn ms(millis: u64) -> Duration {
+    Duration::from_millis(millis)
+}
+
+/// `payload` IPv4 ya da IPv6 paketi; tür ilk dört bitten seçilir.
+pub fn ethernet(src: [u8; 6], dst: [u8; 6], payload: &[u8]) -> Vec<u8> {
+    let ethertype: u16 = if payload.first().is_some_and(|byte| byte >> 4 == 6) { 0x86dd } else { 0x0800 };
+    let mut frame = Vec::with_capacity(14 + payload.len());
+    frame.extend_from_slice(&dst);
+    frame.extend_from_slice(&src);
+    frame.extend_from_slice(&ethertype.to_be_bytes());
+    frame.extend_from_slice(payload);
+    frame
+}
+
+pub fn ipv4(src: Ipv4Addr, dst: Ipv4Addr, protocol: u8, payload: &[u8]) -> Vec<u8> {
+    ipv4_fragment(src, dst, protocol, 0, 0, false, payload)
+}
+
+/// `offset` bayt cinsindendir ve 8'in katı olmalı.
+pub fn ipv4_fragment(
+    src: Ipv4Addr,
+    dst: Ipv4Addr,
+    protocol: u8,
+    id: u16,
+    offset: usize,
+    more: bool,
+    payload: &[u8],
+) -> Vec<u8> {
+    let total_len = (20 + payload.len()) as u16;
+    let flags_offset = (offset / 8) as u16 | if more { 0x2000 } else { 0 };
+    let mut packet = Vec::with_capacity(total_len as usize);
+    packet.extend_from_slice(&[0x45, 0]);
+    packet.extend_from_slice(&total_len.to_be_bytes());
+    packet.extend_from_slice(&id.to_be_bytes());
+    packet.extend_from_slice(&flags_offset.to_be_bytes());
+    packet.extend_from_slice(&[64, protocol, 0, 0]);
+    packet.extend_from_slice(&src.octets());
+    packet.extend_from_slice(&dst.octets());
+
+    let mut sum: u32 = packet.chunks(2).map(|word| u16::from_be_bytes([word[0], word[1]]) as u32).sum();
+    while sum > 0xffff {
+        sum = (sum & 0xffff) + (sum >> 16);
+    }
+    packet[10..12].copy_from_slice(&(!(sum as u16)).to_be_bytes());
+
+    packet.extend_from_slice(payload);
+    packet
+}
+
+pub fn ipv6(src: Ipv6Addr, dst: Ipv6Addr, next_header: u8, payload: &[u8]) -> Vec<u8> {
+    let mut packet = Vec::with_capacity(40 + payload.len());
+    packet.extend_from_slice(&[0x60, 0, 0, 0]);
+    packet.extend_from_slice(&(payload.len() as u16).to_be_bytes());
+    packet.extend_from_slice(&[next_header, 64]);
+    packet.extend_from_slice(&src.octets());
+    packet.extend_from_slice(&dst.octets());
+    packet.extend_from_slice(payload);
+    packet
+}
+
+pub fn tcp(src_port: u16, dst_port: u16, seq: u32, ack: u32, flags: u8, window: u16, payload: &[u8]) -> Vec<u8> {
+    let mut segment = Vec::with_capacity(20 + payload.len());
+    segment.extend_from_slice(&src_port.to_be_bytes());
+    segment.extend_from_slice(&dst_port.to_be_bytes());
+    segment.extend_from_slice(&seq.to_be_bytes());
+    segment.extend_from_slice(&ack.to_be_bytes());
+    segment.extend_from_slice(&[5 << 4, flags]);
+    segment.extend_from_slice(&window.to_be_bytes());
+    segment.extend_from_slice(&[0, 0, 0, 0]);
+    segment.extend_from_slice(payload);
+    segment
+}
+
+pub fn udp(src_port: u16, dst_port: u16, payload: &[u8]) -> Vec<u8> {
+    let mut datagram = Vec::with_capacity(8 + payload.len());
+    datagram.extend_from_slice(&src_port.to_be_bytes());
+    datagram.extend_from_slice(&dst_port.to_be_bytes());
+    datagram.extend_from_slice(&(8 + payload.len() as u16).to_be_bytes());
+    datagram.extend_from_slice(&[0, 0]);
+    datagram.extend_from_slice(payload);
+    datagram
+}
+
+/// Sıkıştırılmamış alan adı
+pub fn dns_name(name: &str) -> Vec<u8> {
+    let mut out = Vec::new();
+    for label in name.split('.').filter(|label| !label.is_empty()) {
+        out.push(label.len() as u8);
+        out.extend_from_slice(label.as_bytes());
+    }
+    out.push(0);
+    out
+}
+
+/// `name` ham (sıkıştırılmış olabilen) addır; sınıf her zaman IN.
+pub fn dns_record(name: &[u8], record_type: u16, ttl: u32, rdata: &[u8]) -> Vec<u8> {
+    let mut out = name.to_vec();
+    out.extend_from_slice(&record_type.to_be_bytes());
+    out.extend_from_slice(&1u16.to_be_bytes());
+    out.extend_from_slice(&ttl.to_be_bytes());
+    out.extend_from_slice(&(rdata.len() as u16).to_be_bytes());
+    out.extend_from_slice(rdata);
+    out
+}
+
+/// Tek sorulu DNS mesajı; sorudaki ad 12. bayttan başlar, yani yanıtlarda
+/// `[0xc0, 12]` işaretçisiyle gösterilebilir.
+pub fn dns_message(flags: u16, question: &str, answers: &[Vec<u8>]) -> Vec<u8> {
+    let mut out = vec![0x12, 0x34];
+    out.extend_from_slice(&flags.to_be_bytes());
+    out.extend_from_slice(&1u16.to_be_bytes());
+    out.extend_from_slice(&(answers.len() as u16).to_be_bytes());
+    out.extend_from_slice(&[0, 0, 0, 0]);
+    out.extend(dns_name(question));
+    out.extend_from_slice(&[0, 1, 0, 1]);
+    for answer in answers {
+        out.extend_from_slice(answer);
+    }
+    out
+}
+
+// MaxMind DB biçimi: maxmind.github.io/MaxMind-DB
+fn control(kind: u8, size: usize) -> Vec<u8> {
+    if kind <= 7 {
+        vec![(kind << 5) | size as u8]
+    } else {
+        vec![size as u8, kind - 7]
+    }
+}
+
+fn mmdb_string(value: &str) -> Vec<u8> {
+    let mut out = control(2, value.len());
+    out.extend_from_slice(value.as_bytes());
+    out
+}
+
+fn mmdb_double(value: f64) -> Vec<u8> {
+    let mut out = control(3, 8);
+    out.extend_from_slice(&value.to_be_bytes());
+    out
+}
+
+fn mmdb_uint(kind: u8, value: u64) -> Vec<u8> {
+    let bytes = value.to_be_bytes();
+    let skip = bytes.iter().take_while(|byte| **byte == 0).count();
+    let mut out = control(kind, bytes.len() - skip);
+    out.extend_from_slice(&bytes[skip..]);
+    out
+}
+
+fn mmdb_map(entries: &[(&str, Vec<u8>)]) -> Vec<u8> {
+    let mut out = control(7, entries.len());
+    for (key, value) in entries {
+        out.extend(mmdb_string(key));
+        out.extend_from_slice(value);
+    }
+    out
+}
+
+fn mmdb_city(latitude: f64, longitude: f64, country: &str) -> Vec<u8> {
+    mmdb_map(&[
+        ("location", mmdb_map(&[("latitude", mmdb_double(latitude)), ("longitude", mmdb_double(longitude))])),
+        ("country", mmdb_map(&[("iso_code", mmdb_string(country))])),
+    ])
+}
+
+pub const US: (f64, f64) = (37.751, -97.822);
+pub const DE: (f64, f64) = (51.2993, 9.491);
+
+/// IPv6 ağacı: IPv4 adreslerinden 0.0.0.0-127.255.255.255 ve tüm global
+/// IPv6 adresleri ABD'de, 128.0.0.0-255.255.255.255 Almanya'da görünür.
+pub fn geoip_reader() -> Arc<maxminddb::Reader<Vec<u8>>> {
+    let us = mmdb_city(US.0, US.1, "US");
+    let de = mmdb_city(DE.0, DE.1, "DE");
+    // IPv4 alt ağacına (::/96) giden 96 düğüm ve adresin ilk bitine göre
+    // ayıran bir düğüm
+    let node_count = 97usize;
+    let us_record = node_count + 16;
+    let de_record = node_count + 16 + us.len();
+
+    let mut db = Vec::new();
+    for node in 0..node_count {
+        let (left, right) = if node < 96 { (node + 1, us_record) } else { (us_record, de_record) };
+        for record in [left, right] {
+            db.extend_from_slice(&(record as u32).to_be_bytes()[1..]);
+        }
+    }
+    db.extend_from_slice(&[0; 16]);
+    db.extend(us);
+    db.extend(de);
+    db.extend_from_slice(b"\xab\xcd\xefMaxMind.com");
+    let mut languages = control(11, 1);
+    languages.extend(mmdb_string("en"));
+    db.extend(mmdb_map(&[
+        ("node_count", mmdb_uint(6, node_count as u64)),
+        ("record_size", mmdb_uint(5, 24)),
+        ("ip_version", mmdb_uint(5, 6)),
+        ("database_type", mmdb_string("GeoLite2-City")),
+        ("languages", languages),
+        ("binary_format_major_version", mmdb_uint(5, 2)),
+        ("binary_format_minor_version", mmdb_uint(5, 0)),
+        ("build_epoch", mmdb_uint(9, 1_700_000_000)),
+        ("description", mmdb_map(&[("en", mmdb_string("test"))])),
+    ]));
+
+    Arc::new(maxminddb::Reader::from_source(db).expect("test GeoIP veritabanı"))
+}
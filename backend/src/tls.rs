@@ -0,0 +1,446 @@
+use pnet::packet::tcp::TcpFlags;
+
+use crate::decode::TcpHeader;
+use crate::stream::StreamBuffer;
+
+const RECORD_HEADER_LEN: usize = 5;
+const CONTENT_HANDSHAKE: u8 = 22;
+const HANDSHAKE_CLIENT_HELLO: u8 = 1;
+// Kayıt en fazla 2^14 bayt düz metin taşır; sıkıştırma payıyla birlikte
+const MAX_RECORD_LEN: usize = (1 << 14) + 2048;
+const EXT_SERVER_NAME: u16 = 0;
+const EXT_ALPN: u16 = 16;
+const EXT_SUPPORTED_VERSIONS: u16 = 43;
+const SERVER_NAME_HOST: u8 = 0;
+// Büyük anahtar paylaşımlarıyla ClientHello birkaç segmente yayılabilir;
+// bundan uzun veride ClientHello aranmaz
+const MAX_CLIENT_HELLO_LEN: usize = 32 * 1024;
+
+/// İstemcinin bağlantı başında sunduğu TLS parametreleri
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ClientHello {
+    /// Sunulan en yüksek sürüm; TLS 1.3'te `supported_versions`
+    /// uzantısından, yoksa ClientHello başlığından
+    pub version: u16,
+    /// Server Name Indication
+    pub sni: Option<String>,
+    pub alpn: Vec<String>,
+    /// Tercih sırasıyla; GREASE değerleri çıkarılmış
+    pub cipher_suites: Vec<u16>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Parse {
+    Complete(ClientHello),
+    /// Veri ClientHello ile başlıyor ama henüz tamamlanmadı
+    Incomplete,
+    /// Veri TLS el sıkışmasıyla başlamıyor
+    NotTls,
+    Malformed,
+}
+
+/// GREASE (RFC 8701) değerleri gerçek bir seçeneği göstermez
+fn is_grease(value: u16) -> bool {
+    value & 0x0f0f == 0x0a0a && value >> 8 == value & 0xff
+}
+
+pub fn version_name(version: u16) -> String {
+    match version {
+        0x0300 => "SSL 3.0".to_string(),
+        0x0301 => "TLS 1.0".to_string(),
+        0x0302 => "TLS 1.1".to_string(),
+        0x0303 => "TLS 1.2".to_string(),
+        0x0304 => "TLS 1.3".to_string(),
+        other => format!("0x{:04x}", other),
+    }
+}
+
+/// Yaygın şifre takımlarının IANA adları; diğerleri onaltılık kodla
+pub fn cipher_suite_name(suite: u16) -> String {
+    let name = match suite {
+        0x1301 => "TLS_AES_128_GCM_SHA256",
+        0x1302 => "TLS_AES_256_GCM_SHA384",
+        0x1303 => "TLS_CHACHA20_POLY1305_SHA256",
+        0x1304 => "TLS_AES_128_CCM_SHA256",
+        0x1305 => "TLS_AES_128_CCM_8_SHA256",
+        0xc02b => "TLS_ECDHE_ECDSA_WITH_AES_128_GCM_SHA256",
+        0xc02c => "TLS_ECDHE_ECDSA_WITH_AES_256_GCM_SHA384",
+        0xc02f => "TLS_ECDHE_RSA_WITH_AES_128_GCM_SHA256",
+        0xc030 => "TLS_ECDHE_RSA_WITH_AES_256_GCM_SHA384",
+        0xcca8 => "TLS_ECDHE_RSA_WITH_CHACHA20_POLY1305_SHA256",
+        0xcca9 => "TLS_ECDHE_ECDSA_WITH_CHACHA20_POLY1305_SHA256",
+        0xc009 => "TLS_ECDHE_ECDSA_WITH_AES_128_CBC_SHA",
+        0xc00a => "TLS_ECDHE_ECDSA_WITH_AES_256_CBC_SHA",
+        0xc013 => "TLS_ECDHE_RSA_WITH_AES_128_CBC_SHA",
+        0xc014 => "TLS_ECDHE_RSA_WITH_AES_256_CBC_SHA",
+        0xc023 => "TLS_ECDHE_ECDSA_WITH_AES_128_CBC_SHA256",
+        0xc024 => "TLS_ECDHE_ECDSA_WITH_AES_256_CBC_SHA384",
+        0xc027 => "TLS_ECDHE_RSA_WITH_AES_128_CBC_SHA256",
+        0xc028 => "TLS_ECDHE_RSA_WITH_AES_256_CBC_SHA384",
+        0x009e => "TLS_DHE_RSA_WITH_AES_128_GCM_SHA256",
+        0x0067 => "TLS_DHE_RSA_WITH_AES_128_CBC_SHA256",
+        0x006b => "TLS_DHE_RSA_WITH_AES_256_CBC_SHA256",
+        0x009f => "TLS_DHE_RSA_WITH_AES_256_GCM_SHA384",
+        0xccaa => "TLS_DHE_RSA_WITH_CHACHA20_POLY1305_SHA256",
+        0x009c => "TLS_RSA_WITH_AES_128_GCM_SHA256",
+        0x009d => "TLS_RSA_WITH_AES_256_GCM_SHA384",
+        0x003c => "TLS_RSA_WITH_AES_128_CBC_SHA256",
+        0x003d => "TLS_RSA_WITH_AES_256_CBC_SHA256",
+        0x002f => "TLS_RSA_WITH_AES_128_CBC_SHA",
+        0x0035 => "TLS_RSA_WITH_AES_256_CBC_SHA",
+        0x000a => "TLS_RSA_WITH_3DES_EDE_CBC_SHA",
+        0x00ff => "TLS_EMPTY_RENEGOTIATION_INFO_SCSV",
+        0x5600 => "TLS_FALLBACK_SCSV",
+        other => return format!("0x{:04x}", other),
+    };
+    name.to_string()
+}
+
+/// Sıradaki alanları okuyan imleç; veri yetmezse `None`
+struct Reader<'a> {
+    data: &'a [u8],
+}
+
+impl<'a> Reader<'a> {
+    fn bytes(&mut self, len: usize) -> Option<&'a [u8]> {
+        if len > self.data.len() {
+            return None;
+        }
+        let (head, rest) = self.data.split_at(len);
+        self.data = rest;
+        Some(head)
+    }
+
+    fn u8(&mut self) -> Option<u8> {
+        self.bytes(1).map(|bytes| bytes[0])
+    }
+
+    fn u16(&mut self) -> Option<u16> {
+        self.bytes(2).map(|bytes| u16::from_be_bytes([bytes[0], bytes[1]]))
+    }
+
+    /// `prefix` baytlık uzunlukla başlayan alan
+    fn vector(&mut self, prefix: usize) -> Option<Reader<'a>> {
+        let len = self.bytes(prefix)?.iter().fold(0usize, |len, byte| len << 8 | *byte as usize);
+        self.bytes(len).map(|data| Reader { data })
+    }
+
+    fn is_empty(&self) -> bool {
+        self.data.is_empty()
+    }
+}
+
+/// Bağlantının başından itibaren istemci verisinde ClientHello arar. El
+/// sıkışma mesajı birden çok TLS kaydına bölünmüş olabilir.
+pub fn parse_client_hello(data: &[u8]) -> Parse {
+    let mut handshake = Vec::new();
+    let mut records = Reader { data };
+
+    loop {
+        let header = match records.bytes(RECORD_HEADER_LEN) {
+            Some(header) => header,
+            // Eksik kayıt başlığı da olsa ilk bayt TLS olup olmadığını söyler
+            None if handshake.is_empty() && records.data.first().is_some_and(|byte| *byte != CONTENT_HANDSHAKE) => {
+                return Parse::NotTls
+            }
+            None => return Parse::Incomplete,
+        };
+        let len = u16::from_be_bytes([header[3], header[4]]) as usize;
+        if header[0] != CONTENT_HANDSHAKE || header[1] != 3 {
+            return if handshake.is_empty() { Parse::NotTls } else { Parse::Malformed };
+        }
+        if len == 0 || len > MAX_RECORD_LEN {
+            return Parse::Malformed;
+        }
+        let fragment = match records.bytes(len) {
+            Some(fragment) => fragment,
+            // Kaydın geri kalanı sonraki segmentlerde
+            None => {
+                handshake.extend_from_slice(records.data);
+                return match handshake.first() {
+                    Some(&kind) if kind != HANDSHAKE_CLIENT_HELLO => Parse::NotTls,
+                    _ => Parse::Incomplete,
+                };
+            }
+        };
+        handshake.extend_from_slice(fragment);
+
+        if handshake[0] != HANDSHAKE_CLIENT_HELLO {
+            return Parse::NotTls;
+        }
+        if handshake.len() >= 4 {
+            let body_len = u32::from_be_bytes([0, handshake[1], handshake[2], handshake[3]]) as usize;
+            if handshake.len() >= 4 + body_len {
+                return match parse_body(&handshake[4..4 + body_len]) {
+                    Some(hello) => Parse::Complete(hello),
+                    None => Parse::Malformed,
+                };
+            }
+        }
+    }
+}
+
+fn parse_body(body: &[u8]) -> Option<ClientHello> {
+    let mut body = Reader { data: body };
+    let legacy_version = body.u16()?;
+    body.bytes(32)?;
+    body.vector(1)?;
+
+    let mut suites = body.vector(2)?;
+    let mut cipher_suites = Vec::new();
+    while !suites.is_empty() {
+        let suite = suites.u16()?;
+        if !is_grease(suite) {
+            cipher_suites.push(suite);
+        }
+    }
+    body.vector(1)?;
+
+    let mut hello = ClientHello { version: legacy_version, sni: None, alpn: Vec::new(), cipher_suites };
+    // SSL 3.0 istemcileri uzantı göndermeyebilir
+    if body.is_empty() {
+        return Some(hello);
+    }
+
+    let mut extensions = body.vector(2)?;
+    while !extensions.is_empty() {
+        let kind = extensions.u16()?;
+        let mut data = extensions.vector(2)?;
+        match kind {
+            EXT_SERVER_NAME => {
+                let mut names = data.vector(2)?;
+                while !names.is_empty() {
+                    let name_type = names.u8()?;
+                    let name = names.vector(2)?;
+                    if name_type == SERVER_NAME_HOST && hello.sni.is_none() {
+                        hello.sni = Some(String::from_utf8_lossy(name.data).to_ascii_lowercase());
+                    }
+                }
+            }
+            EXT_ALPN => {
+                let mut protocols = data.vector(2)?;
+                while !protocols.is_empty() {
+                    let protocol = protocols.vector(1)?;
+                    hello.alpn.push(String::from_utf8_lossy(protocol.data).into_owned());
+                }
+            }
+            EXT_SUPPORTED_VERSIONS => {
+                let mut versions = data.vector(1)?;
+                while !versions.is_empty() {
+                    let version = versions.u16()?;
+                    if !is_grease(version) && version > hello.version {
+                        hello.version = version;
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+    Some(hello)
+}
+
+/// Akışı başlatan tarafın ilk verisini ClientHello tamamlanana ya da TLS
+/// olmadığı anlaşılana kadar biriktirir.
+pub enum HelloReader {
+    /// Veri bekleniyor; SYN görüldüyse verinin başlayacağı sıra numarası
+    Waiting(Option<u32>),
+    Reading(Box<StreamBuffer>),
+    Done,
+}
+
+impl Default for HelloReader {
+    fn default() -> Self {
+        HelloReader::Waiting(None)
+    }
+}
+
+impl HelloReader {
+    pub fn is_done(&self) -> bool {
+        matches!(self, HelloReader::Done)
+    }
+
+    /// Başlatan taraftan gelen segmenti ekler. `Complete`, `NotTls` ya da
+    /// `Malformed` döndükten sonra okuma biter.
+    pub fn push(&mut self, tcp: &TcpHeader, payload: &[u8]) -> Parse {
+        // SYN bir sıra numarası tüketir; TCP Fast Open'da SYN veri de taşır
+        let syn = tcp.flags & TcpFlags::SYN != 0;
+        let sequence = if syn { tcp.sequence.wrapping_add(1) } else { tcp.sequence };
+        if syn {
+            if let HelloReader::Waiting(start) = self {
+                *start = Some(sequence);
+            }
+        }
+        if payload.is_empty() {
+            return Parse::Incomplete;
+        }
+
+        if let HelloReader::Waiting(start) = *self {
+            // SYN görülmediyse yakalama ilk veriden başlamış sayılır
+            let buffer = StreamBuffer::new(start.unwrap_or(sequence), MAX_CLIENT_HELLO_LEN);
+            *self = HelloReader::Reading(Box::new(buffer));
+        }
+        let buffer = match self {
+            HelloReader::Reading(buffer) => buffer,
+            HelloReader::Waiting(_) | HelloReader::Done => return Parse::NotTls,
+        };
+        if !buffer.push(sequence, payload) {
+            return Parse::Incomplete;
+        }
+
+        let parse = parse_client_hello(buffer.data());
+        if parse != Parse::Incomplete || buffer.data().len() >= MAX_CLIENT_HELLO_LEN {
+            *self = HelloReader::Done;
+        }
+        parse
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn vector(prefix: usize, data: &[u8]) -> Vec<u8> {
+        let mut out = data.len().to_be_bytes()[8 - prefix..].to_vec();
+        out.extend_from_slice(data);
+        out
+    }
+
+    fn extension(kind: u16, data: &[u8]) -> Vec<u8> {
+        [&kind.to_be_bytes()[..], &vector(2, data)].concat()
+    }
+
+    /// Tür ve uzunluk başlığıyla ClientHello el sıkışma mesajı
+    fn client_hello(version: u16, suites: &[u16], extensions: &[Vec<u8>]) -> Vec<u8> {
+        let mut body = version.to_be_bytes().to_vec();
+        body.extend_from_slice(&[0x11; 32]);
+        body.extend(vector(1, &[0x22; 32]));
+        body.extend(vector(2, &suites.iter().flat_map(|suite| suite.to_be_bytes()).collect::<Vec<_>>()));
+        body.extend(vector(1, &[0]));
+        if !extensions.is_empty() {
+            body.extend(vector(2, &extensions.concat()));
+        }
+        let mut message = vec![HANDSHAKE_CLIENT_HELLO];
+        message.extend_from_slice(&(body.len() as u32).to_be_bytes()[1..]);
+        message.extend(body);
+        message
+    }
+
+    /// El sıkışma mesajını en fazla `chunk` baytlık kayıtlara böler
+    fn records(handshake: &[u8], chunk: usize) -> Vec<u8> {
+        handshake
+            .chunks(chunk)
+            .flat_map(|fragment| [&[CONTENT_HANDSHAKE, 3, 1][..], &vector(2, fragment)].concat())
+            .collect()
+    }
+
+    fn browser_hello() -> Vec<u8> {
+        let sni = vector(2, &[&[SERVER_NAME_HOST][..], &vector(2, b"Www.Example.COM")].concat());
+        let alpn = vector(2, &[vector(1, b"h2"), vector(1, b"http/1.1")].concat());
+        let versions = vector(1, &[0x3a, 0x3a, 0x03, 0x04, 0x03, 0x03]);
+        client_hello(
+            0x0303,
+            &[0x0a0a, 0x1301, 0x1302, 0xc02f],
+            &[
+                extension(0xdada, &[]),
+                extension(EXT_SERVER_NAME, &sni),
+                extension(EXT_ALPN, &alpn),
+                extension(EXT_SUPPORTED_VERSIONS, &versions),
+            ],
+        )
+    }
+
+    fn browser_parse() -> Parse {
+        Parse::Complete(ClientHello {
+            version: 0x0304,
+            sni: Some("www.example.com".into()),
+            alpn: vec!["h2".into(), "http/1.1".into()],
+            cipher_suites: vec![0x1301, 0x1302, 0xc02f],
+        })
+    }
+
+    fn segment(flags: u8, sequence: u32, payload: &[u8]) -> TcpHeader {
+        TcpHeader { flags, sequence, window: 65535, payload_len: payload.len() }
+    }
+
+    #[test]
+    fn client_hello_extensions_are_parsed_without_grease() {
+        assert_eq!(parse_client_hello(&records(&browser_hello(), 1 << 14)), browser_parse());
+        assert_eq!(version_name(0x0304), "TLS 1.3");
+        assert_eq!(version_name(0x7f1c), "0x7f1c");
+        assert_eq!(cipher_suite_name(0x1301), "TLS_AES_128_GCM_SHA256");
+        assert_eq!(cipher_suite_name(0x0a0a), "0x0a0a");
+    }
+
+    #[test]
+    fn hello_without_extensions_keeps_legacy_version() {
+        let hello = client_hello(0x0301, &[0x002f, 0x0035], &[]);
+        assert_eq!(
+            parse_client_hello(&records(&hello, 1 << 14)),
+            Parse::Complete(ClientHello { version: 0x0301, sni: None, alpn: Vec::new(), cipher_suites: vec![0x002f, 0x0035] })
+        );
+    }
+
+    #[test]
+    fn handshake_split_across_records_is_joined() {
+        let data = records(&browser_hello(), 40);
+        assert!(data.len() > browser_hello().len() + 2 * RECORD_HEADER_LEN);
+        assert_eq!(parse_client_hello(&data), browser_parse());
+        // Verinin her öneki henüz tamamlanmamış bir ClientHello'dur
+        for len in 1..data.len() {
+            assert_eq!(parse_client_hello(&data[..len]), Parse::Incomplete, "{} bayt", len);
+        }
+    }
+
+    #[test]
+    fn other_protocols_and_broken_hellos_are_rejected() {
+        assert_eq!(parse_client_hello(b"GET / HTTP/1.1\r\n"), Parse::NotTls);
+        assert_eq!(parse_client_hello(b"G"), Parse::NotTls);
+        // Uygulama verisi kaydı ve ServerHello
+        assert_eq!(parse_client_hello(&[23, 3, 3, 0, 1, 0]), Parse::NotTls);
+        assert_eq!(parse_client_hello(&records(&[2, 0, 0, 0], 1 << 14)), Parse::NotTls);
+
+        assert_eq!(parse_client_hello(&[CONTENT_HANDSHAKE, 3, 1, 0, 0]), Parse::Malformed);
+        // El sıkışma ortasında başka türde kayıt
+        let hello = browser_hello();
+        let mut data = records(&hello[..20], 1 << 14);
+        data.extend_from_slice(&[23, 3, 3, 0, 1, 0]);
+        assert_eq!(parse_client_hello(&data), Parse::Malformed);
+        // Şifre takımı listesi gövdeden uzun
+        let mut hello = client_hello(0x0303, &[0x1301], &[]);
+        let suites = 4 + 2 + 32 + 33;
+        hello[suites..suites + 2].copy_from_slice(&200u16.to_be_bytes());
+        assert_eq!(parse_client_hello(&records(&hello, 1 << 14)), Parse::Malformed);
+    }
+
+    #[test]
+    fn reader_reorders_segments_and_skips_retransmissions() {
+        let data = records(&browser_hello(), 1 << 14);
+        let (first, rest) = data.split_at(50);
+        let (second, third) = rest.split_at(60);
+        let mut reader = HelloReader::default();
+
+        assert_eq!(reader.push(&segment(TcpFlags::SYN, 999, &[]), &[]), Parse::Incomplete);
+        assert_eq!(reader.push(&segment(TcpFlags::ACK, 1110, third), third), Parse::Incomplete);
+        assert_eq!(reader.push(&segment(TcpFlags::ACK, 1050, second), second), Parse::Incomplete);
+        assert_eq!(reader.push(&segment(TcpFlags::ACK, 1050, second), second), Parse::Incomplete);
+        assert!(!reader.is_done());
+        assert_eq!(reader.push(&segment(TcpFlags::ACK, 1000, first), first), browser_parse());
+        assert!(reader.is_done());
+        assert_eq!(reader.push(&segment(TcpFlags::ACK, 1000, first), first), Parse::NotTls);
+    }
+
+    #[test]
+    fn reader_without_syn_starts_at_first_data() {
+        let data = records(&browser_hello(), 1 << 14);
+        let first = &data[..100];
+        let mut reader = HelloReader::default();
+        assert_eq!(reader.push(&segment(TcpFlags::ACK, 7000, first), first), Parse::Incomplete);
+        // Yeniden iletim eski veriyle yeni veriyi birlikte taşıyabilir
+        assert_eq!(reader.push(&segment(TcpFlags::ACK, 7050, &data[50..]), &data[50..]), browser_parse());
+
+        let ssh = b"SSH-2.0-OpenSSH_9.6\r\n";
+        let mut reader = HelloReader::default();
+        assert_eq!(reader.push(&segment(TcpFlags::ACK, 7000, ssh), ssh), Parse::NotTls);
+        assert!(reader.is_done());
+    }
+}
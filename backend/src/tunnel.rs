@@ -0,0 +1,367 @@
+use std::net::IpAddr;
+
+use pnet::packet::ip::IpNextHeaderProtocols;
+
+use crate::decode::{IpPacket, NetworkLayer};
+
+// Linux çekirdeğinin eski varsayılanı 8472; IANA portu 4789
+const VXLAN_PORTS: [u16; 2] = [4789, 8472];
+const GENEVE_PORT: u16 = 6081;
+
+const ETHERTYPE_IPV4: u16 = 0x0800;
+const ETHERTYPE_IPV6: u16 = 0x86dd;
+// Saydam Ethernet köprüleme (GRE/GENEVE içinde Ethernet çerçevesi)
+const ETHERTYPE_TEB: u16 = 0x6558;
+const ETHERTYPE_ERSPAN_II: u16 = 0x88be;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TunnelKind {
+    Gre,
+    /// Cisco/ayna port oturumları (GRE üzerinden, tip II)
+    Erspan,
+    Vxlan,
+    Geneve,
+    IpInIp,
+    SixInFour,
+}
+
+impl TunnelKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            TunnelKind::Gre => "gre",
+            TunnelKind::Erspan => "erspan",
+            TunnelKind::Vxlan => "vxlan",
+            TunnelKind::Geneve => "geneve",
+            TunnelKind::IpInIp => "ip_in_ip",
+            TunnelKind::SixInFour => "6in4",
+        }
+    }
+
+    /// Tünel kimliğinin olaylardaki alan adı
+    pub fn id_field(&self) -> &'static str {
+        match self {
+            TunnelKind::Gre => "key",
+            TunnelKind::Erspan => "session_id",
+            _ => "vni",
+        }
+    }
+}
+
+/// Dış başlıktan kalan tünel bilgisi
+#[derive(Debug, Clone, Copy)]
+pub struct Tunnel {
+    pub kind: TunnelKind,
+    pub src: IpAddr,
+    pub dst: IpAddr,
+    /// GRE anahtarı, ERSPAN oturumu ya da VXLAN/GENEVE VNI
+    pub id: Option<u32>,
+}
+
+/// Tünelin taşıdığı iç paket
+pub enum Inner<'a> {
+    Ethernet(&'a [u8]),
+    Network(NetworkLayer<'a>),
+}
+
+/// Paket bilinen bir tünel protokolü taşıyorsa tünel bilgisini ve iç paketi
+/// döndürür.
+pub fn decapsulate<'a>(ip: &IpPacket<'a>) -> Option<(Tunnel, Inner<'a>)> {
+    let payload = ip.payload;
+    let (kind, id, inner) = match ip.protocol {
+        IpNextHeaderProtocols::Ipv4 => (TunnelKind::IpInIp, None, Inner::Network(NetworkLayer::Ipv4(payload))),
+        IpNextHeaderProtocols::Ipv6 => {
+            let kind = if ip.src.is_ipv4() { TunnelKind::SixInFour } else { TunnelKind::IpInIp };
+            (kind, None, Inner::Network(NetworkLayer::Ipv6(payload)))
+        }
+        IpNextHeaderProtocols::Gre => decode_gre(payload)?,
+        IpNextHeaderProtocols::Udp => decode_udp_tunnel(payload)?,
+        _ => return None,
+    };
+
+    Some((Tunnel { kind, src: ip.src, dst: ip.dst, id }, inner))
+}
+
+fn decode_gre(data: &[u8]) -> Option<(TunnelKind, Option<u32>, Inner<'_>)> {
+    let flags = u16::from_be_bytes([*data.first()?, *data.get(1)?]);
+    // Yalnızca sürüm 0 (sürüm 1 PPTP'nin GRE'si); RFC 1701'in yönlendirme,
+    // katı kaynak yönlendirme ve özyineleme alanları desteklenmez
+    if flags & 0x4fff != 0 {
+        return None;
+    }
+    let protocol = u16::from_be_bytes([*data.get(2)?, *data.get(3)?]);
+
+    let mut offset = 4;
+    if flags & 0x8000 != 0 {
+        // Sağlama toplamı ve ayrılmış alan
+        offset += 4;
+    }
+    let mut key = None;
+    if flags & 0x2000 != 0 {
+        let bytes = data.get(offset..offset + 4)?;
+        key = Some(u32::from_be_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]));
+        offset += 4;
+    }
+    if flags & 0x1000 != 0 {
+        // Sıra numarası
+        offset += 4;
+    }
+    let payload = data.get(offset..)?;
+
+    match protocol {
+        ETHERTYPE_IPV4 => Some((TunnelKind::Gre, key, Inner::Network(NetworkLayer::Ipv4(payload)))),
+        ETHERTYPE_IPV6 => Some((TunnelKind::Gre, key, Inner::Network(NetworkLayer::Ipv6(payload)))),
+        ETHERTYPE_TEB => Some((TunnelKind::Gre, key, Inner::Ethernet(payload))),
+        ETHERTYPE_ERSPAN_II => {
+            let header = payload.get(..8)?;
+            let session_id = u16::from_be_bytes([header[2], header[3]]) & 0x03ff;
+            Some((TunnelKind::Erspan, Some(session_id as u32), Inner::Ethernet(&payload[8..])))
+        }
+        _ => None,
+    }
+}
+
+fn decode_udp_tunnel(data: &[u8]) -> Option<(TunnelKind, Option<u32>, Inner<'_>)> {
+    let dst_port = u16::from_be_bytes([*data.get(2)?, *data.get(3)?]);
+    let payload = data.get(8..)?;
+
+    if VXLAN_PORTS.contains(&dst_port) {
+        let header = payload.get(..8)?;
+        // VNI geçerli bayrağı
+        if header[0] & 0x08 == 0 {
+            return None;
+        }
+        let vni = u32::from_be_bytes([header[4], header[5], header[6], header[7]]) >> 8;
+        return Some((TunnelKind::Vxlan, Some(vni), Inner::Ethernet(&payload[8..])));
+    }
+
+    if dst_port == GENEVE_PORT {
+        let header = payload.get(..8)?;
+        if header[0] >> 6 != 0 {
+            return None;
+        }
+        let header_len = 8 + (header[0] & 0x3f) as usize * 4;
+        let protocol = u16::from_be_bytes([header[2], header[3]]);
+        let vni = u32::from_be_bytes([header[4], header[5], header[6], header[7]]) >> 8;
+        let inner = payload.get(header_len..)?;
+        let inner = match protocol {
+            ETHERTYPE_TEB => Inner::Ethernet(inner),
+            ETHERTYPE_IPV4 => Inner::Network(NetworkLayer::Ipv4(inner)),
+            ETHERTYPE_IPV6 => Inner::Network(NetworkLayer::Ipv6(inner)),
+            _ => return None,
+        };
+        return Some((TunnelKind::Geneve, Some(vni), inner));
+    }
+
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::decode::{self, IpPacket};
+    use crate::testutil::*;
+    use std::net::{Ipv4Addr, Ipv6Addr};
+
+    const OUTER_SRC: Ipv4Addr = Ipv4Addr::new(198, 51, 100, 1);
+    const OUTER_DST: Ipv4Addr = Ipv4Addr::new(198, 51, 100, 2);
+
+    fn inner_v4() -> Vec<u8> {
+        ipv4(Ipv4Addr::new(10, 1, 0, 1), Ipv4Addr::new(10, 2, 0, 1), 17, &udp(40000, 443, &[7; 16]))
+    }
+
+    fn inner_frame() -> Vec<u8> {
+        ethernet(CLIENT_MAC, SERVER_MAC, &inner_v4())
+    }
+
+    fn outer(protocol: u8, payload: &[u8]) -> Vec<u8> {
+        ipv4(OUTER_SRC, OUTER_DST, protocol, payload)
+    }
+
+    fn decapsulate_v4(packet: &[u8]) -> Option<(Tunnel, Inner<'_>)> {
+        let ip: IpPacket = decode::decode_ip(NetworkLayer::Ipv4(packet)).unwrap();
+        decapsulate(&ip)
+    }
+
+    fn gre(flags: u16, protocol: u16, fields: &[u32], payload: &[u8]) -> Vec<u8> {
+        let mut header = flags.to_be_bytes().to_vec();
+        header.extend_from_slice(&protocol.to_be_bytes());
+        for field in fields {
+            header.extend_from_slice(&field.to_be_bytes());
+        }
+        header.extend_from_slice(payload);
+        header
+    }
+
+    fn vxlan(vni: u32, frame: &[u8]) -> Vec<u8> {
+        let mut header = vec![0x08, 0, 0, 0];
+        header.extend_from_slice(&(vni << 8).to_be_bytes());
+        header.extend_from_slice(frame);
+        header
+    }
+
+    fn ethernet_payload<'a>(inner: Inner<'a>) -> &'a [u8] {
+        match inner {
+            Inner::Ethernet(data) => data,
+            Inner::Network(_) => panic!("Ethernet bekleniyordu"),
+        }
+    }
+
+    fn ipv4_payload<'a>(inner: Inner<'a>) -> &'a [u8] {
+        match inner {
+            Inner::Network(NetworkLayer::Ipv4(data)) => data,
+            _ => panic!("IPv4 bekleniyordu"),
+        }
+    }
+
+    fn ipv6_payload<'a>(inner: Inner<'a>) -> &'a [u8] {
+        match inner {
+            Inner::Network(NetworkLayer::Ipv6(data)) => data,
+            _ => panic!("IPv6 bekleniyordu"),
+        }
+    }
+
+    #[test]
+    fn gre_without_key_carries_ip() {
+        let packet = outer(47, &gre(0, ETHERTYPE_IPV4, &[], &inner_v4()));
+        let (tunnel, inner) = decapsulate_v4(&packet).unwrap();
+        assert_eq!(tunnel.kind, TunnelKind::Gre);
+        assert_eq!(tunnel.src, IpAddr::V4(OUTER_SRC));
+        assert_eq!(tunnel.dst, IpAddr::V4(OUTER_DST));
+        assert_eq!(tunnel.id, None);
+        assert_eq!(ipv4_payload(inner), inner_v4());
+    }
+
+    #[test]
+    fn gre_key_follows_checksum_and_precedes_sequence() {
+        // Sağlama toplamı, anahtar ve sıra numarası
+        let packet = outer(47, &gre(0xb000, ETHERTYPE_TEB, &[0xffff_0000, 4242, 17], &inner_frame()));
+        let (tunnel, inner) = decapsulate_v4(&packet).unwrap();
+        assert_eq!(tunnel.kind, TunnelKind::Gre);
+        assert_eq!(tunnel.id, Some(4242));
+        assert_eq!(tunnel.kind.id_field(), "key");
+        assert_eq!(ethernet_payload(inner), inner_frame());
+
+        let packet = outer(47, &gre(0x2000, ETHERTYPE_IPV6, &[7], &[0x60; 40]));
+        let (tunnel, inner) = decapsulate_v4(&packet).unwrap();
+        assert_eq!(tunnel.id, Some(7));
+        assert_eq!(ipv6_payload(inner), [0x60; 40]);
+    }
+
+    #[test]
+    fn gre_routing_recursion_and_other_versions_are_rejected() {
+        for flags in [0x4000, 0x0800, 0x0100, 0x0001, 0x3081] {
+            let packet = outer(47, &gre(flags, ETHERTYPE_IPV4, &[0, 0], &inner_v4()));
+            assert!(decapsulate_v4(&packet).is_none(), "{:#06x}", flags);
+        }
+        // Bilinmeyen yük türü
+        assert!(decapsulate_v4(&outer(47, &gre(0, 0x880b, &[], &inner_v4()))).is_none());
+    }
+
+    #[test]
+    fn erspan_session_id_comes_from_its_header() {
+        // Tip II: sürüm 1, VLAN 0, COS/En/T ve 10 bitlik oturum kimliği
+        let mut erspan = vec![0x10, 0x00, 0x0c, 0x2a, 0, 0, 0, 0];
+        erspan.extend(inner_frame());
+        let packet = outer(47, &gre(0x1000, ETHERTYPE_ERSPAN_II, &[1], &erspan));
+        let (tunnel, inner) = decapsulate_v4(&packet).unwrap();
+        assert_eq!(tunnel.kind, TunnelKind::Erspan);
+        assert_eq!(tunnel.id, Some(0x02a));
+        assert_eq!(tunnel.kind.id_field(), "session_id");
+        assert_eq!(ethernet_payload(inner), inner_frame());
+    }
+
+    #[test]
+    fn vxlan_on_both_ports_requires_the_vni_flag() {
+        for port in VXLAN_PORTS {
+            let packet = outer(17, &udp(50000, port, &vxlan(5000, &inner_frame())));
+            let (tunnel, inner) = decapsulate_v4(&packet).unwrap();
+            assert_eq!(tunnel.kind, TunnelKind::Vxlan);
+            assert_eq!(tunnel.id, Some(5000));
+            assert_eq!(ethernet_payload(inner), inner_frame());
+        }
+
+        let mut header = vxlan(5000, &inner_frame());
+        header[0] = 0;
+        assert!(decapsulate_v4(&outer(17, &udp(50000, 4789, &header))).is_none());
+        // Başka porttaki UDP tünel değildir
+        assert!(decapsulate_v4(&outer(17, &udp(50000, 4790, &vxlan(5000, &inner_frame())))).is_none());
+    }
+
+    #[test]
+    fn geneve_skips_options() {
+        let geneve = |first: u8, protocol: u16, options: &[u8], payload: &[u8]| {
+            let mut header = vec![first, 0];
+            header.extend_from_slice(&protocol.to_be_bytes());
+            header.extend_from_slice(&(77u32 << 8).to_be_bytes());
+            header.extend_from_slice(options);
+            header.extend_from_slice(payload);
+            outer(17, &udp(50000, GENEVE_PORT, &header))
+        };
+        // İki 4 baytlık sözcük seçenek
+        let options = [0x01, 0x02, 0x80, 0x01, 0xde, 0xad, 0xbe, 0xef];
+        let packet = geneve(2, ETHERTYPE_TEB, &options, &inner_frame());
+        let (tunnel, inner) = decapsulate_v4(&packet).unwrap();
+        assert_eq!(tunnel.kind, TunnelKind::Geneve);
+        assert_eq!(tunnel.id, Some(77));
+        assert_eq!(tunnel.kind.id_field(), "vni");
+        assert_eq!(ethernet_payload(inner), inner_frame());
+
+        let packet = geneve(0, ETHERTYPE_IPV4, &[], &inner_v4());
+        assert_eq!(ipv4_payload(decapsulate_v4(&packet).unwrap().1), inner_v4());
+
+        // Sürüm 1 ve yükten uzun seçenekler
+        assert!(decapsulate_v4(&geneve(0x40, ETHERTYPE_TEB, &[], &inner_frame())).is_none());
+        assert!(decapsulate_v4(&geneve(0x3f, ETHERTYPE_TEB, &options, &[])).is_none());
+    }
+
+    #[test]
+    fn ip_in_ip_and_6in4() {
+        let packet = outer(4, &inner_v4());
+        let (tunnel, inner) = decapsulate_v4(&packet).unwrap();
+        assert_eq!(tunnel.kind, TunnelKind::IpInIp);
+        assert_eq!(tunnel.id, None);
+        assert_eq!(ipv4_payload(inner), inner_v4());
+
+        let client: Ipv6Addr = "2001:db8::1".parse().unwrap();
+        let server: Ipv6Addr = "2a00:1450::1".parse().unwrap();
+        let inner_v6 = ipv6(client, server, 17, &udp(40000, 443, &[]));
+        let packet = outer(41, &inner_v6);
+        let (tunnel, inner) = decapsulate_v4(&packet).unwrap();
+        assert_eq!(tunnel.kind, TunnelKind::SixInFour);
+        assert_eq!(tunnel.kind.as_str(), "6in4");
+        assert_eq!(ipv6_payload(inner), inner_v6);
+
+        // IPv6 içinde IPv6 6in4 değildir
+        let packet = ipv6(server, client, 41, &inner_v6);
+        let ip = decode::decode_ip(NetworkLayer::Ipv6(&packet)).unwrap();
+        assert_eq!(decapsulate(&ip).unwrap().0.kind, TunnelKind::IpInIp);
+    }
+
+    #[test]
+    fn nested_tunnels_open_one_layer_at_a_time() {
+        // GRE içinde VXLAN
+        let (vtep_a, vtep_b) = (Ipv4Addr::new(172, 16, 0, 1), Ipv4Addr::new(172, 16, 0, 2));
+        let vxlan_packet = ipv4(vtep_a, vtep_b, 17, &udp(50000, 4789, &vxlan(9, &inner_frame())));
+        let packet = outer(47, &gre(0x2000, ETHERTYPE_IPV4, &[1], &vxlan_packet));
+
+        let (first, inner) = decapsulate_v4(&packet).unwrap();
+        assert_eq!((first.kind, first.id), (TunnelKind::Gre, Some(1)));
+        let (second, inner) = decapsulate_v4(ipv4_payload(inner)).unwrap();
+        assert_eq!((second.kind, second.id), (TunnelKind::Vxlan, Some(9)));
+        assert_eq!(second.src, IpAddr::V4(vtep_a));
+        let frame = decode::decode_ethernet(ethernet_payload(inner)).unwrap();
+        let ip = decode::decode_ip(frame.network).unwrap();
+        assert!(decapsulate(&ip).is_none());
+    }
+
+    #[test]
+    fn truncated_headers_are_not_tunnels() {
+        let keyed = gre(0x2000, ETHERTYPE_IPV4, &[1], &[]);
+        assert!(decapsulate_v4(&outer(47, &keyed[..6])).is_none());
+        assert!(decapsulate_v4(&outer(47, &[0, 0, 0x08])).is_none());
+        let erspan = gre(0x1000, ETHERTYPE_ERSPAN_II, &[1], &[0x10, 0, 0, 1]);
+        assert!(decapsulate_v4(&outer(47, &erspan)).is_none());
+        assert!(decapsulate_v4(&outer(17, &udp(50000, 4789, &[0x08, 0, 0, 0, 0]))).is_none());
+        assert!(decapsulate_v4(&outer(17, &udp(50000, GENEVE_PORT, &[0, 0, 0x65]))).is_none());
+    }
+}
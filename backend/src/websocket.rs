@@ -1,66 +1,246 @@
 use tokio::net::{TcpListener, TcpStream};
-use tokio::sync::broadcast;
+use tokio::sync::{broadcast, watch};
 use tokio_tungstenite::{
     accept_async,
     tungstenite::protocol::Message,
 };
 use futures::{StreamExt, SinkExt};
-use serde_json::json;
+use serde_json::{json, Value};
+use std::collections::HashSet;
+use std::net::{IpAddr, SocketAddr};
+use std::sync::Arc;
 
-pub async fn start_websocket_server(tx: broadcast::Sender<String>) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+use crate::filter::{Filter, Subject};
+use crate::flow::Direction;
+use crate::stats::Stats;
+
+/// İstemcinin `set_filter` mesajıyla seçtiği filtre. Akışlar `flow_start`
+/// olayında değerlendirilir; sonraki olaylar akış kimliğiyle eşleştirilir.
+struct ClientFilter {
+    filter: Filter,
+    flows: HashSet<u64>,
+}
+
+impl ClientFilter {
+    fn new(filter: Filter) -> Self {
+        Self { filter, flows: HashSet::new() }
+    }
+
+    /// Mesaj istemciye gidecekse (gerekirse süzülmüş haliyle) döndürür.
+    fn apply(&mut self, msg: String) -> Option<String> {
+        let event: Value = match serde_json::from_str(&msg) {
+            Ok(event) => event,
+            Err(_) => return Some(msg),
+        };
+
+        // Yeni akışlar dizi olarak gelir
+        if let Some(items) = event.as_array() {
+            let total = items.len();
+            let kept: Vec<&Value> = items.iter().filter(|item| self.allows_start(item)).collect();
+            return match kept.len() {
+                0 => None,
+                n if n == total => Some(msg),
+                _ => Some(Value::from(kept.into_iter().cloned().collect::<Vec<_>>()).to_string()),
+            };
+        }
+
+        match event["flow_id"].as_u64() {
+            Some(flow_id) => {
+                let known = self.flows.contains(&flow_id);
+                if event["type"] == "flow_end" {
+                    self.flows.remove(&flow_id);
+                }
+                known.then_some(msg)
+            }
+            None if event["type"] == "icmp_error" => icmp_subject(&event)
+                .filter(|subject| self.filter.matches(subject))
+                .map(|_| msg),
+            // Akışa ait olmayan diğer mesajlar (durum, istatistik ...) süzülmez
+            None => Some(msg),
+        }
+    }
+
+    fn allows_start(&mut self, item: &Value) -> bool {
+        let flow_id = match item["flow_id"].as_u64() {
+            Some(flow_id) => flow_id,
+            // Akış kimliği taşımayan kayıtlar süzülmez
+            None => return true,
+        };
+        let allowed = flow_subject(item).is_some_and(|subject| self.filter.matches(&subject));
+        if allowed {
+            self.flows.insert(flow_id);
+        }
+        allowed
+    }
+}
+
+fn protocol_number(name: &str) -> u8 {
+    match name {
+        "tcp" => 6,
+        "udp" => 17,
+        "icmp" => 1,
+        "icmpv6" => 58,
+        _ => 0,
+    }
+}
+
+fn ip_field(value: &Value) -> Option<IpAddr> {
+    value.as_str()?.parse().ok()
+}
+
+fn flow_subject(item: &Value) -> Option<Subject> {
+    let protocol = protocol_number(item["protocol"].as_str().unwrap_or_default());
+    let has_ports = protocol == 6 || protocol == 17;
+    let port = |end: &Value| if has_ports { end["port"].as_u64().map(|port| port as u16) } else { None };
+    let country = |end: &Value| end["country"].as_str().map(str::to_string);
+    let direction = match item["direction"].as_str() {
+        Some("inbound") => Some(Direction::Inbound),
+        Some("outbound") => Some(Direction::Outbound),
+        Some("transit") => Some(Direction::Transit),
+        _ => None,
+    };
+    let tunnels = item["tunnels"].as_array().cloned().unwrap_or_default();
+
+    Some(Subject {
+        protocol,
+        src: ip_field(&item["source"]["ip"])?,
+        dst: ip_field(&item["destination"]["ip"])?,
+        src_port: port(&item["source"]),
+        dst_port: port(&item["destination"]),
+        src_country: country(&item["source"]),
+        dst_country: country(&item["destination"]),
+        direction,
+        vlan_ids: item["vlan_ids"]
+            .as_array()
+            .map(|ids| ids.iter().filter_map(|id| id.as_u64().map(|id| id as u16)).collect())
+            .unwrap_or_default(),
+        tunneled: !tunnels.is_empty(),
+        tunnel_ids: tunnels
+            .iter()
+            .filter_map(|tunnel| ["vni", "key", "session_id"].iter().find_map(|field| tunnel[*field].as_u64()))
+            .map(|id| id as u32)
+            .collect(),
+    })
+}
+
+/// Akışa bağlanamayan ICMP hatası: raporlayandan hatayı alan uca
+fn icmp_subject(event: &Value) -> Option<Subject> {
+    let reporter = ip_field(&event["reporter"]["ip"])?;
+    let receiver = ip_field(&event["original"]["source"]["ip"]).unwrap_or(reporter);
+
+    Some(Subject {
+        protocol: if event["ip_version"] == 6 { 58 } else { 1 },
+        src: reporter,
+        dst: receiver,
+        src_port: None,
+        dst_port: None,
+        src_country: event["reporter"]["country"].as_str().map(str::to_string),
+        dst_country: None,
+        direction: None,
+        vlan_ids: Vec::new(),
+        tunneled: false,
+        tunnel_ids: Vec::new(),
+    })
+}
+
+/// `{"type": "set_filter", "filter": "..."}` mesajını işler; boş filtre
+/// süzmeyi kapatır. İstemciye gönderilecek yanıtı döndürür.
+fn handle_set_filter(request: &Value, filter_tx: &watch::Sender<Option<Filter>>) -> Value {
+    let text = request["filter"].as_str().unwrap_or_default().trim();
+    if text.is_empty() {
+        let _ = filter_tx.send(None);
+        return json!({ "type": "filter_status", "ok": true, "filter": null });
+    }
+
+    match Filter::parse(text) {
+        Ok(filter) => {
+            let status = json!({ "type": "filter_status", "ok": true, "filter": filter.as_str() });
+            let _ = filter_tx.send(Some(filter));
+            status
+        }
+        Err(e) => json!({
+            "type": "filter_status",
+            "ok": false,
+            "filter": text,
+            "error": e.message,
+            "position": e.position
+        }),
+    }
+}
+
+/// `status` yakalamanın son durumunu taşır; yeni bağlanan istemcilere ilk
+/// olarak gönderilir.
+pub async fn start_websocket_server(
+    tx: broadcast::Sender<String>,
+    status: watch::Receiver<String>,
+    stats: Arc<Stats>,
+) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
     let addr = "127.0.0.1:8080";
     let listener = TcpListener::bind(addr).await?;
     println!("WebSocket sunucusu başlatıldı: {}", addr);
 
     while let Ok((stream, addr)) = listener.accept().await {
         println!("Yeni WebSocket bağlantısı: {}", addr);
-        let tx = tx.clone();
-        
-        tokio::spawn(async move {
-            if let Err(e) = handle_connection(stream, tx).await {
-                eprintln!("Bağlantı hatası {}: {}", addr, e);
-            }
-        });
+        tokio::spawn(serve_client(stream, addr, tx.clone(), status.clone(), stats.clone()));
     }
 
     Ok(())
 }
 
-async fn handle_connection(stream: TcpStream, tx: broadcast::Sender<String>) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+/// Bağlantıyı kapanana kadar sürdürür; `clients` göstergesi bağlantı
+/// süresince artırılmış kalır.
+async fn serve_client(
+    stream: TcpStream,
+    addr: SocketAddr,
+    tx: broadcast::Sender<String>,
+    status: watch::Receiver<String>,
+    stats: Arc<Stats>,
+) {
+    stats.websocket.connections.inc();
+    stats.websocket.clients.inc();
+    if let Err(e) = handle_connection(stream, tx, status, &stats).await {
+        eprintln!("Bağlantı hatası {}: {}", addr, e);
+    }
+    stats.websocket.clients.dec();
+}
+
+async fn handle_connection(
+    stream: TcpStream,
+    tx: broadcast::Sender<String>,
+    status: watch::Receiver<String>,
+    stats: &Arc<Stats>,
+) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
     let ws_stream = accept_async(stream).await?;
     println!("WebSocket el sıkışması tamamlandı");
 
     let (mut write, mut read) = ws_stream.split();
     let mut rx = tx.subscribe();
 
-    // Başlangıç test mesajı
-    let test_data = json!([{
-        "source": {
-            "ip": "8.8.8.8",  // Google DNS
-            "port": 53,
-            "latitude": 37.751,
-            "longitude": -97.822
-        },
-        "destination": {
-            "ip": "8.8.4.4",  // Google DNS
-            "port": 53,
-            "latitude": 37.751,
-            "longitude": -97.822
-        }
-    }]);
-
-    write.send(Message::Text(test_data.to_string())).await?;
-    println!("Test verisi gönderildi");
+    // Sonraki değişiklikler broadcast üzerinden gelir
+    let current_status = status.borrow().clone();
+    write.send(Message::Text(current_status)).await?;
 
     // İki task oluştur: biri okuma, diğeri yazma için
     let (tx1, mut rx1) = tokio::sync::mpsc::channel(32);
-    
+    let (filter_tx, mut filter_rx) = watch::channel(None);
+    let reply_tx = tx1.clone();
+    let read_stats = stats.clone();
+
     // Okuma task'ı
-    let read_task = tokio::spawn(async move {
+    let mut read_task = tokio::spawn(async move {
         while let Some(msg) = read.next().await {
             match msg {
                 Ok(Message::Text(text)) => {
                     println!("İstemciden mesaj alındı: {}", text);
+                    let request: Value = serde_json::from_str(&text).unwrap_or_default();
+                    let reply = match request["type"].as_str() {
+                        Some("set_filter") => handle_set_filter(&request, &filter_tx),
+                        Some("get_stats") => read_stats.to_json(),
+                        _ => continue,
+                    };
+                    if reply_tx.send(reply.to_string()).await.is_err() {
+                        break;
+                    }
                 }
                 Ok(Message::Close(_)) => {
                     println!("Bağlantı kapatma isteği alındı");
@@ -86,9 +266,41 @@ async fn handle_connection(stream: TcpStream, tx: broadcast::Sender<String>) ->
     });
 
     // Broadcast kanalından gelen mesajları işle
-    while let Ok(msg) = rx.recv().await {
+    let mut client_filter: Option<ClientFilter> = None;
+    loop {
+        let msg = tokio::select! {
+            msg = rx.recv() => match msg {
+                Ok(msg) => msg,
+                // İstemci yetişemediyse kaçırdığı mesajlar atlanır, bağlantı sürer
+                Err(broadcast::error::RecvError::Lagged(skipped)) => {
+                    eprintln!("İstemci geride kaldı, {} mesaj atlandı", skipped);
+                    stats.websocket.lagged.add(skipped);
+                    continue;
+                }
+                Err(broadcast::error::RecvError::Closed) => break,
+            },
+            // İstemci ayrıldı; yayın akışı durgunken de bağlantı hemen kapansın
+            _ = &mut read_task => break,
+        };
         println!("Broadcast'ten mesaj alındı: {}", msg);
+
+        // Filtre değiştiyse önceki akış eşleşmeleri geçersiz
+        if filter_rx.has_changed().unwrap_or(false) {
+            client_filter = filter_rx.borrow_and_update().clone().map(ClientFilter::new);
+        }
+        let msg = match client_filter.as_mut() {
+            Some(client_filter) => match client_filter.apply(msg) {
+                Some(msg) => msg,
+                None => {
+                    stats.websocket.filtered.inc();
+                    continue;
+                }
+            },
+            None => msg,
+        };
+
         tx1.send(msg).await?;
+        stats.websocket.sent.inc();
     }
 
     // Task'ları temizle
@@ -97,4 +309,43 @@ async fn handle_connection(stream: TcpStream, tx: broadcast::Sender<String>) ->
 
     println!("WebSocket bağlantısı kapandı");
     Ok(())
-} 
\ No newline at end of file
+} 
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+    use tokio_tungstenite::connect_async;
+
+    #[tokio::test]
+    async fn client_gauge_drops_when_client_leaves_without_broadcasts() {
+        let (tx, _) = broadcast::channel(16);
+        let (_status_tx, status) = watch::channel(r#"{"type":"capture_status"}"#.to_string());
+        let stats = Arc::new(Stats::default());
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+
+        let server = {
+            let (tx, stats) = (tx.clone(), stats.clone());
+            tokio::spawn(async move {
+                let (stream, peer) = listener.accept().await.unwrap();
+                serve_client(stream, peer, tx, status, stats).await;
+            })
+        };
+
+        let (mut client, _) = connect_async(format!("ws://{}", addr)).await.unwrap();
+        let first = client.next().await.unwrap().unwrap();
+        assert_eq!(first, Message::Text(r#"{"type":"capture_status"}"#.to_string()));
+
+        client.send(Message::Text(r#"{"type":"get_stats"}"#.to_string())).await.unwrap();
+        let reply = client.next().await.unwrap().unwrap().into_text().unwrap();
+        let reply: Value = serde_json::from_str(&reply).unwrap();
+        assert_eq!(reply["websocket"]["clients"], 1);
+
+        // Yayın kanalı açık ama sessizken istemci ayrılıyor
+        client.close(None).await.unwrap();
+        tokio::time::timeout(Duration::from_secs(5), server).await.expect("bağlantı kapanmadı").unwrap();
+        assert_eq!(stats.websocket.clients.get(), 0);
+        assert_eq!(stats.websocket.connections.get(), 1);
+        drop(tx);
+    }
+}
@@ -1,10 +1,18 @@
 import React, { useState, useEffect } from 'react';
 import Map from './Map';
 
+// Haritada tutulacak en fazla ICMP hata olayı
+const MAX_ICMP_EVENTS = 100;
+
 function App() {
     const [connections, setConnections] = useState([]);
+    const [icmpEvents, setIcmpEvents] = useState([]);
     const [error, setError] = useState(null);
     const [wsInstance, setWsInstance] = useState(null);
+    const [filterText, setFilterText] = useState('');
+    const [filterError, setFilterError] = useState(null);
+    const [captureStats, setCaptureStats] = useState({});
+    const [captureStatus, setCaptureStatus] = useState(null);
 
     useEffect(() => {
         let ws = null;
@@ -26,6 +34,35 @@ function App() {
                         console.log('Gelen veri:', data);
                         if (Array.isArray(data)) {
                             setConnections(prevConnections => [...prevConnections, ...data]);
+                        } else if (data.type === 'flow_update' || data.type?.startsWith('connection_') || data.type?.startsWith('tcp_') || data.type?.startsWith('tls_')) {
+                            // Sayaçları ve TCP durumunu güncelle, çizgi kalınlığı trafiğe göre değişsin
+                            setConnections(prevConnections =>
+                                prevConnections.map(connection =>
+                                    connection.flow_id === data.flow_id ? { ...connection, ...data, type: connection.type } : connection
+                                )
+                            );
+                        } else if (data.type === 'filter_status') {
+                            if (data.ok) {
+                                // Yeni filtreden önceki akışların güncellemeleri artık gelmez
+                                setConnections([]);
+                                setIcmpEvents([]);
+                                setFilterError(null);
+                            } else {
+                                setFilterError(`${data.position}. karakter: ${data.error}`);
+                            }
+                        } else if (data.type === 'capture_stats') {
+                            // Fanout'ta her soket ayrı sayaç bildirir
+                            setCaptureStats(prevStats => ({ ...prevStats, [`${data.source}/${data.worker}`]: data }));
+                        } else if (data.type === 'capture_status') {
+                            setCaptureStatus(data);
+                        } else if (data.type === 'icmp_error') {
+                            // ICMP hataları bağlantılardan ayrı bir katmanda gösterilir
+                            setIcmpEvents(prevEvents => [...prevEvents, data].slice(-MAX_ICMP_EVENTS));
+                        } else if (data.type === 'flow_end') {
+                            // Sona eren akışı haritadan kaldır
+                            setConnections(prevConnections =>
+                                prevConnections.filter(connection => connection.flow_id !== data.flow_id)
+                            );
                         }
                     } catch (err) {
                         console.error('Veri işleme hatası:', err);
@@ -61,15 +98,56 @@ function App() {
         };
     }, []);
 
+    const kernelDropped = Object.values(captureStats).reduce((total, stats) => total + stats.kernel_dropped, 0);
+    const kernelReceived = Object.values(captureStats).reduce((total, stats) => total + stats.kernel_received, 0);
+
+    const statusText = {
+        starting: 'Yakalama başlatılıyor',
+        waiting_for_interface: 'Arayüz bekleniyor',
+        failed: 'Yakalama başarısız',
+        finished: 'Kayıt oynatma tamamlandı',
+        stopped: 'Yakalama durduruldu',
+    };
+
+    const applyFilter = (event) => {
+        event.preventDefault();
+        if (wsInstance && wsInstance.readyState === WebSocket.OPEN) {
+            wsInstance.send(JSON.stringify({ type: 'set_filter', filter: filterText }));
+        }
+    };
+
     return (
         <div style={{ height: '100vh', width: '100%' }}>
+            <form onSubmit={applyFilter} style={{ position: 'absolute', top: 10, left: 60, zIndex: 1000, background: 'white', padding: '6px' }}>
+                <input
+                    value={filterText}
+                    onChange={(e) => setFilterText(e.target.value)}
+                    placeholder="tcp and port 443 and country != TR"
+                    style={{ width: '320px' }}
+                />
+                <button type="submit">Filtrele</button>
+                {filterError && <div style={{ color: 'red' }}>{filterError}</div>}
+            </form>
             {error && (
                 <div style={{ position: 'absolute', top: 10, right: 10, zIndex: 1000, background: 'red', color: 'white', padding: '10px' }}>
                     <div>Hata: {error}</div>
                     <div>Yeniden bağlanmaya çalışılıyor...</div>
                 </div>
             )}
-            <Map connections={connections} />
+            {captureStatus && captureStatus.state !== 'running' && (
+                <div style={{ position: 'absolute', top: 60, right: 10, zIndex: 1000, background: captureStatus.state === 'failed' ? 'red' : 'gold', padding: '10px' }}>
+                    <div>{statusText[captureStatus.state] || captureStatus.state}{captureStatus.reason && `: ${captureStatus.reason}`}</div>
+                    {captureStatus.retry_in_ms != null && (
+                        <div>{Math.ceil(captureStatus.retry_in_ms / 1000)} sn sonra tekrar denenecek</div>
+                    )}
+                </div>
+            )}
+            {kernelDropped > 0 && (
+                <div style={{ position: 'absolute', bottom: 20, left: 10, zIndex: 1000, background: 'orange', padding: '6px' }}>
+                    Çekirdekte düşen paket: {kernelDropped} / {kernelReceived}
+                </div>
+            )}
+            <Map connections={connections} icmpEvents={icmpEvents} />
         </div>
     );
 }
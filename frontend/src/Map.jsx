@@ -1,5 +1,5 @@
 import React, { Component } from 'react';
-import { MapContainer, TileLayer, Marker, Popup, Polyline } from 'react-leaflet';
+import { MapContainer, TileLayer, Marker, Popup, Polyline, CircleMarker } from 'react-leaflet';
 import 'leaflet/dist/leaflet.css';
 
 class Map extends Component {
@@ -13,6 +13,7 @@ class Map extends Component {
 
     render() {
         const connections = Array.isArray(this.props.connections) ? this.props.connections : [];
+        const icmpEvents = Array.isArray(this.props.icmpEvents) ? this.props.icmpEvents : [];
 
         return (
             <MapContainer center={[0, 0]} zoom={2} style={{ height: '100vh', width: '100%' }}>
@@ -23,30 +24,66 @@ class Map extends Component {
                 {connections.map((connection, index) => {
                     const sourcePosition = [connection.source.latitude, connection.source.longitude];
                     const destPosition = [connection.destination.latitude, connection.destination.longitude];
+                    const bytes = (connection.source_to_destination?.bytes || 0) + (connection.destination_to_source?.bytes || 0);
+                    // Başlangıçtan sonra öğrenilen adlar güncellemelerle gelir
+                    const sourceHost = connection.hostnames?.source ?? connection.source.hostname;
+                    const destHost = connection.hostnames?.destination ?? connection.destination.hostname;
 
                     return (
                         <React.Fragment key={index}>
                             <Marker position={sourcePosition}>
                                 <Popup>
+                                    {sourceHost && (<>Host: {sourceHost}<br /></>)}
                                     Source IP: {connection.source.ip}<br />
                                     Port: {connection.source.port}
+                                    {connection.interface && (
+                                        <><br />Interface: {connection.interface}</>
+                                    )}
                                 </Popup>
                             </Marker>
                             <Marker position={destPosition}>
                                 <Popup>
+                                    {destHost && (<>Host: {destHost}<br /></>)}
                                     Destination IP: {connection.destination.ip}<br />
                                     Port: {connection.destination.port}
+                                    {connection.tls?.sni && (
+                                        <><br />SNI: {connection.tls.sni}</>
+                                    )}
+                                    {connection.tls && (
+                                        <><br />TLS: {connection.tls.version}{connection.tls.alpn.length > 0 && ` (${connection.tls.alpn.join(', ')})`}</>
+                                    )}
+                                    {connection.tcp?.handshake_rtt_ms != null && (
+                                        <><br />RTT: {connection.tcp.handshake_rtt_ms.toFixed(1)} ms</>
+                                    )}
                                 </Popup>
                             </Marker>
                             <Polyline 
                                 positions={[sourcePosition, destPosition]}
-                                color="red"
-                                weight={1}
+                                color={connection.protocol?.startsWith('icmp') ? 'blue' : 'red'}
+                                weight={1 + Math.log10(1 + bytes)}
                                 opacity={0.5}
                             />
                         </React.Fragment>
                     );
                 })}
+                {icmpEvents
+                    .filter(event => event.reporter.latitude != null && event.reporter.longitude != null)
+                    .map((event, index) => (
+                        <CircleMarker
+                            key={`icmp-${index}`}
+                            center={[event.reporter.latitude, event.reporter.longitude]}
+                            radius={6}
+                            color="orange"
+                        >
+                            <Popup>
+                                ICMP: {event.description} (type {event.icmp_type}, code {event.icmp_code})<br />
+                                Reporter: {event.reporter.ip}
+                                {event.original && (
+                                    <><br />Original: {event.original.source.ip} → {event.original.destination.ip}</>
+                                )}
+                            </Popup>
+                        </CircleMarker>
+                    ))}
             </MapContainer>
         );
     }